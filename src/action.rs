@@ -0,0 +1,64 @@
+/// A reusable piece of robot code, like running an intake or scoring, with named parameters.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Action {
+    pub name: String,
+    pub params: Vec<String>,
+    /// Code with `{param}` placeholders for each parameter
+    pub template: String,
+}
+
+/// An action attached to a point, with a value for each of its parameters.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct Instance {
+    /// Name of the action in the library
+    pub action: String,
+    pub args: Vec<String>,
+}
+
+impl Action {
+    /// The action's code with each placeholder replaced by its value.
+    /// Missing values are left empty.
+    pub fn expand(&self, args: &[String]) -> String {
+        self.params
+            .iter()
+            .enumerate()
+            .fold(self.template.clone(), |code, (i, param)| {
+                code.replace(
+                    &format!("{{{param}}}"),
+                    args.get(i).map_or("", String::as_str),
+                )
+            })
+    }
+}
+
+/// Name of the library action run at sync markers, given the marker's name.
+pub const SYNC: &str = "Sync";
+
+/// Code waiting at a sync marker: the library's [`SYNC`] action if there is one,
+/// or a call to a `waitForPartner` function the robot code provides.
+pub fn sync(library: &[Action], marker: &str) -> String {
+    let instance = Instance {
+        action: SYNC.into(),
+        args: vec![marker.into()],
+    };
+    if library.iter().any(|a| a.name == SYNC) {
+        expand(library, &instance)
+    } else {
+        format!("waitForPartner({marker:?});\n")
+    }
+}
+
+/// Expands an attached action using the library, ending in a newline.
+pub fn expand(library: &[Action], instance: &Instance) -> String {
+    match library.iter().find(|a| a.name == instance.action) {
+        Some(action) => {
+            let code = action.expand(&instance.args);
+            if code.ends_with('\n') {
+                code
+            } else {
+                code + "\n"
+            }
+        }
+        None => format!("// Unknown action {}\n", instance.action),
+    }
+}
@@ -0,0 +1,342 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::bezier::{interpolate, BezPoint};
+use crate::transform::normalize;
+use egui::{pos2, vec2, Pos2, Vec2};
+
+/// Shortest fitted line that has a direction, in inches.
+const MIN_LINE: f32 = 1e-3;
+
+/// An ideal shape a path can be compared against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Line,
+    Arc,
+}
+
+/// A fitted primitive in field coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Line {
+        start: Pos2,
+        end: Pos2,
+    },
+    /// Angles are in radians, `sweep` is signed.
+    Arc {
+        center: Pos2,
+        radius: f32,
+        start: f32,
+        sweep: f32,
+    },
+}
+
+/// How well a path matches a primitive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fit {
+    pub shape: Shape,
+    /// Root mean square distance from the shape
+    pub rms: f32,
+    /// Largest distance from the shape
+    pub max: f32,
+}
+
+impl Shape {
+    /// Distance from a position to the shape's outline.
+    fn distance(&self, p: Pos2) -> f32 {
+        match *self {
+            Shape::Line { start, end } => {
+                let dir = end - start;
+                if dir.length() < MIN_LINE {
+                    return p.distance(start);
+                }
+                let dir = dir.normalized();
+                let v = p - start;
+                (v.x * dir.y - v.y * dir.x).abs()
+            }
+            Shape::Arc { center, radius, .. } => (p.distance(center) - radius).abs(),
+        }
+    }
+    /// Points along the shape, for drawing.
+    pub fn outline(&self, n: usize) -> Vec<Pos2> {
+        match *self {
+            Shape::Line { start, end } => vec![start, end],
+            Shape::Arc {
+                center,
+                radius,
+                start,
+                sweep,
+            } => (0..=n)
+                .map(|i| {
+                    let angle = start + sweep * i as f32 / n as f32;
+                    center + radius * egui::vec2(angle.cos(), angle.sin())
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Samples positions along a whole path, `steps` per segment.
+pub fn sample(points: &[Rc<RefCell<BezPoint>>], steps: usize) -> Vec<Pos2> {
+    let mut samples = Vec::new();
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0].borrow(), pair[1].borrow());
+        for i in 0..steps {
+            samples.push(interpolate(&a, &b, i as f32 / steps as f32).into());
+        }
+    }
+    if let Some(last) = points.last() {
+        samples.push(last.borrow().pos.borrow().clone().into());
+    }
+    samples
+}
+
+/// Distance from `p` to the nearest part of the line through `line`'s positions.
+pub fn distance_to(line: &[Pos2], p: Pos2) -> f32 {
+    match line {
+        [] => f32::INFINITY,
+        [only] => only.distance(p),
+        _ => line
+            .windows(2)
+            .map(|seg| {
+                let (a, b) = (seg[0], seg[1]);
+                let ab = b - a;
+                let t = if ab.length_sq() > 0.0 {
+                    ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (a + ab * t).distance(p)
+            })
+            .fold(f32::INFINITY, f32::min),
+    }
+}
+
+/// How the target heading changes between points with a set heading.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeadingMode {
+    /// Face the direction of travel, ignoring set headings
+    Tangent,
+    /// Turn at a steady rate per second, by when the robot reaches each point as driven
+    Time,
+    /// Turn at a steady rate per inch travelled
+    #[default]
+    Distance,
+}
+
+impl HeadingMode {
+    pub const ALL: [HeadingMode; 3] = [
+        HeadingMode::Tangent,
+        HeadingMode::Time,
+        HeadingMode::Distance,
+    ];
+}
+
+/// Samples the target heading in degrees along a path, `steps` per segment, at the same
+/// positions as [`sample`]. Before the first and after the last set heading it is held.
+/// Without set headings the robot faces the direction of travel, or away from it on reversed segments.
+/// `Time` mode turns over `times`, when the robot reaches each sample in seconds, which
+/// [`crate::sim::headings`] works out. Without them it turns by distance.
+pub fn headings(points: &[BezPoint], steps: usize, mode: HeadingMode, times: &[f32]) -> Vec<f32> {
+    let n = points.len();
+    if n < 2 || steps == 0 {
+        return Vec::new();
+    }
+    let count = (n - 1) * steps + 1;
+    let samples: Vec<Pos2> = (0..count)
+        .map(|k| {
+            let seg = (k / steps).min(n - 2);
+            let t = (k - seg * steps) as f32 / steps as f32;
+            interpolate(&points[seg], &points[seg + 1], t).into()
+        })
+        .collect();
+    // Where each sample is along the path, in the units headings are interpolated over
+    let coords: Vec<f32> = if mode == HeadingMode::Time && times.len() == count {
+        times.to_vec()
+    } else {
+        let mut lengths = vec![0.0];
+        for pair in samples.windows(2) {
+            lengths.push(lengths.last().unwrap() + pair[0].distance(pair[1]));
+        }
+        lengths
+    };
+    let keys: Vec<(f32, f32)> = points
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| p.heading.map(|h| (coords[i * steps], h)))
+        .collect();
+    if mode == HeadingMode::Tangent || keys.is_empty() {
+        return (0..count)
+            .map(|k| {
+                let (a, b) = if k + 1 < count {
+                    (k, k + 1)
+                } else {
+                    (k - 1, k)
+                };
+                let travel = (samples[b] - samples[a]).angle().to_degrees();
+                // Reversed segments are driven facing backwards
+                if points[a / steps].reverse {
+                    normalize(travel + 180.0)
+                } else {
+                    travel
+                }
+            })
+            .collect();
+    }
+    coords
+        .iter()
+        .map(|&c| {
+            let after = keys.partition_point(|(kc, _)| *kc <= c);
+            match (after.checked_sub(1).map(|i| keys[i]), keys.get(after)) {
+                (Some((ac, ah)), Some(&(bc, bh))) if bc > ac => {
+                    normalize(ah + normalize(bh - ah) * (c - ac) / (bc - ac))
+                }
+                (Some((_, h)), _) | (None, Some(&(_, h))) => h,
+                (None, None) => unreachable!(),
+            }
+        })
+        .collect()
+}
+
+/// The parameter of the point halfway along a segment by arc length.
+pub fn midpoint(a: &BezPoint, b: &BezPoint) -> f32 {
+    const STEPS: usize = 200;
+    let samples: Vec<Pos2> = (0..=STEPS)
+        .map(|i| interpolate(a, b, i as f32 / STEPS as f32).into())
+        .collect();
+    let mut lengths = vec![0.0];
+    for pair in samples.windows(2) {
+        lengths.push(lengths.last().unwrap() + pair[0].distance(pair[1]));
+    }
+    let half = lengths[STEPS] / 2.0;
+    let j = lengths.partition_point(|l| *l < half).clamp(1, STEPS);
+    let span = lengths[j] - lengths[j - 1];
+    let f = if span > 0.0 {
+        (half - lengths[j - 1]) / span
+    } else {
+        0.0
+    };
+    (j - 1) as f32 / STEPS as f32 + f / STEPS as f32
+}
+
+/// Samples the curvature along a whole path, `steps` per segment, for drawing a curvature comb.
+/// Each sample is a position and its curvature vector: the unit normal towards the center of
+/// curvature scaled by the curvature (1 / radius), so straight sections give zero vectors.
+pub fn curvature(points: &[Rc<RefCell<BezPoint>>], steps: usize) -> Vec<(Pos2, Vec2)> {
+    let mut samples = Vec::new();
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0].borrow(), pair[1].borrow());
+        let [p0, p1, p2, p3] =
+            [&a.pos, &a.cp2, &b.cp1, &b.pos].map(|p| Pos2::from(p.borrow().clone()));
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let vector = curvature_vector([p0, p1, p2, p3], t);
+            samples.push((interpolate(&a, &b, t).into(), vector));
+        }
+    }
+    samples
+}
+
+/// How sharply the segment from `a` to `b` curves `t` of the way along, in 1 / inches.
+pub fn curvature_at(a: &BezPoint, b: &BezPoint, t: f32) -> f32 {
+    let curve = [&a.pos, &a.cp2, &b.cp1, &b.pos].map(|p| Pos2::from(p.borrow().clone()));
+    curvature_vector(curve, t).length()
+}
+
+/// The curvature vector of a cubic at `t`: the unit normal towards the center of curvature
+/// scaled by the curvature.
+fn curvature_vector([p0, p1, p2, p3]: [Pos2; 4], t: f32) -> Vec2 {
+    let u = 1.0 - t;
+    let d1 = 3.0 * u * u * (p1 - p0) + 6.0 * u * t * (p2 - p1) + 3.0 * t * t * (p3 - p2);
+    let d2 = 6.0 * u * (p2 - p1 - (p1 - p0)) + 6.0 * t * (p3 - p2 - (p2 - p1));
+    let speed = d1.length();
+    if speed < 1e-6 {
+        Vec2::ZERO
+    } else {
+        let k = (d1.x * d2.y - d1.y * d2.x) / speed.powi(3);
+        k * vec2(-d1.y, d1.x) / speed
+    }
+}
+
+/// Fits a primitive to sampled positions by least squares.
+/// Returns `None` if there are too few samples or they are degenerate.
+pub fn fit(primitive: Primitive, samples: &[Pos2]) -> Option<Fit> {
+    if samples.len() < 3 {
+        return None;
+    }
+    let n = samples.len() as f32;
+    let cx = samples.iter().map(|p| p.x).sum::<f32>() / n;
+    let cy = samples.iter().map(|p| p.y).sum::<f32>() / n;
+    let shape = match primitive {
+        Primitive::Line => {
+            // Principal axis through the centroid
+            let (mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0);
+            for p in samples {
+                sxx += (p.x - cx).powi(2);
+                syy += (p.y - cy).powi(2);
+                sxy += (p.x - cx) * (p.y - cy);
+            }
+            let (sin, cos) = (0.5 * f32::atan2(2.0 * sxy, sxx - syy)).sin_cos();
+            let project = |p: &Pos2| (p.x - cx) * cos + (p.y - cy) * sin;
+            let (t0, t1) = (project(&samples[0]), project(samples.last()?));
+            // A path that ends where it started, like a loop, has no line to compare against
+            if (t1 - t0).abs() < MIN_LINE {
+                return None;
+            }
+            Shape::Line {
+                start: pos2(cx + cos * t0, cy + sin * t0),
+                end: pos2(cx + cos * t1, cy + sin * t1),
+            }
+        }
+        Primitive::Arc => {
+            // Algebraic (Kasa) circle fit about the centroid:
+            // minimise sum (u² + v² + D u + E v + F)²
+            let (mut suu, mut svv, mut suv) = (0.0, 0.0, 0.0);
+            let (mut suuu, mut svvv, mut suvv, mut svuu) = (0.0, 0.0, 0.0, 0.0);
+            for p in samples {
+                let (u, v) = (p.x - cx, p.y - cy);
+                suu += u * u;
+                svv += v * v;
+                suv += u * v;
+                suuu += u * u * u;
+                svvv += v * v * v;
+                suvv += u * v * v;
+                svuu += v * u * u;
+            }
+            let det = suu * svv - suv * suv;
+            if det.abs() < 1e-9 {
+                return None;
+            }
+            let bu = 0.5 * (suuu + suvv);
+            let bv = 0.5 * (svvv + svuu);
+            let uc = (bu * svv - bv * suv) / det;
+            let vc = (bv * suu - bu * suv) / det;
+            let center = pos2(cx + uc, cy + vc);
+            let radius = (uc * uc + vc * vc + (suu + svv) / n).sqrt();
+            // Unwrap the swept angle along the path
+            let angle = |p: &Pos2| f32::atan2(p.y - center.y, p.x - center.x);
+            let start = angle(&samples[0]);
+            let mut sweep = 0.0;
+            for pair in samples.windows(2) {
+                let mut delta = angle(&pair[1]) - angle(&pair[0]);
+                if delta > std::f32::consts::PI {
+                    delta -= std::f32::consts::TAU;
+                } else if delta < -std::f32::consts::PI {
+                    delta += std::f32::consts::TAU;
+                }
+                sweep += delta;
+            }
+            Shape::Arc {
+                center,
+                radius,
+                start,
+                sweep,
+            }
+        }
+    };
+    let distances: Vec<f32> = samples.iter().map(|p| shape.distance(*p)).collect();
+    Some(Fit {
+        shape,
+        rms: (distances.iter().map(|d| d * d).sum::<f32>() / n).sqrt(),
+        max: distances.iter().copied().fold(0.0, f32::max),
+    })
+}
@@ -1,11 +1,40 @@
 use std::{cell::RefCell, rc::Rc};
 
-use crate::bezier::{interpolate, BezPoint, Point, SavePoint};
-use crate::generate::generate;
-use egui::{pos2, Color32, FontDefinitions, FontFamily, Pos2, Stroke, TextEdit, Vec2};
+use crate::action::{Action, Instance};
+use crate::analysis::{self, Fit, HeadingMode, Primitive};
+use crate::bezier::{
+    interpolate, split, straighten, BezPoint, EndPose, HandleMode, Point, Pose, SavePoint, Wait,
+};
+use crate::constraint::{self, Constraint};
+use crate::expr::{self, Alliance, Variable};
+use crate::files::{self, Pick, Picked};
+use crate::gallery;
+use crate::generate::{generate, Driving};
+use crate::hooks::{self, Event};
+use crate::import;
+use crate::migrate;
+use crate::pattern::Pattern;
+use crate::preset::{self, Preset};
+use crate::recovery::{self, Corrupt, Snapshot};
+use crate::report::{Journal, Report};
+use crate::script;
+use crate::selection::{self, Filter};
+use crate::share;
+use crate::sim::{self, Profile, Robot, Timeline};
+use crate::sketch;
+use crate::stats;
+use crate::sync::{Backend, Part, Project, Transfer};
+use crate::toast::Toasts;
+use crate::transform::{self, Axis};
+use crate::validate;
+use base64::Engine;
+use egui::{
+    pos2, Color32, DragValue, FontDefinitions, FontFamily, Pos2, Stroke, TextEdit, Ui, Vec2,
+};
 #[allow(deprecated)]
 use egui_extras::RetainedImage;
 use std::sync::Arc;
+use uuid::Uuid;
 
 // Uncomment this section to get access to the console_log macro
 // Use console_log to print things to console. println macro doesn't work
@@ -29,6 +58,7 @@ extern "C" {
     fn log_many(a: &str, b: &str);
 }
 
+#[allow(unused_macros)]
 macro_rules! console_log {
     // Note that this is using the `log` function imported above during
     // `bare_bones`
@@ -36,15 +66,100 @@ macro_rules! console_log {
 }
 
 // */
+/// Trimming at least this many points exports a backup first.
+const BACKUP_TRIM_THRESHOLD: usize = 3;
+/// How far a duplicated point is shifted from the original, in inches.
+const DUPLICATE_OFFSET: f32 = 6.0;
+/// Speed limit given to a point when one is first set, in inches per second.
+const DEFAULT_SPEED_LIMIT: f32 = 20.0;
+/// Color of segments driven in reverse.
+const REVERSE_COLOR: Color32 = Color32::from_rgb(255, 110, 200);
+/// Screen length of the heading arrows drawn on points.
+const HEADING_ARROW: f32 = 30.0;
+/// Shift-dragging a heading arrow snaps it to multiples of this, in degrees.
+const HEADING_SNAP: f32 = 15.0;
+/// Extension of saved project files.
+const PROJECT_EXTENSION: &str = ".pathy";
+/// Extension of project files with the field image inside.
+const BUNDLE_EXTENSION: &str = ".pathy-bundle";
+/// Number of projects kept in the recent projects list.
+const MAX_RECENT: usize = 8;
+/// Storage key the uploaded field image is kept under.
+const FIELD_KEY: &str = "field";
+/// Length of the autonomous period, in seconds.
+const AUTON_TIME: f32 = 15.0;
+/// Tracking error drawn fully red, in inches.
+const TRACKING_TOLERANCE: f32 = 6.0;
+/// Grid size the swept area is filled on, in inches.
+const SWEPT_CELL: f32 = 0.5;
+/// Seconds between checks for changes to autosave.
+const AUTOSAVE_INTERVAL: f64 = 15.0;
+/// Range of field sizes allowed, in inches.
+pub const SIZE_RANGE: std::ops::RangeInclusive<f32> = 12.0..=1000.0;
+/// Range of screen scales allowed, in pixels.
+pub const SCALE_RANGE: std::ops::RangeInclusive<u32> = 100..=4000;
+/// Range of point densities allowed, in points per curve.
+pub const STEPS_RANGE: std::ops::RangeInclusive<usize> = 1..=1000;
+/// Grid spacings tiles can be split into.
+pub const TILE_FRACTIONS: [u32; 4] = [1, 2, 4, 8];
+/// Range of sketch tolerances allowed, in inches.
+pub const SKETCH_TOLERANCE_RANGE: std::ops::RangeInclusive<f32> = 0.1..=24.0;
+/// Range of samples per segment allowed in generated code, for the whole path or one segment.
+pub const CODE_SAMPLES_RANGE: std::ops::RangeInclusive<usize> = 2..=1000;
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum CursorMode {
     Default,
     Create,
     Insert,
+    Split,
+    Sketch,
     Delete,
     Trim,
 }
 
+/// Keys that switch cursor modes and toggle snapping, so a team can share one layout.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Keybinds {
+    pub create: egui::Key,
+    pub insert: egui::Key,
+    pub split: egui::Key,
+    pub delete: egui::Key,
+    pub trim: egui::Key,
+    pub sketch: egui::Key,
+    pub snap: egui::Key,
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self {
+            create: egui::Key::C,
+            insert: egui::Key::I,
+            split: egui::Key::S,
+            delete: egui::Key::D,
+            trim: egui::Key::T,
+            sketch: egui::Key::K,
+            snap: egui::Key::G,
+        }
+    }
+}
+
+impl Keybinds {
+    /// Each key with what it does, for rebinding them.
+    fn labelled(&mut self) -> [(&mut egui::Key, &'static str); 7] {
+        [
+            (&mut self.create, "Create"),
+            (&mut self.insert, "Insert"),
+            (&mut self.split, "Split"),
+            (&mut self.delete, "Delete"),
+            (&mut self.trim, "Trim"),
+            (&mut self.sketch, "Sketch"),
+            (&mut self.snap, "Snap"),
+        ]
+    }
+}
+
 /// Represents chosen background image.
 #[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq)]
 pub enum Background {
@@ -53,6 +168,199 @@ pub enum Background {
     Custom,
 }
 
+/// A route drawn faintly behind the open path to compare against.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Comparison {
+    /// Another of the project's paths
+    Path(Uuid),
+    /// A path file dropped on the field
+    File {
+        name: String,
+        points: Vec<SavePoint>,
+    },
+}
+
+/// Poses recorded on the robot during a run, drawn over the path to see how closely it was followed.
+#[derive(Clone, Debug)]
+pub struct Telemetry {
+    pub name: String,
+    /// Poses in time order, with their time in seconds
+    pub poses: Vec<(f32, Pose)>,
+    /// How far each pose is from the open path, in inches
+    pub errors: Vec<f32>,
+    /// The open path and samples per segment the errors were measured against
+    pub measured: Option<(Vec<SavePoint>, usize)>,
+}
+
+/// What a path's timeline is worked out from: its points, start pose, end pose, profile and heading mode.
+pub type Timed = (Vec<SavePoint>, Option<Pose>, EndPose, Profile, HeadingMode);
+
+/// What the open path's simulation is worked out from, so it's only run again when one of them changes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulated {
+    /// The robot driving both routes
+    pub robot: Robot,
+    /// The open path's
+    pub ours: Timed,
+    /// The partner's path's, if there is one
+    pub theirs: Option<Timed>,
+}
+
+/// The open path and its partner's routes timed as if driven, kept until what they're timed from changes.
+#[derive(Default)]
+pub struct Simulation {
+    /// What the routes were timed from
+    pub from: Option<Simulated>,
+    pub ours: Timeline,
+    pub theirs: Option<Timeline>,
+    /// When the robots first touch, if they do
+    pub collision: Option<f32>,
+    /// Area the robot sweeps, filled in the first time it's shown
+    pub swept: Option<Vec<egui::Rect>>,
+}
+
+impl Simulation {
+    /// Seconds until both robots are done.
+    pub fn duration(&self) -> f32 {
+        self.ours
+            .duration()
+            .max(self.theirs.as_ref().map_or(0.0, Timeline::duration))
+    }
+}
+
+/// A project saved or opened recently, kept so it can be switched back to in one click.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct RecentProject {
+    pub name: String,
+    /// When it was last saved, opened or switched away from, in seconds since the Unix epoch
+    pub time: u64,
+    /// The open path's shape, scaled so the field is a unit square
+    pub thumbnail: Vec<Pos2>,
+    /// Color of the open path
+    pub color: Color32,
+    /// Paths, actions and variables, as saved by `PathyApp::saved_text`
+    pub project: String,
+}
+
+/// A project with its field image, so it opens looking the same on another computer.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Bundle<P> {
+    /// The uploaded field image as base64, empty if there isn't one
+    image: String,
+    project: P,
+}
+
+/// Order paths are listed in by the path browser.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PathSort {
+    Name,
+    /// By first tag, untagged paths last
+    Tag,
+    /// Most recently changed first
+    #[default]
+    Modified,
+}
+
+/// Point the path is rotated about.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pivot {
+    #[default]
+    Center,
+    First,
+    Inspected,
+    Custom,
+}
+
+/// One of a project's paths, such as a qualification auton, skills run or backup route.
+/// The open path's points live in `PathyApp::points`, and are copied back here when switching.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct NamedPath {
+    /// Stays the same through renames, so partners can refer to each other
+    pub id: Uuid,
+    pub name: String,
+    /// Notes on the route, put at the top of its generated code
+    pub notes: String,
+    /// Alliance the route is for, if only one
+    pub side: Option<Alliance>,
+    /// Color the path is drawn in
+    pub color: Color32,
+    /// Where the robot is placed before the route starts, if set
+    pub start: Option<Pose>,
+    /// What the robot must be doing at the end of the route
+    pub end: EndPose,
+    /// Path the other robot on the alliance runs at the same time
+    pub partner: Option<Uuid>,
+    /// How the path is driven, for simulating it
+    pub profile: Profile,
+    /// How headings are interpolated between points that set one
+    pub heading_mode: HeadingMode,
+    /// Labels for finding the path, like "skills" or "left"
+    pub tags: Vec<String>,
+    /// When the path was last changed, in seconds since the Unix epoch
+    pub modified: u64,
+    pub points: Vec<SavePoint>,
+    /// Constraints between this path's points
+    pub constraints: Vec<Constraint>,
+}
+
+impl Default for NamedPath {
+    fn default() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: String::new(),
+            notes: String::new(),
+            side: None,
+            color: Color32::YELLOW,
+            start: None,
+            end: EndPose::default(),
+            partner: None,
+            profile: Profile::default(),
+            heading_mode: HeadingMode::default(),
+            tags: Vec::new(),
+            modified: files::timestamp(),
+            points: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+}
+
+impl NamedPath {
+    /// How the path is driven by `robot`: from its start pose, to its end pose, within its profile's limits,
+    /// turning between set headings by its heading mode.
+    pub fn driving<'a>(&'a self, robot: &'a Robot) -> Driving<'a> {
+        Driving {
+            start: self.start.as_ref(),
+            end: &self.end,
+            profile: &self.profile,
+            mode: self.heading_mode,
+            robot,
+        }
+    }
+    /// The name as a lowercase identifier, for file and generated variable names.
+    pub fn slug(&self) -> String {
+        slug(&self.name, "path")
+    }
+}
+
+/// A name as a lowercase identifier, safe in file and generated variable names,
+/// or `fallback` if nothing of it is left.
+fn slug(name: &str, fallback: &str) -> String {
+    let slug: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let slug = slug.trim_matches('_');
+    match slug.chars().next() {
+        None => fallback.into(),
+        // Identifiers can't start with a digit
+        Some(c) if c.is_ascii_digit() => format!("{fallback}_{slug}"),
+        Some(_) => slug.into(),
+    }
+}
+
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
@@ -68,23 +376,240 @@ pub struct PathyApp {
     /// Current background image
     #[serde(skip)]
     pub overlay: Option<RetainedImage>,
-    /// Uploaded background image data
+    /// Uploaded background image data, stored under its own key as base64.
+    /// Still read from older saves that kept it in the app state.
+    #[serde(skip_serializing)]
     pub uploaded: Option<Arc<[u8]>>,
     /// Field background state
     pub background: Background,
+    /// Where the custom field image was last loaded from, if online
+    pub field_url: String,
+    /// Whether the field image is being downloaded from `field_url`
+    #[serde(skip)]
+    pub fetching: bool,
     /// Bezier points
     #[serde(skip)]
     pub points: Vec<Rc<RefCell<BezPoint>>>,
+    /// Every path in the project, including the open one
+    pub paths: Vec<NamedPath>,
+    /// Index of the open path
+    pub current: usize,
+    /// Path whose name is being edited in its tab
+    #[serde(skip)]
+    pub renaming: Option<usize>,
     /// Number of steps
     pub steps: usize,
+    /// Create and trim at the start of the path instead of the end
+    #[serde(skip)]
+    pub from_start: bool,
+    /// Snap points to grid
+    pub snap: bool,
+    /// Grid spacing
+    pub grid: f32,
+    /// Measure and snap in field tiles rather than inches
+    pub tiles: bool,
+    /// Size of a field tile
+    pub tile_size: f32,
+    /// Snap to 1/n of a tile
+    pub tile_fraction: u32,
+    /// Square new points up against nearby walls
+    pub wall_snap: bool,
+    /// How close to a wall a new point must be to snap to it
+    pub wall_reach: f32,
+    /// Distance from a wall snapped points are placed at, usually half the robot's width
+    pub wall_offset: f32,
+    /// Handle mode given to new points
+    pub handle_mode: HandleMode,
+    /// Keys for the cursor modes and snapping
+    pub keys: Keybinds,
+    /// Samples per segment in generated code, where a point doesn't set its own
+    pub code_samples: usize,
+    /// Script window visibility
+    pub show_script: bool,
+    /// Action library window visibility
+    pub show_actions: bool,
+    /// Path browser window visibility
+    pub show_browser: bool,
+    /// Order the path browser lists paths in
+    pub browser_sort: PathSort,
+    /// Text the path browser filters names and tags by
+    #[serde(skip)]
+    pub browser_filter: String,
+    /// Tag being typed for the open path
+    #[serde(skip)]
+    pub new_tag: String,
+    /// Path script source
+    pub script: String,
+    /// Error from the last script run
+    #[serde(skip)]
+    pub script_error: Option<String>,
+    /// Primitive the path is compared against
+    #[serde(skip)]
+    pub primitive: Option<Primitive>,
+    /// Freehand stroke being sketched, in field coordinates
+    #[serde(skip)]
+    pub stroke: Vec<Pos2>,
+    /// Furthest a sketched stroke may be from the fitted path
+    pub sketch_tolerance: f32,
+    /// Show tangent and normal arrows at each anchor
+    pub show_tangents: bool,
+    /// Show the curvature comb
+    pub show_comb: bool,
+    /// Comb spike length per unit of curvature
+    pub comb_scale: f32,
+    /// Show point names on the field
+    pub show_names: bool,
+    /// Show the interpolated heading along the path
+    pub show_profile: bool,
+    /// Show the area the robot covers driving the open path
+    pub show_swept: bool,
+    /// The open path and its partner's routes as last timed
+    #[serde(skip)]
+    pub simulation: Simulation,
+    /// Saved state that failed to load, held back from being overwritten until dealt with
+    #[serde(skip)]
+    pub corrupt: Vec<Corrupt>,
+    /// Version of the saved state, see [`migrate`]. Unversioned saves read as 0.
+    pub version: u32,
+    /// When the app state was last persisted, in seconds since the Unix epoch
+    pub saved_at: u64,
+    /// Projects saved or opened recently, most recent first
+    pub recent: Vec<RecentProject>,
+    /// Autosaves in storage, newest first
+    #[serde(skip)]
+    pub autosaves: Vec<Snapshot>,
+    /// When changes were last checked for autosaving, in egui time
+    #[serde(skip)]
+    pub autosaved: f64,
+    /// Autosave with changes that never made it into the saved state, offered on startup
+    #[serde(skip)]
+    pub restore_offer: Option<Snapshot>,
+    /// Path length the last time it was validated
+    #[serde(skip)]
+    pub validated: usize,
+    /// Set by edits to point values, which don't change the path length, to validate it again
+    #[serde(skip)]
+    pub revalidate: bool,
+    /// Problems reported by the last validation, so unchanged ones aren't reported again
+    #[serde(skip)]
+    pub problems: Vec<String>,
+    /// Size of generated test patterns
+    #[serde(skip)]
+    pub pattern_size: f32,
+    /// Offset to move the path by
+    #[serde(skip)]
+    pub translation: Vec2,
+    /// Rotation angle in degrees
+    #[serde(skip)]
+    pub rotation: f32,
+    /// Rotation pivot
+    #[serde(skip)]
+    pub pivot: Pivot,
+    /// Custom rotation pivot
+    #[serde(skip)]
+    pub custom_pivot: Pos2,
+    /// Point the context menu was opened on
+    #[serde(skip)]
+    pub context_point: Option<Rc<RefCell<BezPoint>>>,
     /// Locked selected point
     #[serde(skip)]
     pub selected: Option<Rc<RefCell<Point>>>,
+    /// Point whose position is being typed, where the popup opened, and the typed position
+    #[serde(skip)]
+    pub typing: Option<(Rc<RefCell<Point>>, Pos2, Pos2)>,
+    /// Point being dragged out in Create mode, whose handles follow the pointer
+    #[serde(skip)]
+    pub pen: Option<Rc<RefCell<BezPoint>>>,
+    /// Point whose heading arrow is being dragged
+    #[serde(skip)]
+    pub turning: Option<Rc<RefCell<BezPoint>>>,
+    /// Whether the simulation is playing
+    #[serde(skip)]
+    pub playing: bool,
+    /// Seconds into the simulated route being shown, zero when stopped at the start
+    #[serde(skip)]
+    pub playhead: f32,
+    /// How many times faster than real time the simulation plays
+    pub playback_speed: f32,
+    /// The robot driving the routes
+    pub robot: Robot,
+    /// Route drawn behind the open path to compare against
+    #[serde(skip)]
+    pub compare: Option<Comparison>,
+    /// Whether a project, import or preset file is being picked, so the app checks for it arriving
+    #[serde(skip)]
+    pub opening: bool,
+    /// Poses recorded during a run of the open path
+    #[serde(skip)]
+    pub telemetry: Option<Telemetry>,
+    /// Whether a telemetry file is being picked, or on native, waiting to be dropped
+    #[serde(skip)]
+    pub picking_telemetry: bool,
+    /// Project file waiting for the user to confirm replacing unsaved changes, by name and contents
+    #[serde(skip)]
+    pub pending_open: Option<(String, Vec<u8>)>,
+    /// The project's paths, actions and variables as last saved or opened, to detect unsaved changes
+    #[serde(skip)]
+    pub saved_state: Option<String>,
+    /// Where the locked point was when the drag started
+    #[serde(skip)]
+    pub drag_start: Pos2,
+    /// Anchor and handles of the dragged point's parent when the drag started
+    #[serde(skip)]
+    pub pre_drag: [Pos2; 3],
+    /// Set when a drag is cancelled, so it doesn't restart before the button is released
+    #[serde(skip)]
+    pub drag_cancelled: bool,
     /// Inspected point
     #[serde(skip)]
     pub inspecting: Option<Rc<RefCell<Point>>>,
+    /// Points picked with shift+click for group edits
+    #[serde(skip)]
+    pub selection: Vec<Uuid>,
+    /// Part of the selected points that group edits apply to
+    #[serde(skip)]
+    pub select_filter: Filter,
+    /// Percentage to scale selected handles by
+    #[serde(skip)]
+    pub handle_scale: f32,
+    /// Point list visibility
+    pub show_points: bool,
+    /// Relationships between points
+    pub constraints: Vec<Constraint>,
+    /// Named values usable in point expressions
+    pub variables: Vec<Variable>,
+    /// Reusable code that can be attached to points
+    pub actions: Vec<Action>,
+    /// Alliance whose variable values are shown on the field
+    pub alliance: Alliance,
+    /// Point numbers typed for a new constraint
+    #[serde(skip)]
+    pub constraint_points: String,
+    /// A pulled project differing from the open one, the differing parts,
+    /// and the parts picked to take from it
+    #[serde(skip)]
+    pub conflict: Option<(Project, Vec<Part>, Vec<Part>)>,
+    /// Issue report window visibility
+    #[serde(skip)]
+    pub show_report: bool,
+    /// Problem description typed for an issue report
+    #[serde(skip)]
+    pub report_description: String,
+    /// Where projects are pushed to and pulled from
+    pub sync: Backend,
+    /// Name the project is synced under
+    pub project_name: String,
+    /// Push or pull waiting on the sync backend
+    #[serde(skip)]
+    pub transfer: Option<Transfer>,
     /// Generated code
     pub generated: String,
+    /// Notifications
+    #[serde(skip)]
+    pub toasts: Toasts,
+    /// Recent edits, for issue reports
+    #[serde(skip)]
+    pub journal: Journal,
 }
 
 impl Default for PathyApp {
@@ -97,104 +622,2816 @@ impl Default for PathyApp {
             overlay: None,
             uploaded: None,
             background: Background::Game,
+            field_url: String::new(),
+            fetching: false,
             points: Vec::new(),
+            paths: vec![NamedPath {
+                name: "Path 1".into(),
+                ..Default::default()
+            }],
+            current: 0,
+            renaming: None,
             steps: 100,
+            from_start: false,
+            snap: false,
+            grid: 1.0,
+            tiles: false,
+            tile_size: 24.0,
+            tile_fraction: 2,
+            wall_snap: false,
+            wall_reach: 6.0,
+            keys: Keybinds::default(),
+            code_samples: 10,
+            wall_offset: 7.5,
+            handle_mode: HandleMode::Mirrored,
+            show_script: false,
+            show_actions: false,
+            show_browser: false,
+            browser_sort: PathSort::default(),
+            browser_filter: String::new(),
+            new_tag: String::new(),
+            script: String::new(),
+            script_error: None,
+            primitive: None,
+            stroke: Vec::new(),
+            sketch_tolerance: 2.0,
+            show_tangents: false,
+            show_comb: false,
+            comb_scale: 100.0,
+            show_names: true,
+            show_profile: true,
+            show_swept: false,
+            simulation: Simulation::default(),
+            corrupt: Vec::new(),
+            // Unversioned until upgraded, so saves from before versioning start at the first step
+            version: 0,
+            saved_at: 0,
+            recent: Vec::new(),
+            autosaves: Vec::new(),
+            autosaved: 0.0,
+            restore_offer: None,
+            validated: 0,
+            revalidate: false,
+            problems: Vec::new(),
+            pattern_size: 48.0,
+            translation: Vec2::ZERO,
+            rotation: 90.0,
+            pivot: Pivot::Center,
+            custom_pivot: Pos2::ZERO,
+            context_point: None,
             selected: None,
+            typing: None,
+            pen: None,
+            turning: None,
+            playing: false,
+            playhead: 0.0,
+            playback_speed: 1.0,
+            robot: Robot::default(),
+            compare: None,
+            opening: false,
+            telemetry: None,
+            picking_telemetry: false,
+            pending_open: None,
+            saved_state: None,
+            drag_start: Pos2::ZERO,
+            pre_drag: [Pos2::ZERO; 3],
+            drag_cancelled: false,
             inspecting: None,
+            selection: Vec::new(),
+            select_filter: Filter::Anchors,
+            handle_scale: 120.0,
+            show_points: false,
+            constraints: Vec::new(),
+            variables: Vec::new(),
+            actions: Vec::new(),
+            alliance: Alliance::Red,
+            constraint_points: String::new(),
+            conflict: None,
+            show_report: false,
+            report_description: String::new(),
+            sync: Backend::Local,
+            project_name: "auton".into(),
+            transfer: None,
             generated: String::new(),
+            toasts: Toasts::default(),
+            journal: Journal::default(),
+        }
+    }
+}
+
+impl PathyApp {
+    /// Called once before the first frame.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // This is also where you can customize the look and feel of egui using
+        // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
+        let mut fonts = FontDefinitions::default();
+
+        fonts.font_data.insert(
+            "SpaceGrotesk".to_owned(),
+            std::sync::Arc::new(egui::FontData::from_static(include_bytes!(
+                "../assets/SpaceGrotesk-Regular.ttf"
+            ))),
+        );
+        fonts
+            .families
+            .get_mut(&FontFamily::Proportional)
+            .unwrap()
+            .insert(0, "SpaceGrotesk".into());
+        cc.egui_ctx.set_fonts(fonts);
+        // only if in dark mode
+        cc.egui_ctx.style_mut_of(egui::Theme::Dark, |style| {
+            style.visuals.panel_fill = Color32::from_gray(10);
+        });
+
+        // Load previous app state (if any).
+        // Note that you must enable the `persistence` feature for this to work.
+        // Anything that fails to load is kept so it can be exported or salvaged.
+        let mut corrupt = Vec::new();
+        let mut app: Self = match cc.storage.map(|s| recovery::load(s, eframe::APP_KEY)) {
+            Some(Ok(Some(app))) => app,
+            Some(Err(e)) => {
+                corrupt.push(e);
+                Default::default()
+            }
+            _ => Default::default(),
+        };
+        let raw = cc
+            .storage
+            .and_then(|s| s.get_string(eframe::APP_KEY))
+            .unwrap_or_default();
+        match migrate::upgrade(&mut app, Some(&raw)) {
+            Ok(note) => {
+                if let Some(note) = note {
+                    app.toasts.add(note);
+                }
+            }
+            Err(error) => {
+                // Held like corrupt state, so this version doesn't overwrite it
+                corrupt.push(Corrupt {
+                    key: eframe::APP_KEY.into(),
+                    raw,
+                    error,
+                });
+                app = Default::default();
+                // A fresh state is always current
+                let _ = migrate::upgrade(&mut app, None);
+            }
+        }
+
+        // load saved path
+        app.points = match cc
+            .storage
+            .map(|s| recovery::load::<Vec<SavePoint>>(s, "path"))
+        {
+            Some(Ok(Some(saved))) => saved
+                .into_iter()
+                .map(|p| BezPoint::load(p.into()))
+                .collect(),
+            Some(Err(e)) => {
+                corrupt.push(e);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+        let loaded = app.points.len();
+        app.validate();
+        if app.points.len() < loaded {
+            if let Some(raw) = cc.storage.and_then(|s| s.get_string("path")) {
+                corrupt.push(Corrupt {
+                    key: "path".into(),
+                    raw,
+                    error: "Some points had invalid positions".into(),
+                });
+            }
+        }
+        app.corrupt = corrupt;
+
+        // The field image is kept apart from the rest of the state, since it's large
+        if let Some(field) = cc.storage.and_then(|s| s.get_string(FIELD_KEY)) {
+            app.uploaded = base64::engine::general_purpose::STANDARD
+                .decode(field)
+                .ok()
+                .filter(|bytes| !bytes.is_empty())
+                .map(Into::into);
+        }
+
+        // Offer the newest autosave if the app closed before its changes were saved
+        app.autosaves = cc.storage.map(recovery::autosaves).unwrap_or_default();
+        app.store_path();
+        app.restore_offer = app
+            .autosaves
+            .first()
+            .filter(|s| s.time > app.saved_at && s.project != app.saved_text())
+            .cloned();
+
+        files::listen_for_paste(&cc.egui_ctx);
+
+        // A path shared by link opens in its own tab
+        if let Some(packed) = share::opened() {
+            app.open_shared(&packed);
+        }
+
+        // Generate code and load overlay on startup
+        app.generate();
+        app.load_field_overlay();
+        app
+    }
+    /// Downloads the whole project, every path and setting, as a file that can be opened again
+    fn save_project(&mut self) {
+        self.store_path();
+        let text = self.without_local(serde_json::to_string_pretty);
+        let result = text.map_err(|e| e.to_string()).and_then(|text| {
+            files::save_project(
+                &format!("{}{PROJECT_EXTENSION}", self.project_name),
+                text.as_bytes(),
+            )
+        });
+        match result {
+            Ok(location) => {
+                self.saved_state = Some(self.saved_text());
+                self.remember();
+                self.toasts.add(format!("Project saved to {location}"));
+            }
+            Err(e) => self.toasts.add(format!("Project save failed: {e}")),
+        }
+    }
+    /// Downloads the project along with the field image, as one file
+    fn export_bundle(&mut self) {
+        self.store_path();
+        let image = self
+            .uploaded
+            .as_ref()
+            .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+            .unwrap_or_default();
+        let text = self.without_local(|app| {
+            let bundle = Bundle {
+                image,
+                project: app,
+            };
+            serde_json::to_string_pretty(&bundle)
+        });
+        let result = text.map_err(|e| e.to_string()).and_then(|text| {
+            files::save_project(
+                &format!("{}{BUNDLE_EXTENSION}", self.project_name),
+                text.as_bytes(),
+            )
+        });
+        match result {
+            Ok(location) => {
+                self.saved_state = Some(self.saved_text());
+                self.remember();
+                self.toasts.add(format!("Bundle saved to {location}"));
+            }
+            Err(e) => self.toasts.add(format!("Bundle export failed: {e}")),
+        }
+    }
+    /// Runs `write` with the settings that belong to this machine rather than the project taken out,
+    /// so they aren't put in files shared with teammates: the recent list, the name the project is
+    /// synced under and the sync settings, which can hold credentials.
+    fn without_local<T>(&mut self, write: impl FnOnce(&Self) -> T) -> T {
+        let recent = std::mem::take(&mut self.recent);
+        let sync = std::mem::take(&mut self.sync);
+        let project_name = std::mem::take(&mut self.project_name);
+        let result = write(self);
+        self.recent = recent;
+        self.sync = sync;
+        self.project_name = project_name;
+        result
+    }
+    /// The parts of the project worth saving, as text to compare
+    fn saved_text(&self) -> String {
+        ron::to_string(&(&self.paths, &self.actions, &self.variables)).unwrap_or_default()
+    }
+    /// Whether the project has paths that were changed since it was last saved or opened
+    fn unsaved(&mut self) -> bool {
+        self.store_path();
+        let text = self.saved_text();
+        match &self.saved_state {
+            Some(saved) => *saved != text,
+            None => self.paths.iter().any(|p| !p.points.is_empty()),
+        }
+    }
+    /// Snapshots the project into an autosave slot if it changed since the last one
+    fn autosave(&mut self, frame: &mut eframe::Frame) {
+        // Don't bury a crashed session's autosave before the user has answered
+        if self.restore_offer.is_some() {
+            return;
+        }
+        self.store_path();
+        let project = self.saved_text();
+        if self.autosaves.first().is_some_and(|s| s.project == project)
+            || self.paths.iter().all(|p| p.points.is_empty())
+        {
+            return;
+        }
+        let Some(storage) = frame.storage_mut() else {
+            return;
+        };
+        let snapshot = Snapshot {
+            time: files::timestamp(),
+            project,
+        };
+        recovery::autosave(storage, &snapshot);
+        self.autosaves = recovery::autosaves(storage);
+    }
+    /// Replaces the project's paths, actions and variables with an autosave's
+    fn restore(&mut self, snapshot: &Snapshot) {
+        match self.load_saved(&snapshot.project) {
+            Ok(()) => self.toasts.add("Autosave restored"),
+            Err(e) => self
+                .toasts
+                .add(format!("Could not restore the autosave: {e}")),
+        }
+    }
+    /// Replaces the project's paths, actions and variables with ones saved by [`Self::saved_text`],
+    /// backing up the open path first
+    fn load_saved(&mut self, text: &str) -> Result<(), String> {
+        type Saved = (Vec<NamedPath>, Vec<Action>, Vec<Variable>);
+        let (mut paths, actions, variables) =
+            ron::from_str::<Saved>(text).map_err(|e| e.to_string())?;
+        if paths.is_empty() {
+            return Err("it has no paths".into());
+        }
+        for path in &mut paths {
+            path.profile.clamp();
+        }
+        if !self.points.is_empty() {
+            self.backup();
+        }
+        self.paths = paths;
+        self.actions = actions;
+        self.variables = variables;
+        self.load_path(self.current.min(self.paths.len() - 1));
+        Ok(())
+    }
+    /// Puts the open project at the top of the recent projects list, so it can be switched back to
+    fn remember(&mut self) {
+        self.store_path();
+        let samples = analysis::sample(&self.points, 8);
+        let thumbnail = samples
+            .iter()
+            .map(|p| (p.to_vec2() / self.size).to_pos2())
+            .collect();
+        let recent = RecentProject {
+            name: self.project_name.clone(),
+            time: files::timestamp(),
+            thumbnail,
+            color: self.paths[self.current].color,
+            project: self.saved_text(),
+        };
+        self.recent.retain(|r| r.name != recent.name);
+        self.recent.insert(0, recent);
+        self.recent.truncate(MAX_RECENT);
+    }
+    /// Switches to a recent project, remembering the open one
+    fn open_recent(&mut self, i: usize) {
+        let recent = self.recent[i].clone();
+        self.remember();
+        match self.load_saved(&recent.project) {
+            Ok(()) => {
+                self.project_name = recent.name;
+                self.saved_state = Some(recent.project);
+                self.remember();
+                self.toasts
+                    .add(format!("Switched to {}", self.project_name));
+            }
+            Err(e) => self
+                .toasts
+                .add(format!("Could not open {}: {e}", recent.name)),
+        }
+    }
+    /// Offers to restore changes from a session that closed before saving them
+    fn restore_window(&mut self, ctx: &egui::Context) {
+        let Some(snapshot) = &self.restore_offer else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("Restore unsaved changes")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Pathy closed before saving your latest changes. Restore the autosave from {}?",
+                    ago(files::timestamp().saturating_sub(snapshot.time))
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+        if let Some(restore) = choice {
+            if let Some(snapshot) = self.restore_offer.take() {
+                if restore {
+                    self.restore(&snapshot);
+                }
+            }
+        }
+    }
+    /// Opens a project file, first asking whether to replace unsaved changes
+    fn request_open(&mut self, name: String, bytes: Vec<u8>) {
+        if self.unsaved() {
+            self.pending_open = Some((name, bytes));
+        } else {
+            self.open_project(&name, &bytes);
+        }
+    }
+    /// Asks whether to replace unsaved changes with the project file being opened
+    fn open_window(&mut self, ctx: &egui::Context) {
+        let Some((name, _)) = &self.pending_open else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("Unsaved changes")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Opening {name} replaces {}, which has unsaved changes.",
+                    self.project_name
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Save first").clicked() {
+                        choice = Some(Some(true));
+                    }
+                    if ui.button("Discard changes").clicked() {
+                        choice = Some(Some(false));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(None);
+                    }
+                });
+            });
+        // Whether to save first, or `None` to cancel
+        if let Some(save) = choice {
+            let pending = self.pending_open.take();
+            if let (Some(save), Some((name, bytes))) = (save, pending) {
+                if save {
+                    self.save_project();
+                }
+                self.open_project(&name, &bytes);
+            }
+        }
+    }
+    /// Replaces everything with a saved project file's contents
+    fn open_project(&mut self, name: &str, bytes: &[u8]) {
+        let text = match std::str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => {
+                self.toasts.add(format!("Could not open {name}: {e}"));
+                return;
+            }
+        };
+        let project = if name.ends_with(BUNDLE_EXTENSION) {
+            serde_json::from_str::<Bundle<Self>>(text)
+                .map_err(|e| e.to_string())
+                .and_then(|bundle| {
+                    let mut app = bundle.project;
+                    let image = base64::engine::general_purpose::STANDARD
+                        .decode(bundle.image)
+                        .map_err(|e| e.to_string())?;
+                    app.uploaded = (!image.is_empty()).then(|| image.into());
+                    Ok(app)
+                })
+        } else if text.trim_start().starts_with('{') {
+            serde_json::from_str::<Self>(text).map_err(|e| e.to_string())
+        } else {
+            // Project files were RON before they were JSON
+            ron::from_str::<Self>(text).map_err(|e| e.to_string())
+        };
+        let mut app = match project {
+            Ok(app) => app,
+            Err(e) => {
+                self.toasts.add(format!("Could not open {name}: {e}"));
+                return;
+            }
+        };
+        match migrate::upgrade(&mut app, None) {
+            Ok(note) => {
+                if let Some(note) = note {
+                    self.toasts.add(note);
+                }
+            }
+            Err(e) => {
+                self.toasts.add(format!("Could not open {name}: {e}"));
+                return;
+            }
+        }
+        if !self.points.is_empty() {
+            self.backup();
+        }
+        // Project files don't carry the field image, so keep the one in use
+        if app.uploaded.is_none() {
+            app.uploaded = self.uploaded.take();
+        }
+        // The recent list and sync settings belong to this machine, not the file
+        app.recent = std::mem::take(&mut self.recent);
+        app.sync = std::mem::take(&mut self.sync);
+        app.toasts = std::mem::take(&mut self.toasts);
+        app.corrupt = std::mem::take(&mut self.corrupt);
+        *self = app;
+        self.load_path(self.current);
+        self.load_field_overlay();
+        self.saved_state = Some(self.saved_text());
+        // Name the project after its file, so it's listed under that
+        self.project_name = name
+            .trim_end_matches(BUNDLE_EXTENSION)
+            .trim_end_matches(PROJECT_EXTENSION)
+            .into();
+        self.remember();
+        self.toasts.add(format!("Opened {name}"));
+    }
+    /// Copies a link that opens the current path to the clipboard
+    fn share_link(&mut self, ctx: &egui::Context) {
+        self.store_path();
+        match share::pack(&self.paths[self.current]) {
+            Ok(packed) => {
+                ctx.copy_text(share::link(&packed));
+                self.toasts.add("Share link copied");
+            }
+            Err(e) => self.toasts.add(format!("Could not make a share link: {e}")),
+        }
+    }
+    /// Adds a path from a share link to the project and opens it
+    fn open_shared(&mut self, packed: &str) {
+        match share::unpack::<NamedPath>(packed) {
+            Ok(mut path) => {
+                path.profile.clamp();
+                // The path may already be in this project, and its partner won't be
+                path.id = Uuid::new_v4();
+                path.partner = None;
+                let name = path.name.clone();
+                self.store_path();
+                self.paths.push(path);
+                self.load_path(self.paths.len() - 1);
+                self.toasts.add(format!("Opened shared path {name}"));
+            }
+            Err(e) => self
+                .toasts
+                .add(format!("Could not open the shared link: {e}")),
+        }
+    }
+    /// Adds the routes in another tool's file to the project as new paths, fitting curves to them
+    fn import(&mut self, name: &str, bytes: &[u8]) {
+        match import::read(name, bytes, self.size) {
+            Ok(routes) => {
+                self.store_path();
+                let count = routes.len();
+                for route in routes {
+                    let points = route
+                        .points(self.sketch_tolerance)
+                        .iter()
+                        .map(|p| p.borrow().clone().into())
+                        .collect();
+                    self.paths.push(NamedPath {
+                        name: route.name,
+                        start: route.start,
+                        points,
+                        ..Default::default()
+                    });
+                }
+                self.load_path(self.paths.len() - 1);
+                self.toasts.add(match count {
+                    1 => format!("Imported {name}"),
+                    _ => format!("Imported {count} paths from {name}"),
+                });
+            }
+            Err(e) => self.toasts.add(format!("Could not import {name}: {e}")),
+        }
+    }
+    /// Copies the open path back into the project's path list
+    fn store_path(&mut self) {
+        let path = &mut self.paths[self.current];
+        let points: Vec<SavePoint> = self
+            .points
+            .iter()
+            .map(|p| p.borrow().clone().into())
+            .collect();
+        if points != path.points || self.constraints != path.constraints {
+            path.modified = files::timestamp();
+        }
+        path.points = points;
+        path.constraints = self.constraints.clone();
+    }
+    /// Opens one of the project's paths without storing the open one
+    fn load_path(&mut self, i: usize) {
+        self.current = i;
+        let path = &self.paths[i];
+        self.points = path
+            .points
+            .iter()
+            .map(|p| BezPoint::load(p.clone().into()))
+            .collect();
+        self.constraints = path.constraints.clone();
+        self.selection.clear();
+        self.inspecting = None;
+        self.context_point = None;
+        self.typing = None;
+        self.validate();
+        self.generate();
+    }
+    /// Switches to another of the project's paths
+    fn open_path(&mut self, i: usize) {
+        if i != self.current {
+            self.journal.record(format!("Opened path {}", i + 1));
+            self.store_path();
+            self.load_path(i);
+        }
+    }
+    /// Draws a tab for each path, with controls to add, rename, duplicate and remove them
+    fn path_tabs(&mut self, ui: &mut Ui) {
+        let mut opened = None;
+        let mut duplicated = None;
+        let mut removed = None;
+        let count = self.paths.len();
+        for i in 0..count {
+            if self.renaming == Some(i) {
+                let resp =
+                    ui.add(TextEdit::singleline(&mut self.paths[i].name).desired_width(100.0));
+                if resp.lost_focus() {
+                    self.renaming = None;
+                } else {
+                    resp.request_focus();
+                }
+                continue;
+            }
+            let path = &self.paths[i];
+            let resp = ui
+                .selectable_label(
+                    i == self.current,
+                    egui::RichText::new(&path.name).color(path.color),
+                )
+                .on_hover_text("Double click to rename, right click for more");
+            if resp.clicked() {
+                opened = Some(i);
+            }
+            if resp.double_clicked() {
+                self.renaming = Some(i);
+            }
+            resp.context_menu(|ui| {
+                if ui.button("Rename").clicked() {
+                    self.renaming = Some(i);
+                    ui.close_menu();
+                }
+                if ui.button("Duplicate").clicked() {
+                    duplicated = Some(i);
+                    ui.close_menu();
+                }
+                if ui
+                    .add_enabled(count > 1, egui::Button::new("Delete"))
+                    .clicked()
+                {
+                    removed = Some(i);
+                    ui.close_menu();
+                }
+            });
+        }
+        if ui.button("+").on_hover_text("New path").clicked() {
+            self.paths.push(NamedPath {
+                name: format!("Path {}", count + 1),
+                ..Default::default()
+            });
+            opened = Some(count);
+        }
+        if let Some(i) = opened {
+            if i == count {
+                self.journal.record(format!("Added path {}", i + 1));
+            }
+            self.open_path(i);
+        }
+        if let Some(i) = duplicated {
+            self.journal.record(format!("Duplicated path {}", i + 1));
+            self.store_path();
+            let mut copy = self.paths[i].clone();
+            copy.id = Uuid::new_v4();
+            copy.name += " copy";
+            self.paths.insert(i + 1, copy);
+            self.open_path(i + 1);
+        }
+        if let Some(i) = removed {
+            self.journal.record(format!("Deleted path {}", i + 1));
+            if i == self.current {
+                self.paths.remove(i);
+                self.load_path(i.min(self.paths.len() - 1));
+            } else {
+                self.paths.remove(i);
+                if i < self.current {
+                    self.current -= 1;
+                }
+            }
+            self.renaming = None;
+        }
+    }
+    /// Lists the project's paths, filtered by name or tag and sorted, opening the one clicked
+    fn path_browser(&mut self, ui: &mut Ui) {
+        let mut resorted = false;
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.browser_filter)
+                    .hint_text("Filter by name or tag")
+                    .desired_width(140.0),
+            );
+            for sort in [PathSort::Name, PathSort::Tag, PathSort::Modified] {
+                resorted |= ui
+                    .selectable_value(&mut self.browser_sort, sort, format!("{sort:?}"))
+                    .clicked();
+            }
+        });
+        // Bring the open path's modified time up to date, so it sorts by its latest edit
+        if resorted {
+            self.store_path();
+        }
+        let mut tags: Vec<&String> = self.paths.iter().flat_map(|p| &p.tags).collect();
+        tags.sort();
+        tags.dedup();
+        let mut picked = None;
+        ui.horizontal_wrapped(|ui| {
+            for tag in tags {
+                if ui
+                    .selectable_label(self.browser_filter == *tag, tag)
+                    .clicked()
+                {
+                    picked = Some(tag.clone());
+                }
+            }
+        });
+        if let Some(tag) = picked {
+            if self.browser_filter == tag {
+                self.browser_filter.clear();
+            } else {
+                self.browser_filter = tag;
+            }
+        }
+        ui.separator();
+        let filter = self.browser_filter.trim().to_lowercase();
+        let mut listed: Vec<usize> = (0..self.paths.len())
+            .filter(|&i| {
+                let path = &self.paths[i];
+                path.name.to_lowercase().contains(&filter)
+                    || path.tags.iter().any(|t| t.contains(&filter))
+            })
+            .collect();
+        match self.browser_sort {
+            PathSort::Name => {
+                listed.sort_by_key(|&i| self.paths[i].name.to_lowercase());
+            }
+            PathSort::Tag => listed.sort_by_key(|&i| {
+                let tag = self.paths[i].tags.iter().min();
+                (tag.is_none(), tag.cloned())
+            }),
+            PathSort::Modified => {
+                listed.sort_by_key(|&i| std::cmp::Reverse(self.paths[i].modified));
+            }
+        }
+        if listed.is_empty() {
+            ui.label("No paths match");
+        }
+        let now = files::timestamp();
+        let mut opened = None;
+        for i in listed {
+            let path = &self.paths[i];
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(
+                        i == self.current,
+                        egui::RichText::new(&path.name).color(path.color),
+                    )
+                    .clicked()
+                {
+                    opened = Some(i);
+                }
+                if !path.tags.is_empty() {
+                    ui.weak(path.tags.join(", "));
+                }
+                ui.weak(ago(now.saturating_sub(path.modified)));
+            });
+        }
+        if let Some(i) = opened {
+            self.open_path(i);
+        }
+    }
+    /// Repairs invalid points, reporting anything that couldn't be fixed
+    fn validate(&mut self) {
+        let problems = validate::check(&mut self.points, self.size);
+        self.validated = self.points.len();
+        self.revalidate = false;
+        let new: Vec<&String> = problems
+            .iter()
+            .filter(|p| !self.problems.contains(p))
+            .collect();
+        match new.as_slice() {
+            [] => {}
+            [problem] => self.toasts.add(problem.as_str()),
+            [first, rest @ ..] => self
+                .toasts
+                .add(format!("{first} (and {} more problems)", rest.len())),
+        }
+        self.problems = problems;
+    }
+    /// Times the open path and its partner's, lined up at their sync markers.
+    fn timelines(&self) -> (Timeline, Option<Timeline>) {
+        let points: Vec<BezPoint> = self.points.iter().map(|p| p.borrow().clone()).collect();
+        let mut ours = Timeline::new(&points, &self.paths[self.current].driving(&self.robot));
+        let partner = self.partner().map(|path| {
+            let points: Vec<BezPoint> = path.points.iter().cloned().map(BezPoint::from).collect();
+            let mut theirs = Timeline::new(&points, &path.driving(&self.robot));
+            sim::sync(&mut ours, &mut theirs);
+            theirs
+        });
+        (ours, partner)
+    }
+    /// Times the open path and its partner's again, if anything they're timed from has changed.
+    fn simulate(&mut self) {
+        let from = self.simulated();
+        if self.simulation.from.as_ref() == Some(&from) {
+            return;
+        }
+        let (ours, theirs) = self.timelines();
+        let collision = theirs
+            .as_ref()
+            .and_then(|theirs| sim::collision(&ours, theirs, 0.05));
+        self.simulation = Simulation {
+            from: Some(from),
+            ours,
+            theirs,
+            collision,
+            swept: None,
+        };
+    }
+    /// What the open path and its partner's timelines are worked out from.
+    fn simulated(&self) -> Simulated {
+        let path = &self.paths[self.current];
+        Simulated {
+            robot: self.robot,
+            ours: (
+                self.saved_points(),
+                path.start,
+                path.end,
+                path.profile,
+                path.heading_mode,
+            ),
+            theirs: self.partner().map(|path| {
+                (
+                    path.points.clone(),
+                    path.start,
+                    path.end,
+                    path.profile,
+                    path.heading_mode,
+                )
+            }),
+        }
+    }
+    /// Points of the route being compared against, if it still exists.
+    fn compared(&self) -> Option<&[SavePoint]> {
+        match self.compare.as_ref()? {
+            Comparison::Path(id) => self
+                .paths
+                .iter()
+                .enumerate()
+                .find(|(i, p)| *i != self.current && p.id == *id)
+                .map(|(_, p)| p.points.as_slice()),
+            Comparison::File { points, .. } => Some(points),
+        }
+    }
+    /// Shows recorded poses from a telemetry file over the path
+    fn load_telemetry(&mut self, name: String, bytes: &[u8]) {
+        let poses = std::str::from_utf8(bytes)
+            .map(|text| import::poses(text, self.size))
+            .unwrap_or_default();
+        if poses.len() < 2 {
+            self.toasts
+                .add(format!("{name} has no time, x, y, theta lines to compare"));
+            return;
+        }
+        self.toasts
+            .add(format!("Comparing with {} poses from {name}", poses.len()));
+        self.telemetry = Some(Telemetry {
+            name,
+            poses,
+            errors: Vec::new(),
+            measured: None,
+        });
+        self.measure_telemetry();
+    }
+    /// Measures how far each recorded pose is from the open path, if the path changed since last time.
+    fn measure_telemetry(&mut self) {
+        let against = (self.saved_points(), self.steps);
+        let Some(telemetry) = &mut self.telemetry else {
+            return;
+        };
+        if telemetry.measured.as_ref() == Some(&against) {
+            return;
+        }
+        let planned = analysis::sample(&self.points, self.steps.max(1));
+        telemetry.errors = telemetry
+            .poses
+            .iter()
+            .map(|(_, pose)| analysis::distance_to(&planned, pose.pos))
+            .collect();
+        telemetry.measured = Some(against);
+    }
+    /// The open path's points as saved.
+    fn saved_points(&self) -> Vec<SavePoint> {
+        self.points
+            .iter()
+            .map(|p| p.borrow().clone().into())
+            .collect()
+    }
+    /// Draws a saved route as a faint line over the field
+    fn faint_path(&self, ui: &Ui, rect: egui::Rect, points: &[SavePoint], color: Color32) {
+        let ratio = self.scale as f32 / self.size;
+        let points: Vec<BezPoint> = points.iter().cloned().map(BezPoint::from).collect();
+        let mut line = Vec::new();
+        for pair in points.windows(2) {
+            for i in 0..=self.steps {
+                let pos: Pos2 =
+                    interpolate(&pair[0], &pair[1], i as f32 / self.steps as f32).into();
+                line.push(rect.min + pos.to_vec2() * ratio);
+            }
+        }
+        ui.painter().add(egui::Shape::line(
+            line,
+            Stroke::new(2.0, color.gamma_multiply(0.4)),
+        ));
+    }
+    /// The path the open path's partner robot runs, if it has one.
+    fn partner(&self) -> Option<&NamedPath> {
+        let id = self.paths[self.current].partner?;
+        self.paths
+            .iter()
+            .enumerate()
+            .find(|(i, p)| *i != self.current && p.id == id)
+            .map(|(_, p)| p)
+    }
+    /// Gives a point handles again once it is no longer a sharp corner.
+    fn reshape(&self, point: &Rc<RefCell<BezPoint>>) {
+        if point.borrow().mode == HandleMode::Sharp {
+            return;
+        }
+        if let Some(i) = self.points.iter().position(|p| Rc::ptr_eq(p, point)) {
+            validate::open_handles(&self.points, i);
+        }
+    }
+    /// Update generated code
+    fn generate(&mut self) {
+        self.generated = self.generate_code();
+        hooks::emit(Event::Generate, || self.generated.clone());
+        hooks::emit(Event::PathChanged, || {
+            ron::to_string(&self.saved_points()).unwrap_or_default()
+        });
+    }
+    fn generate_code(&self) -> String {
+        self.path_code(&self.paths[self.current], &self.points)
+    }
+    /// Generates code for one of the project's paths, whose points are `points`
+    fn path_code(&self, path: &NamedPath, points: &[Rc<RefCell<BezPoint>>]) -> String {
+        let prefix = format!("{}_", path.slug());
+        // Notes head the code so they travel with it
+        let mut notes = String::new();
+        for line in path.notes.lines() {
+            notes += &format!("// {line}\n");
+        }
+        let driven = points
+            .iter()
+            .any(|p| !p.borrow().x_expr.is_empty() || !p.borrow().y_expr.is_empty());
+        let driving = path.driving(&self.robot);
+        let step = 1.0 / self.code_samples as f32;
+        if !driven || !self.variables.iter().any(Variable::per_alliance) {
+            return notes + &generate(points, step, &prefix, &driving, &self.actions);
+        }
+        // Emit a variant of the path for each alliance it's run on
+        let alliances = match path.side {
+            Some(alliance) => vec![alliance],
+            None => vec![Alliance::Red, Alliance::Blue],
+        };
+        let variants: Vec<String> = alliances
+            .into_iter()
+            .map(|alliance| {
+                let variant: Vec<_> = points
+                    .iter()
+                    .map(|p| BezPoint::load(SavePoint::from(p.borrow().clone()).into()))
+                    .collect();
+                expr::apply(&variant, &expr::resolve(&self.variables, alliance));
+                // The robot is placed relative to the first point, so the start pose moves with it
+                let start = path.start.map(|mut start| {
+                    if let (Some(before), Some(after)) = (points.first(), variant.first()) {
+                        start.pos += Pos2::from(after.borrow().pos.borrow().clone())
+                            - Pos2::from(before.borrow().pos.borrow().clone());
+                    }
+                    start
+                });
+                let driving = Driving {
+                    start: start.as_ref(),
+                    ..path.driving(&self.robot)
+                };
+                let prefix = format!("{alliance:?}_{prefix}").to_lowercase();
+                format!(
+                    "// {alliance:?} alliance\n{}",
+                    generate(&variant, step, &prefix, &driving, &self.actions)
+                )
+            })
+            .collect();
+        notes + &variants.join("\n")
+    }
+    /// Exports the code for every path in the project as one file, each under a heading with its name
+    fn generate_all(&mut self) {
+        self.store_path();
+        // Variables are named after their path, so two paths with the same slug would clash in one file
+        let mut slugs: Vec<(String, &str)> = Vec::new();
+        for path in &self.paths {
+            let slug = path.slug();
+            if let Some((_, other)) = slugs.iter().find(|(s, _)| *s == slug) {
+                let message = format!(
+                    "\"{other}\" and \"{}\" both generate as {slug}, rename one",
+                    path.name
+                );
+                self.toasts.add(message);
+                return;
+            }
+            slugs.push((slug, &path.name));
+        }
+        let sections: Vec<String> = self
+            .paths
+            .iter()
+            .map(|path| {
+                let points: Vec<_> = path
+                    .points
+                    .iter()
+                    .map(|p| BezPoint::load(p.clone().into()))
+                    .collect();
+                format!(
+                    "// ==== {} ====\n{}",
+                    path.name,
+                    self.path_code(path, &points)
+                )
+            })
+            .collect();
+        match files::export(
+            &format!(
+                "{}-paths-{}.cpp",
+                slug(&self.project_name, "project"),
+                files::timestamp()
+            ),
+            sections.join("\n\n").as_bytes(),
+        ) {
+            Ok(location) => self.toasts.add(format!(
+                "Code for {} paths saved to {location}",
+                self.paths.len()
+            )),
+            Err(e) => self.toasts.add(format!("Generate all failed: {e}")),
+        }
+    }
+    /// Exports a backup of the current path before a destructive edit
+    fn backup(&mut self) {
+        let saved: Vec<SavePoint> = self
+            .points
+            .iter()
+            .map(|p| p.borrow().clone().into())
+            .collect();
+        let result = ron::ser::to_string_pretty(&saved, Default::default())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                files::backup(
+                    &format!(
+                        "pathy-backup-{}-{}.ron",
+                        self.paths[self.current].slug(),
+                        files::timestamp()
+                    ),
+                    text.as_bytes(),
+                )
+            });
+        match result {
+            Ok(location) => self.toasts.add(format!("Backup saved to {location}")),
+            Err(e) => self.toasts.add(format!("Backup failed: {e}")),
+        }
+    }
+    /// Draws the editable point list, returning whether any point was changed
+    fn point_list(&mut self, ui: &mut Ui) -> bool {
+        let mut updated = false;
+        let mut clicked = None;
+        let inspected = self
+            .inspecting
+            .as_ref()
+            .and_then(|p| p.borrow().parent.upgrade());
+        let mut moved = None;
+        let (count, steps) = (self.points.len(), self.steps);
+        // Checked against the values the points are placed with
+        let variables = expr::resolve(&self.variables, self.alliance);
+        for (i, point) in self.points.iter().enumerate() {
+            let active = inspected.as_ref().is_some_and(|p| Rc::ptr_eq(p, point));
+            let mut bez = point.borrow_mut();
+            let row = ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.dnd_drag_source(egui::Id::new(bez.id).with("drag"), i, |ui| {
+                        ui.label("☰").on_hover_text("Drag to reorder");
+                    });
+                    if ui
+                        .selectable_label(active, format!("Point {}", i + 1))
+                        .clicked()
+                    {
+                        clicked = Some(bez.pos.clone());
+                    }
+                    if ui
+                        .add(
+                            TextEdit::singleline(&mut bez.name)
+                                .hint_text("name")
+                                .desired_width(100.0),
+                        )
+                        .changed()
+                    {
+                        updated = true;
+                    }
+                    if ui
+                        .add(
+                            TextEdit::singleline(&mut bez.marker)
+                                .hint_text("sync marker")
+                                .desired_width(80.0),
+                        )
+                        .on_hover_text("Wait here for the partner robot to reach the same marker. The generated code runs the library's \"Sync\" action with the marker's name, or calls waitForPartner(marker) if there's none")
+                        .changed()
+                    {
+                        updated = true;
+                    }
+                });
+                egui::Grid::new(bez.id).num_columns(3).show(ui, |ui| {
+                    for (label, point) in [
+                        ("Position", &bez.pos),
+                        ("Control 1", &bez.cp1),
+                        ("Control 2", &bez.cp2),
+                    ] {
+                        ui.label(label);
+                        let mut point = point.borrow_mut();
+                        let x = ui.add(DragValue::new(&mut point.x).speed(0.1).suffix(" in"));
+                        let y = ui.add(DragValue::new(&mut point.y).speed(0.1).suffix(" in"));
+                        if x.changed() || y.changed() {
+                            // let the bezier point keep its handles in line
+                            point.editing = true;
+                            updated = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mut set = bez.heading.is_some();
+                    if ui
+                        .checkbox(&mut set, "Heading")
+                        .on_hover_text(
+                            "Face a set direction here, independent of the direction of travel",
+                        )
+                        .changed()
+                    {
+                        // Start from the direction of travel
+                        let (pos, cp2) = (bez.pos.borrow().clone(), bez.cp2.borrow().clone());
+                        bez.heading =
+                            set.then(|| (cp2.y - pos.y).atan2(cp2.x - pos.x).to_degrees());
+                        updated = true;
+                    }
+                    if let Some(heading) = &mut bez.heading {
+                        if ui
+                            .add(DragValue::new(heading).range(-180.0..=180.0).suffix("°"))
+                            .changed()
+                        {
+                            updated = true;
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mut set = bez.speed.is_some();
+                    if ui
+                        .checkbox(&mut set, "Speed limit")
+                        .on_hover_text("Slow down to at most this speed through the point")
+                        .changed()
+                    {
+                        bez.speed = set.then_some(DEFAULT_SPEED_LIMIT);
+                        updated = true;
+                    }
+                    if let Some(speed) = &mut bez.speed {
+                        if ui
+                            .add(DragValue::new(speed).range(1.0..=500.0).suffix(" in/s"))
+                            .changed()
+                        {
+                            updated = true;
+                        }
+                    }
+                });
+                if i + 1 < count {
+                    ui.horizontal(|ui| {
+                        let mut set = bez.steps.is_some();
+                        if ui
+                            .checkbox(&mut set, "Samples")
+                            .on_hover_text(
+                                "Sample the segment to the next point this many times, instead of the path's density",
+                            )
+                            .changed()
+                        {
+                            // The path's density can be lower than a segment's sample count allows
+                            bez.steps = set.then_some(
+                                steps.clamp(*CODE_SAMPLES_RANGE.start(), *CODE_SAMPLES_RANGE.end()),
+                            );
+                            updated = true;
+                        }
+                        if let Some(steps) = &mut bez.steps {
+                            if ui
+                                .add(DragValue::new(steps).range(CODE_SAMPLES_RANGE))
+                                .changed()
+                            {
+                                updated = true;
+                            }
+                        }
+                    });
+                }
+                if ui
+                    .checkbox(&mut bez.stop, "Full stop")
+                    .on_hover_text("Come to rest and settle here before continuing")
+                    .changed()
+                {
+                    updated = true;
+                }
+                let bez = &mut *bez;
+                let mut detached = None;
+                for (j, instance) in bez.actions.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt((bez.id, "action", j))
+                            .selected_text(&instance.action)
+                            .show_ui(ui, |ui| {
+                                for action in &self.actions {
+                                    updated |= ui
+                                        .selectable_value(
+                                            &mut instance.action,
+                                            action.name.clone(),
+                                            &action.name,
+                                        )
+                                        .changed();
+                                }
+                            });
+                        let params = self
+                            .actions
+                            .iter()
+                            .find(|a| a.name == instance.action)
+                            .map_or(&[][..], |a| &a.params[..]);
+                        instance.args.resize(params.len(), String::new());
+                        for (arg, param) in instance.args.iter_mut().zip(params) {
+                            updated |= ui
+                                .add(
+                                    TextEdit::singleline(arg)
+                                        .hint_text(param)
+                                        .desired_width(60.0),
+                                )
+                                .changed();
+                        }
+                        if ui.small_button("x").on_hover_text("Detach").clicked() {
+                            detached = Some(j);
+                        }
+                    });
+                }
+                if let Some(j) = detached {
+                    bez.actions.remove(j);
+                    updated = true;
+                }
+                if let Some(first) = self.actions.first() {
+                    if ui
+                        .small_button("Attach action")
+                        .on_hover_text("Run library code when the robot gets here")
+                        .clicked()
+                    {
+                        bez.actions.push(Instance {
+                            action: first.name.clone(),
+                            args: vec![String::new(); first.params.len()],
+                        });
+                        updated = true;
+                    }
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Wait");
+                    let kind = |w: &Option<Wait>| match w {
+                        None => 0,
+                        Some(Wait::Time(_)) => 1,
+                        Some(Wait::Until(_)) => 2,
+                    };
+                    let mut picked = kind(&bez.wait);
+                    for (i, label, desc) in [
+                        (0, "None", "Drive straight through"),
+                        (1, "Time", "Pause for a number of seconds"),
+                        (
+                            2,
+                            "Until",
+                            "Pause until a condition in the robot code is true",
+                        ),
+                    ] {
+                        ui.selectable_value(&mut picked, i, label)
+                            .on_hover_text(desc);
+                    }
+                    if picked != kind(&bez.wait) {
+                        bez.wait = match picked {
+                            1 => Some(Wait::Time(1.0)),
+                            2 => Some(Wait::Until(String::new())),
+                            _ => None,
+                        };
+                        updated = true;
+                    }
+                    match &mut bez.wait {
+                        Some(Wait::Time(seconds)) => {
+                            updated |= ui
+                                .add(
+                                    DragValue::new(seconds)
+                                        .range(0.0..=15.0)
+                                        .speed(0.05)
+                                        .suffix(" s"),
+                                )
+                                .changed();
+                        }
+                        Some(Wait::Until(condition)) => {
+                            updated |= ui
+                                .add(
+                                    TextEdit::singleline(condition)
+                                        .hint_text("condition")
+                                        .desired_width(100.0),
+                                )
+                                .changed();
+                        }
+                        None => {}
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Expression");
+                    let bez = &mut *bez;
+                    for (expr, hint) in [(&mut bez.x_expr, "x"), (&mut bez.y_expr, "y")] {
+                        let error = (!expr.is_empty())
+                            .then(|| expr::eval(expr, &variables).err())
+                            .flatten();
+                        let resp = ui.add(
+                            TextEdit::singleline(expr)
+                                .hint_text(hint)
+                                .desired_width(70.0)
+                                .text_color_opt(error.as_ref().map(|_| Color32::RED)),
+                        );
+                        if let Some(error) = error {
+                            resp.on_hover_text(error);
+                        }
+                    }
+                });
+            });
+            // Dropping on the top or bottom half of a row places the point before or after it
+            let row = row.response;
+            if let Some(pointer) = ui.input(|i| i.pointer.interact_pos()) {
+                let after = pointer.y > row.rect.center().y;
+                let target = if after { i + 1 } else { i };
+                if row.dnd_hover_payload::<usize>().is_some() {
+                    let y = if after {
+                        row.rect.bottom()
+                    } else {
+                        row.rect.top()
+                    };
+                    ui.painter()
+                        .hline(row.rect.x_range(), y, ui.visuals().selection.stroke);
+                }
+                if let Some(from) = row.dnd_release_payload::<usize>() {
+                    moved = Some((*from, target));
+                }
+            }
+            ui.separator();
+        }
+        if let Some((from, to)) = moved {
+            let point = self.points.remove(from);
+            self.points
+                .insert(if to > from { to - 1 } else { to }, point);
+            updated = true;
+        }
+        if clicked.is_some() {
+            self.inspecting = clicked;
+        }
+        updated
+    }
+    /// The bezier point owning the inspected point, if it still exists
+    fn inspected_point(&self) -> Option<Rc<RefCell<BezPoint>>> {
+        self.inspecting
+            .as_ref()
+            .and_then(|p| p.borrow().parent.upgrade())
+    }
+    /// Inserts a shifted copy of a point right after it
+    fn duplicate(&mut self, point: &Rc<RefCell<BezPoint>>) {
+        let Some(i) = self.points.iter().position(|p| Rc::ptr_eq(p, point)) else {
+            return;
+        };
+        let copy = point.borrow().duplicate(DUPLICATE_OFFSET, DUPLICATE_OFFSET);
+        self.journal.record(format!("Duplicated point {}", i + 1));
+        self.inspecting = Some(copy.borrow().pos.clone());
+        self.points.insert(i + 1, copy);
+        self.generate();
+    }
+    /// Copies the points from the first to the last selected one, or the inspected point,
+    /// to the clipboard.
+    fn copy_points(&mut self, ctx: &egui::Context) {
+        let selected: Vec<usize> = self
+            .points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| self.selection.contains(&p.borrow().id))
+            .map(|(i, _)| i)
+            .collect();
+        let range = match (selected.first(), selected.last(), self.inspected_point()) {
+            (Some(&first), Some(&last), _) => first..=last,
+            (_, _, Some(point)) => match self.points.iter().position(|p| Rc::ptr_eq(p, &point)) {
+                Some(i) => i..=i,
+                None => return,
+            },
+            _ => return,
+        };
+        let saved: Vec<SavePoint> = self.points[range]
+            .iter()
+            .map(|p| p.borrow().clone().into())
+            .collect();
+        match ron::ser::to_string(&saved) {
+            Ok(text) => {
+                ctx.copy_text(text);
+                self.toasts.add(format!("Copied {} points", saved.len()));
+            }
+            Err(e) => self.toasts.add(format!("Copy failed: {e}")),
+        }
+    }
+    /// Pastes copied points after the inspected point, or at the end of the path.
+    /// The pasted points are offset so they don't hide the originals, and become the selection.
+    fn paste_points(&mut self, text: &str) {
+        let Ok(saved) = ron::from_str::<Vec<SavePoint>>(text) else {
+            return;
+        };
+        let pasted: Vec<_> = saved
+            .into_iter()
+            .map(|p| {
+                BezPoint::load(p.into())
+                    .borrow()
+                    .duplicate(DUPLICATE_OFFSET, DUPLICATE_OFFSET)
+            })
+            .collect();
+        if pasted.is_empty() {
+            return;
+        }
+        let at = self
+            .inspected_point()
+            .and_then(|point| self.points.iter().position(|p| Rc::ptr_eq(p, &point)))
+            .map_or(self.points.len(), |i| i + 1);
+        self.journal
+            .record(format!("Pasted {} points after point {at}", pasted.len()));
+        self.selection = pasted.iter().map(|p| p.borrow().id).collect();
+        self.points.splice(at..at, pasted);
+        self.generate();
+    }
+    /// Draws the sync backend settings and push/pull buttons
+    fn sync_menu(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Project");
+            ui.text_edit_singleline(&mut self.project_name);
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .selectable_label(self.sync == Backend::Local, "Local")
+                .clicked()
+            {
+                self.sync = Backend::Local;
+            }
+            // Servers are reached through the browser, which provides the HTTPS client
+            let web = cfg!(target_arch = "wasm32");
+            let webdav = matches!(self.sync, Backend::Remote { .. });
+            if ui
+                .add_enabled(web, egui::SelectableLabel::new(webdav, "WebDAV"))
+                .on_hover_text("WebDAV server, or any that stores files with GET and PUT")
+                .on_disabled_hover_text("Only available in the web version of Pathy")
+                .clicked()
+                && !webdav
+            {
+                self.sync = Backend::Remote {
+                    url: String::new(),
+                    username: String::new(),
+                    password: String::new(),
+                };
+            }
+            let s3 = matches!(self.sync, Backend::S3 { .. });
+            if ui
+                .add_enabled(web, egui::SelectableLabel::new(s3, "S3"))
+                .on_hover_text("AWS S3, or a compatible store like MinIO or Cloudflare R2")
+                .on_disabled_hover_text("Only available in the web version of Pathy")
+                .clicked()
+                && !s3
+            {
+                self.sync = Backend::S3 {
+                    endpoint: String::new(),
+                    region: "us-east-1".into(),
+                    bucket: String::new(),
+                    access_key: String::new(),
+                    secret_key: String::new(),
+                };
+            }
+        });
+        if self.sync.remote() && !cfg!(target_arch = "wasm32") {
+            ui.label(
+                egui::RichText::new("Syncing with a server only works in the web version of Pathy")
+                    .color(Color32::RED),
+            );
+        }
+        match &mut self.sync {
+            Backend::Local => {}
+            Backend::Remote {
+                url,
+                username,
+                password,
+            } => {
+                egui::Grid::new("sync_remote")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("URL");
+                        ui.add(
+                            TextEdit::singleline(url).hint_text("https://dav.example.com/pathy"),
+                        );
+                        ui.end_row();
+                        ui.label("Username");
+                        ui.text_edit_singleline(username);
+                        ui.end_row();
+                        ui.label("Password");
+                        ui.add(TextEdit::singleline(password).password(true))
+                            .on_hover_text(
+                                "Only sent to https:// servers, and forgotten when Pathy closes",
+                            );
+                        ui.end_row();
+                    });
+            }
+            Backend::S3 {
+                endpoint,
+                region,
+                bucket,
+                access_key,
+                secret_key,
+            } => {
+                egui::Grid::new("sync_s3").num_columns(2).show(ui, |ui| {
+                    ui.label("Endpoint");
+                    ui.add(
+                        TextEdit::singleline(endpoint)
+                            .hint_text("https://s3.us-east-1.amazonaws.com"),
+                    );
+                    ui.end_row();
+                    ui.label("Region");
+                    ui.text_edit_singleline(region);
+                    ui.end_row();
+                    ui.label("Bucket");
+                    ui.text_edit_singleline(bucket);
+                    ui.end_row();
+                    ui.label("Access key");
+                    ui.text_edit_singleline(access_key);
+                    ui.end_row();
+                    ui.label("Secret key");
+                    ui.add(TextEdit::singleline(secret_key).password(true))
+                        .on_hover_text(
+                            "Only used to sign requests, and forgotten when Pathy closes",
+                        );
+                    ui.end_row();
+                });
+            }
+        }
+        ui.separator();
+        ui.add_enabled_ui(self.transfer.is_none(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Push").clicked() {
+                    self.push(ui.ctx());
+                    ui.close_menu();
+                }
+                if ui.button("Pull").clicked() {
+                    self.pull(ui.ctx());
+                    ui.close_menu();
+                }
+                if self.transfer.is_some() {
+                    ui.spinner();
+                }
+            });
+        });
+    }
+    /// Starts storing the project with the sync backend
+    fn push(&mut self, ctx: &egui::Context) {
+        let result = ron::ser::to_string_pretty(&self.project(), Default::default())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                let adapter = self.sync.adapter()?;
+                Ok(adapter.store(&self.project_name, text, ctx))
+            });
+        match result {
+            Ok(pending) => self.transfer = Some(Transfer::Push(pending)),
+            Err(e) => self.toasts.add(format!("Push failed: {e}")),
+        }
+    }
+    /// Starts fetching the project stored by the sync backend
+    fn pull(&mut self, ctx: &egui::Context) {
+        match self.sync.adapter() {
+            Ok(adapter) => {
+                let pending = adapter.load(&self.project_name, ctx);
+                self.transfer = Some(Transfer::Pull(pending));
+            }
+            Err(e) => self.toasts.add(format!("Pull failed: {e}")),
+        }
+    }
+    /// Handles a finished push or pull
+    fn poll_transfer(&mut self) {
+        let Some(transfer) = &self.transfer else {
+            return;
+        };
+        match transfer {
+            Transfer::Push(pending) => {
+                let Some(result) = pending.take() else {
+                    return;
+                };
+                self.transfer = None;
+                match result {
+                    Ok(()) => self.toasts.add(format!("Pushed {}", self.project_name)),
+                    Err(e) => self.toasts.add(format!("Push failed: {e}")),
+                }
+            }
+            Transfer::Pull(pending) => {
+                let Some(result) = pending.take() else {
+                    return;
+                };
+                self.transfer = None;
+                self.pulled(result);
+            }
+        }
+    }
+    /// Compares a pulled project with the open one.
+    /// If they differ, the user picks which parts to keep.
+    fn pulled(&mut self, text: Result<Option<String>, String>) {
+        let result = text.and_then(|text| text.map(|text| Project::parse(&text)).transpose());
+        match result {
+            Ok(Some(remote)) => {
+                let local = self.project();
+                let differences = local.differences(&remote);
+                if differences.is_empty() {
+                    self.toasts
+                        .add(format!("{} is up to date", self.project_name));
+                } else if local.paths.iter().all(|p| p.points.is_empty()) {
+                    self.load_project(remote);
+                } else {
+                    self.conflict = Some((remote, differences, Vec::new()));
+                }
+            }
+            Ok(None) => self
+                .toasts
+                .add(format!("No project named {}", self.project_name)),
+            Err(e) => self.toasts.add(format!("Pull failed: {e}")),
+        }
+    }
+    /// The open project, as it would be synced
+    fn project(&self) -> Project {
+        let mut paths = self.paths.clone();
+        let open = &mut paths[self.current];
+        open.points = self
+            .points
+            .iter()
+            .map(|p| p.borrow().clone().into())
+            .collect();
+        open.constraints = self.constraints.clone();
+        Project::new(
+            paths,
+            self.current,
+            self.variables.clone(),
+            self.actions.clone(),
+        )
+    }
+    /// Replaces the open project, backing up the open path if it changes.
+    /// The open path stays open if it's still in the project.
+    fn load_project(&mut self, project: Project) {
+        if project.paths.is_empty() {
+            self.toasts
+                .add(format!("{} has no paths to pull", self.project_name));
+            return;
+        }
+        let id = self.paths[self.current].id;
+        if !self.points.is_empty()
+            && self
+                .project()
+                .differences(&project)
+                .contains(&Part::Path(id))
+        {
+            self.backup();
+        }
+        let open = project
+            .paths
+            .iter()
+            .position(|p| p.id == id)
+            .unwrap_or(project.current.min(project.paths.len() - 1));
+        self.paths = project.paths;
+        self.variables = project.variables;
+        self.actions = project.actions;
+        self.load_path(open);
+        self.journal.record("Pulled the project");
+        self.toasts.add(format!("Pulled {}", self.project_name));
+    }
+    /// Adds a point at a field position, at whichever end of the path is being extended
+    fn create_point(&mut self, ui: &Ui, Pos2 { x, y }: Pos2) -> Rc<RefCell<BezPoint>> {
+        if self.points.is_empty() {
+            self.points
+                .push(BezPoint::new(x, y, x - 10.0, y, x + 10.0, y));
+        } else if self.from_start {
+            let point = self.points[0].borrow().before(x, y);
+            self.points.insert(0, point);
+        } else {
+            let point = self.points.last().unwrap().borrow().after(x, y);
+            self.points.push(point);
+            // setup initial animation value
+            ui.ctx().animate_value_with_time(
+                ui.make_persistent_id(self.points.last().unwrap().borrow().id),
+                0.0,
+                0.5,
+            );
+        }
+        let created = if self.from_start {
+            self.points.first()
+        } else {
+            self.points.last()
+        }
+        .unwrap()
+        .clone();
+        created.borrow_mut().mode = self.handle_mode;
+        if self.wall_snap {
+            transform::snap_to_wall(
+                &mut created.borrow_mut(),
+                self.size,
+                self.wall_reach,
+                self.wall_offset,
+            );
+        }
+        created
+    }
+    /// Aborts the drag in progress, if any, putting the point back where it started
+    fn cancel_drag(&mut self) {
+        let Some(point) = self.selected.take() else {
+            return;
+        };
+        point.borrow_mut().locked = false;
+        self.drag_cancelled = true;
+        self.journal
+            .record(format!("Cancelled dragging {}", self.describe(&point)));
+        let Some(parent) = point.borrow().parent.upgrade() else {
+            return;
+        };
+        let mut parent = parent.borrow_mut();
+        // Translating also resets the anchor's tracked position, so the handles don't jump later
+        let [pos, cp1, cp2] = self.pre_drag;
+        let offset = pos - Pos2::from(parent.pos.borrow().clone());
+        parent.translate(offset.x, offset.y);
+        for (handle, start) in [(&parent.cp1, cp1), (&parent.cp2, cp2)] {
+            let mut handle = handle.borrow_mut();
+            Pos2 {
+                x: handle.x,
+                y: handle.y,
+            } = start;
+        }
+        drop(parent);
+        self.generate();
+    }
+    /// Names an anchor or handle for the edit journal, by where its point is in the path
+    fn describe(&self, point: &Rc<RefCell<Point>>) -> String {
+        let Some(parent) = point.borrow().parent.upgrade() else {
+            return "a removed point".into();
+        };
+        let i = self
+            .points
+            .iter()
+            .position(|p| Rc::ptr_eq(p, &parent))
+            .map_or(0, |i| i + 1);
+        let parent = parent.borrow();
+        if Rc::ptr_eq(&parent.cp1, point) {
+            format!("the entry handle of point {i}")
+        } else if Rc::ptr_eq(&parent.cp2, point) {
+            format!("the exit handle of point {i}")
+        } else {
+            format!("point {i}")
+        }
+    }
+    /// Draws the issue report form
+    fn report_window(&mut self, ui: &mut Ui) {
+        ui.label("Describe what went wrong and what you expected instead:");
+        ui.add(
+            TextEdit::multiline(&mut self.report_description)
+                .desired_rows(4)
+                .desired_width(f32::INFINITY),
+        );
+        ui.label(
+            "The report includes the app version, your settings, every path with its constraints, \
+             variables, actions and your recent edits. The project name and sync settings are left out, \
+             and names, notes, tags, markers and robot code are replaced with placeholders.",
+        );
+        if ui.button("Download report").clicked() {
+            let report = Report::new(
+                self.report_description.clone(),
+                Preset::capture(self),
+                self.project(),
+                self.journal.edits().cloned().collect(),
+            );
+            let result = report.bundle().and_then(|text| {
+                files::export(
+                    &format!("pathy-report-{}.ron", files::timestamp()),
+                    text.as_bytes(),
+                )
+            });
+            match result {
+                Ok(location) => self.toasts.add(format!("Report saved to {location}")),
+                Err(e) => self.toasts.add(format!("Report failed: {e}")),
+            }
+        }
+    }
+    /// Draws the popup for typing an exact position for a point
+    fn position_window(&mut self, ctx: &egui::Context) {
+        let Some((point, at, typed)) = &mut self.typing else {
+            return;
+        };
+        let mut done = None;
+        egui::Window::new("Position")
+            .collapsible(false)
+            .resizable(false)
+            .fixed_pos(*at)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut typed.x).prefix("x: ").suffix(" in"));
+                    ui.add(DragValue::new(&mut typed.y).prefix("y: ").suffix(" in"));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("OK").clicked() || ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        done = Some(true);
+                    }
+                    if ui.button("Cancel").clicked()
+                        || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                    {
+                        done = Some(false);
+                    }
+                });
+            });
+        if done == Some(true) {
+            let (point, typed) = (point.clone(), *typed);
+            self.journal.record(format!(
+                "Typed ({:.1}, {:.1}) for {}",
+                typed.x,
+                typed.y,
+                self.describe(&point)
+            ));
+            let mut point = point.borrow_mut();
+            Pos2 {
+                x: point.x,
+                y: point.y,
+            } = typed;
+            // Lets the handles follow as if the point had been dragged
+            point.editing = true;
+        }
+        if done.is_some() {
+            self.typing = None;
+            self.revalidate = true;
+        }
+    }
+    /// Offers ways to rescue saved state that failed to load
+    fn recovery_window(&mut self, ctx: &egui::Context) {
+        if self.corrupt.is_empty() {
+            return;
+        }
+        let mut export = None;
+        let mut salvage = None;
+        let mut discard = None;
+        egui::Window::new("Safe mode")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("Some saved data couldn't be loaded. It won't be overwritten until you choose what to do with it.");
+                for (i, corrupt) in self.corrupt.iter().enumerate() {
+                    ui.separator();
+                    let name = if corrupt.key == "path" { "Path" } else { "Settings" };
+                    ui.strong(name);
+                    ui.label(&corrupt.error);
+                    ui.horizontal(|ui| {
+                        if ui.button("Export raw data").clicked() {
+                            export = Some(i);
+                        }
+                        if corrupt.key == "path"
+                            && ui
+                                .button("Recover readable points")
+                                .on_hover_text("Replace the open path with every point that can still be read")
+                                .clicked()
+                        {
+                            salvage = Some(i);
+                        }
+                        if ui
+                            .button("Discard")
+                            .on_hover_text("Let it be overwritten the next time the app saves")
+                            .clicked()
+                        {
+                            discard = Some(i);
+                        }
+                    });
+                }
+            });
+        if let Some(i) = export {
+            let corrupt = &self.corrupt[i];
+            let name = format!("pathy-{}-{}.ron", corrupt.key, files::timestamp());
+            match files::backup(&name, corrupt.raw.as_bytes()) {
+                Ok(location) => self.toasts.add(format!("Raw data saved to {location}")),
+                Err(e) => self.toasts.add(format!("Export failed: {e}")),
+            }
+        }
+        if let Some(i) = salvage {
+            let saved = recovery::salvage(&self.corrupt.remove(i).raw);
+            if !self.points.is_empty() {
+                self.backup();
+            }
+            self.points = saved
+                .into_iter()
+                .map(|p| BezPoint::load(p.into()))
+                .collect();
+            self.selection.clear();
+            self.inspecting = None;
+            self.toasts
+                .add(format!("Recovered {} points", self.points.len()));
+            self.validate();
+            self.generate();
+        }
+        if let Some(i) = discard {
+            self.corrupt.remove(i);
+        }
+    }
+    /// Draws the choice between the open and pulled versions of each differing part
+    fn conflict_window(&mut self, ctx: &egui::Context) {
+        if self.conflict.is_none() {
+            return;
+        }
+        let local = self.project();
+        let Some((remote, differences, chosen)) = &mut self.conflict else {
+            return;
+        };
+        let mut resolved = None;
+        egui::Window::new("Resolve sync conflict")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "The pulled copy of {} differs from the open one. Pick which version of each part to keep.",
+                    self.project_name
+                ));
+                egui::Grid::new("conflict").num_columns(3).show(ui, |ui| {
+                    for part in differences.iter() {
+                        ui.label(local.label(*part, remote));
+                        let mut take = chosen.contains(part);
+                        ui.selectable_value(
+                            &mut take,
+                            false,
+                            format!("Open ({})", local.summary(*part)),
+                        );
+                        ui.selectable_value(
+                            &mut take,
+                            true,
+                            format!("Pulled ({})", remote.summary(*part)),
+                        );
+                        chosen.retain(|p| p != part);
+                        if take {
+                            chosen.push(*part);
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        resolved = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        resolved = Some(false);
+                    }
+                });
+            });
+        match resolved {
+            Some(true) => {
+                if let Some((remote, _, chosen)) = self.conflict.take() {
+                    self.load_project(local.merge(remote, &chosen));
+                }
+            }
+            Some(false) => self.conflict = None,
+            None => {}
+        }
+    }
+    /// Exports the editor settings as a preset file.
+    fn export_preset(&mut self) {
+        let result = ron::ser::to_string_pretty(&Preset::capture(self), Default::default())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                files::export(
+                    &format!("pathy-{}.{}", files::timestamp(), preset::EXTENSION),
+                    text.as_bytes(),
+                )
+            });
+        match result {
+            Ok(location) => self.toasts.add(format!("Preset saved to {location}")),
+            Err(e) => self.toasts.add(format!("Preset export failed: {e}")),
+        }
+    }
+    /// Imports a preset file's settings.
+    fn import_preset(&mut self, bytes: &[u8]) {
+        let preset = std::str::from_utf8(bytes)
+            .map_err(|e| e.to_string())
+            .and_then(|text| ron::from_str::<Preset>(text).map_err(|e| e.to_string()));
+        match preset {
+            Ok(preset) => {
+                if preset.apply(self) {
+                    self.toasts.add("Preset imported");
+                } else {
+                    self.toasts
+                        .add("Preset imported, except the field size since a path exists");
+                }
+                self.generate();
+            }
+            Err(e) => self.toasts.add(format!("Preset import failed: {e}")),
+        }
+    }
+    /// Adds a point to the selection, or removes it if already selected
+    fn toggle_selection(&mut self, id: Uuid) {
+        match self.selection.iter().position(|s| *s == id) {
+            Some(i) => {
+                self.selection.remove(i);
+            }
+            None => self.selection.push(id),
+        }
+    }
+    /// Removes the selected points, keeping pinned ones
+    fn delete_selection(&mut self) {
+        let selection = self.selection.clone();
+        let doomed = |p: &Rc<RefCell<BezPoint>>| {
+            let p = p.borrow();
+            !p.pinned && selection.contains(&p.id)
+        };
+        let count = self.points.iter().filter(|p| doomed(p)).count();
+        if count < self.selection.len() {
+            self.toasts.add("Unpin the points to delete them");
+        }
+        if count == 0 {
+            return;
+        }
+        if count >= BACKUP_TRIM_THRESHOLD {
+            self.backup();
+        }
+        self.journal
+            .record(format!("Deleted {count} selected points"));
+        self.points.retain(|p| !doomed(p));
+        self.selection
+            .retain(|id| self.points.iter().any(|p| p.borrow().id == *id));
+        self.generate();
+    }
+    /// Draws the group edit controls for the selected points
+    fn selection_panel(&mut self, ui: &mut Ui) {
+        self.selection
+            .retain(|id| self.points.iter().any(|p| p.borrow().id == *id));
+        if self.selection.is_empty() {
+            ui.label("Shift+click points to select them, or ctrl+A for all");
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label(format!("{} selected", self.selection.len()));
+            if ui.button("Clear (esc)").clicked() {
+                self.selection.clear();
+            }
+            if ui.button("Delete (del)").clicked() {
+                self.delete_selection();
+            }
+        });
+        egui::ComboBox::from_label("Apply to")
+            .selected_text(format!("{:?}", self.select_filter))
+            .show_ui(ui, |ui| {
+                for filter in Filter::ALL {
+                    ui.selectable_value(&mut self.select_filter, filter, format!("{filter:?}"));
+                }
+            });
+        ui.add_enabled_ui(self.select_filter != Filter::Anchors, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.handle_scale)
+                        .range(0.0..=1000.0)
+                        .suffix("%"),
+                );
+                if ui.button("Scale handles").clicked() {
+                    selection::scale_handles(
+                        &self.points,
+                        &self.selection,
+                        self.select_filter,
+                        self.handle_scale / 100.0,
+                    );
+                    self.generate();
+                }
+            });
+        });
+        ui.add_enabled_ui(self.selection.len() > 1, |ui| {
+            ui.horizontal(|ui| {
+                for (axis, label, desc) in [
+                    (
+                        Axis::Horizontal,
+                        "Align horizontally",
+                        "Give every point the first one's y",
+                    ),
+                    (
+                        Axis::Vertical,
+                        "Align vertically",
+                        "Give every point the first one's x",
+                    ),
+                ] {
+                    if ui.button(label).on_hover_text(desc).clicked() {
+                        selection::align(&self.points, &self.selection, axis);
+                        self.journal.record(format!(
+                            "Aligned {} selected points {}",
+                            self.selection.len(),
+                            label.to_lowercase().trim_start_matches("align ")
+                        ));
+                        self.release_expressions();
+                        self.generate();
+                    }
+                }
+            });
+        });
+        ui.add_enabled_ui(self.selection.len() > 2, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Distribute");
+                for (along_curve, label, desc) in [
+                    (
+                        false,
+                        "On line",
+                        "Space points evenly on the line from the first to the last",
+                    ),
+                    (
+                        true,
+                        "On curve",
+                        "Space points evenly along the current curve",
+                    ),
+                ] {
+                    if ui.button(label).on_hover_text(desc).clicked() {
+                        selection::distribute(&self.points, &self.selection, along_curve);
+                        self.journal.record(format!(
+                            "Distributed {} selected points {}",
+                            self.selection.len(),
+                            label.to_lowercase()
+                        ));
+                        self.release_expressions();
+                        self.generate();
+                    }
+                }
+            });
+        });
+        ui.label("Arrow keys nudge the selection");
+        ui.label("Ctrl+C copies the selected range, ctrl+V pastes it");
+    }
+    /// Draws the variable list, returning whether any variable was changed
+    fn variable_list(&mut self, ui: &mut Ui) -> bool {
+        let mut updated = false;
+        let mut removed = None;
+        ui.horizontal(|ui| {
+            ui.label("Showing");
+            for alliance in [Alliance::Red, Alliance::Blue] {
+                updated |= ui
+                    .selectable_value(&mut self.alliance, alliance, format!("{alliance:?}"))
+                    .changed();
+            }
+        });
+        for (i, variable) in self.variables.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                updated |= ui
+                    .add(TextEdit::singleline(&mut variable.name).desired_width(100.0))
+                    .changed();
+                if variable.per_alliance() {
+                    for (value, color) in [
+                        (&mut variable.red, Color32::RED),
+                        (&mut variable.blue, Color32::LIGHT_BLUE),
+                    ] {
+                        let value = value.get_or_insert(variable.value);
+                        ui.colored_label(color, "■");
+                        updated |= ui.add(DragValue::new(value).speed(0.1)).changed();
+                    }
+                } else {
+                    updated |= ui
+                        .add(DragValue::new(&mut variable.value).speed(0.1))
+                        .changed();
+                }
+                let mut split = variable.per_alliance();
+                if ui
+                    .toggle_value(&mut split, "R/B")
+                    .on_hover_text("Use a different value for each alliance")
+                    .changed()
+                {
+                    if split {
+                        variable.red = Some(variable.value);
+                        variable.blue = Some(variable.value);
+                    } else {
+                        variable.red = None;
+                        variable.blue = None;
+                    }
+                    updated = true;
+                }
+                if ui.small_button("x").on_hover_text("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.variables.remove(i);
+            updated = true;
+        }
+        if ui
+            .button("Add variable")
+            .on_hover_text("Variables can be used in point expressions, e.g. offset + 4in")
+            .clicked()
+        {
+            self.variables.push(Variable {
+                name: format!("var{}", self.variables.len() + 1),
+                value: 0.0,
+                red: None,
+                blue: None,
+            });
+        }
+        updated
+    }
+    /// Clears expressions from points an edit moved off them, so they stay where it put them
+    fn release_expressions(&mut self) {
+        let released = expr::release(&self.points, &expr::resolve(&self.variables, self.alliance));
+        if released > 0 {
+            self.toasts.add(format!(
+                "Cleared the expressions of {released} moved point{}",
+                if released == 1 { "" } else { "s" }
+            ));
+        }
+    }
+    /// Moves points driven by expressions to their evaluated positions
+    fn apply_expressions(&mut self) {
+        if expr::apply(&self.points, &expr::resolve(&self.variables, self.alliance)) {
+            self.revalidate = true;
+        }
+    }
+    /// Draws the constraint list and editor, returning whether any constraint was changed
+    fn constraint_list(&mut self, ui: &mut Ui) -> bool {
+        let mut updated = false;
+        let index = |id: &Uuid| {
+            self.points
+                .iter()
+                .position(|p| p.borrow().id == *id)
+                .map_or("?".to_string(), |i| (i + 1).to_string())
+        };
+        let mut removed = None;
+        for (i, constraint) in self.constraints.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                match constraint {
+                    Constraint::Offset { a, b, dx, dy } => {
+                        ui.label(format!("Point {} from {}", index(b), index(a)));
+                        updated |= ui
+                            .add(DragValue::new(dx).speed(0.1).suffix(" in"))
+                            .changed();
+                        updated |= ui
+                            .add(DragValue::new(dy).speed(0.1).suffix(" in"))
+                            .changed();
+                    }
+                    Constraint::Collinear(ids) => {
+                        let names: Vec<String> = ids.iter().map(index).collect();
+                        ui.label(format!("Points {} collinear", names.join(", ")));
+                    }
+                }
+                if ui.small_button("x").on_hover_text("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.constraints.remove(i);
+            updated = true;
+        }
+        // New constraints are made from typed point numbers
+        let picked: Vec<(Uuid, Pos2)> = self
+            .constraint_points
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter_map(|n| n.parse::<usize>().ok())
+            .filter_map(|n| self.points.get(n.wrapping_sub(1)))
+            .map(|p| (p.borrow().id, Pos2::from(p.borrow().pos.borrow().clone())))
+            .collect();
+        let ids: Vec<Uuid> = picked.iter().map(|(id, _)| *id).collect();
+        let distinct = ids.iter().enumerate().all(|(i, id)| !ids[..i].contains(id));
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.constraint_points)
+                    .hint_text("Points, e.g. 1 2")
+                    .desired_width(100.0),
+            );
+            if ui
+                .add_enabled(ids.len() == 2 && distinct, egui::Button::new("Offset"))
+                .on_hover_text("Keep the second point at its current offset from the first")
+                .clicked()
+            {
+                let (a, b) = (picked[0].1, picked[1].1);
+                self.constraints.push(Constraint::Offset {
+                    a: ids[0],
+                    b: ids[1],
+                    dx: b.x - a.x,
+                    dy: b.y - a.y,
+                });
+                self.constraint_points.clear();
+                updated = true;
+            }
+            if ui
+                .add_enabled(ids.len() >= 3 && distinct, egui::Button::new("Collinear"))
+                .on_hover_text("Keep three or more different points on one line")
+                .clicked()
+            {
+                self.constraints.push(Constraint::Collinear(ids.clone()));
+                self.constraint_points.clear();
+                updated = true;
+            }
+        });
+        updated
+    }
+    /// Re-applies constraints, holding the point being moved in place
+    fn solve_constraints(&mut self) {
+        let ids: Vec<Uuid> = self.points.iter().map(|p| p.borrow().id).collect();
+        self.constraints
+            .retain(|c| c.points().iter().all(|id| ids.contains(id)));
+        if self.constraints.is_empty() {
+            return;
+        }
+        let fixed = self
+            .points
+            .iter()
+            .find(|p| {
+                let pos = p.borrow().pos.clone();
+                let pos = pos.borrow();
+                pos.locked || pos.editing
+            })
+            .map(|p| p.borrow().id);
+        if constraint::solve(&self.points, &self.constraints, fixed) {
+            self.generate();
+        }
+    }
+    /// Draws the edit menu with whole-path commands
+    fn edit_menu(&mut self, ui: &mut Ui) {
+        for (axis, label) in [
+            (Axis::Vertical, "Mirror left/right"),
+            (Axis::Horizontal, "Mirror top/bottom"),
+        ] {
+            if ui.button(label).clicked() {
+                transform::mirror(&self.points, &mut self.constraints, axis, self.size);
+                self.journal.record(label);
+                // The start and end poses belong to the route, so they're reflected with it
+                let path = &mut self.paths[self.current];
+                if let Some(start) = &mut path.start {
+                    transform::mirror_pose(start, axis, self.size);
+                }
+                path.end.heading = path
+                    .end
+                    .heading
+                    .map(|h| transform::reflect_heading(h, axis));
+                self.release_expressions();
+                self.revalidate = true;
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        ui.menu_button("Test patterns", |ui| {
+            ui.add(
+                DragValue::new(&mut self.pattern_size)
+                    .range(1.0..=self.size)
+                    .prefix("Size: ")
+                    .suffix(" in"),
+            );
+            for pattern in Pattern::ALL {
+                if ui
+                    .button(format!("{pattern:?}"))
+                    .on_hover_text("Replace the path with this pattern, centered on the field")
+                    .clicked()
+                {
+                    if !self.points.is_empty() {
+                        self.backup();
+                    }
+                    let center = pos2(self.size / 2.0, self.size / 2.0);
+                    self.points = pattern.build(self.pattern_size, center);
+                    self.generate();
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Move by");
+            ui.add(
+                DragValue::new(&mut self.translation.x)
+                    .speed(0.1)
+                    .prefix("x: ")
+                    .suffix(" in"),
+            );
+            ui.add(
+                DragValue::new(&mut self.translation.y)
+                    .speed(0.1)
+                    .prefix("y: ")
+                    .suffix(" in"),
+            );
+        });
+        if ui.button("Move path").clicked() {
+            transform::translate(&self.points, self.translation.x, self.translation.y);
+            self.journal.record(format!(
+                "Moved path by ({:.1}, {:.1})",
+                self.translation.x, self.translation.y
+            ));
+            if let Some(start) = &mut self.paths[self.current].start {
+                start.pos += self.translation;
+            }
+            self.release_expressions();
+            self.revalidate = true;
+            ui.close_menu();
         }
-    }
-}
-
-impl PathyApp {
-    /// Called once before the first frame.
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        // This is also where you can customize the look and feel of egui using
-        // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
-        let mut fonts = FontDefinitions::default();
-
-        fonts.font_data.insert(
-            "SpaceGrotesk".to_owned(),
-            std::sync::Arc::new(egui::FontData::from_static(include_bytes!(
-                "../assets/SpaceGrotesk-Regular.ttf"
-            ))),
-        );
-        fonts
-            .families
-            .get_mut(&FontFamily::Proportional)
-            .unwrap()
-            .insert(0, "SpaceGrotesk".into());
-        cc.egui_ctx.set_fonts(fonts);
-        // only if in dark mode
-        cc.egui_ctx.style_mut_of(egui::Theme::Dark, |style| {
-            style.visuals.panel_fill = Color32::from_gray(10);
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Rotate");
+            ui.add(DragValue::new(&mut self.rotation).speed(1.0).suffix("°"))
+                .on_hover_text("Clockwise angle");
+            ui.label("about");
+            egui::ComboBox::from_id_salt("pivot")
+                .selected_text(format!("{:?}", self.pivot))
+                .show_ui(ui, |ui| {
+                    for (pivot, desc) in [
+                        (Pivot::Center, "Field center"),
+                        (Pivot::First, "First point"),
+                        (Pivot::Inspected, "Inspected point"),
+                        (Pivot::Custom, "Custom position"),
+                    ] {
+                        ui.selectable_value(&mut self.pivot, pivot, format!("{pivot:?}"))
+                            .on_hover_text(desc);
+                    }
+                });
         });
-
-        // Load previous app state (if any).
-        // Note that you must enable the `persistence` feature for this to work.
-        let mut app: Self = if let Some(storage) = cc.storage {
-            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
-        } else {
-            Default::default()
+        if self.pivot == Pivot::Custom {
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut self.custom_pivot.x)
+                        .prefix("x: ")
+                        .suffix(" in"),
+                );
+                ui.add(
+                    DragValue::new(&mut self.custom_pivot.y)
+                        .prefix("y: ")
+                        .suffix(" in"),
+                );
+            });
+        }
+        let pivot = match self.pivot {
+            Pivot::Center => Some(pos2(self.size / 2.0, self.size / 2.0)),
+            Pivot::First => self
+                .points
+                .first()
+                .map(|p| Pos2::from(p.borrow().pos.borrow().clone())),
+            Pivot::Inspected => self
+                .inspecting
+                .as_ref()
+                .filter(|p| p.borrow().parent.strong_count() > 0)
+                .map(|p| Pos2::from(p.borrow().clone())),
+            Pivot::Custom => Some(self.custom_pivot),
         };
-
-        // load saved path
-        app.points = if let Some(storage) = cc.storage {
-            eframe::get_value::<Vec<SavePoint>>(storage, "path")
-                .unwrap_or_default()
-                .iter()
-                .map(|p| BezPoint::load(p.clone().into()))
-                .collect()
+        // Pinned points stay put when rotating a selection
+        let whole = self.selection.is_empty();
+        let selected: Vec<_> = selection::movable(&self.points, &self.selection)
+            .cloned()
+            .collect();
+        let label = if whole {
+            "Rotate path"
         } else {
-            Vec::new()
+            "Rotate selection"
         };
-
-        // Generate code and load overlay on startup
-        app.generate();
-        app.load_field_overlay();
-        app
+        if ui
+            .add_enabled(pivot.is_some(), egui::Button::new(label))
+            .clicked()
+        {
+            if let Some(pivot) = pivot {
+                if whole {
+                    transform::rotate(&self.points, &mut self.constraints, self.rotation, pivot);
+                    let path = &mut self.paths[self.current];
+                    if let Some(start) = &mut path.start {
+                        transform::rotate_pose(start, self.rotation, pivot);
+                    }
+                    path.end.heading = path
+                        .end
+                        .heading
+                        .map(|h| transform::normalize(h + self.rotation));
+                } else {
+                    // Only offsets between two selected points turn with them; the start and end stay put
+                    let ids: Vec<Uuid> = selected.iter().map(|p| p.borrow().id).collect();
+                    let within = self
+                        .constraints
+                        .iter_mut()
+                        .filter(|c| c.points().iter().all(|id| ids.contains(id)));
+                    transform::rotate(&selected, within, self.rotation, pivot);
+                }
+                let what = if whole {
+                    "path".into()
+                } else {
+                    format!("{} selected points", selected.len())
+                };
+                self.journal.record(format!(
+                    "Rotated {what} by {:.1}° about ({:.1}, {:.1})",
+                    self.rotation, pivot.x, pivot.y
+                ));
+                self.release_expressions();
+                self.revalidate = true;
+            }
+            ui.close_menu();
+        }
+        ui.separator();
+        if ui
+            .button("Export settings preset")
+            .on_hover_text("Drop a preset file on the field to import it")
+            .clicked()
+        {
+            self.export_preset();
+            ui.close_menu();
+        }
+        if ui
+            .button("Import settings preset")
+            .on_hover_text("Use a teammate's editor, field, robot, key and code settings, and add their actions. Paths are kept")
+            .clicked()
+        {
+            match files::pick(&format!(".{}", preset::EXTENSION), Pick::Open) {
+                Ok(()) => self.opening = true,
+                Err(e) => self.toasts.add(e),
+            }
+            ui.close_menu();
+        }
+        if ui
+            .button("Export gallery page")
+            .on_hover_text("Self-contained, read-only HTML page of the path")
+            .clicked()
+        {
+            self.export_gallery();
+            ui.close_menu();
+        }
+        if ui
+            .button("Export statistics")
+            .on_hover_text("Markdown report of the path's length, timing, curvature and waypoints")
+            .clicked()
+        {
+            self.export_stats(stats::Format::Markdown);
+            ui.close_menu();
+        }
+        if ui
+            .button("Export statistics as JSON")
+            .on_hover_text("The same report, for scripts and spreadsheets")
+            .clicked()
+        {
+            self.export_stats(stats::Format::Json);
+            ui.close_menu();
+        }
+        if ui
+            .button("Generate all")
+            .on_hover_text("Code for every path in one file, each named after its path")
+            .clicked()
+        {
+            self.generate_all();
+            ui.close_menu();
+        }
     }
-    /// Update generated code
-    fn generate(&mut self) {
-        self.generated = generate(&self.points, 0.1);
+    /// Draws the editor for the project's action library
+    fn action_library(&mut self, ui: &mut Ui) -> bool {
+        let mut updated = false;
+        let mut removed = None;
+        for (i, action) in self.actions.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                updated |= ui
+                    .add(
+                        TextEdit::singleline(&mut action.name)
+                            .hint_text("name")
+                            .desired_width(100.0),
+                    )
+                    .changed();
+                // Edited as one comma separated list
+                let mut params = action.params.join(", ");
+                if ui
+                    .add(
+                        TextEdit::singleline(&mut params)
+                            .hint_text("parameters")
+                            .desired_width(140.0),
+                    )
+                    .on_hover_text("Comma separated, used in the code as {name}")
+                    .changed()
+                {
+                    action.params = params
+                        .split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect();
+                    updated = true;
+                }
+                if ui.small_button("x").on_hover_text("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+            updated |= ui
+                .add(
+                    TextEdit::multiline(&mut action.template)
+                        .code_editor()
+                        .hint_text("intake.move({speed});")
+                        .desired_rows(2),
+                )
+                .changed();
+            ui.separator();
+        }
+        if let Some(i) = removed {
+            self.actions.remove(i);
+            updated = true;
+        }
+        if ui
+            .button("Add action")
+            .on_hover_text("Actions can be attached to points from the point list")
+            .clicked()
+        {
+            self.actions.push(Action {
+                name: format!("action{}", self.actions.len() + 1),
+                ..Default::default()
+            });
+            updated = true;
+        }
+        updated
+    }
+    /// Draws the script editor
+    fn script_window(&mut self, ui: &mut Ui) {
+        ui.add(
+            TextEdit::multiline(&mut self.script)
+                .code_editor()
+                .hint_text(
+                    "// Points around a circle\n\
+                     for i in 0..8 {\n    \
+                     path.add(70 + 36 * cos(i * 45), 70 + 36 * sin(i * 45)).heading(i * 45 + 90)\n\
+                     }",
+                )
+                .desired_rows(10)
+                .desired_width(f32::INFINITY),
+        );
+        if ui
+            .button("Run")
+            .on_hover_text("Replace the path with the points the script creates")
+            .clicked()
+        {
+            match script::run(&self.script, &expr::resolve(&self.variables, self.alliance)) {
+                Ok(points) => {
+                    if !self.points.is_empty() {
+                        self.backup();
+                    }
+                    for point in &points {
+                        point.borrow_mut().mode = self.handle_mode;
+                    }
+                    self.journal
+                        .record(format!("Ran a script making {} points", points.len()));
+                    self.points = points;
+                    self.script_error = None;
+                    self.generate();
+                }
+                Err(e) => self.script_error = Some(e),
+            }
+        }
+        if let Some(error) = &self.script_error {
+            ui.colored_label(Color32::RED, error);
+        }
+    }
+    /// Converts a screen position to field coordinates, snapping to the grid if enabled
+    fn to_field(&self, pos: Pos2, origin: Pos2) -> Pos2 {
+        let x = (pos.x - origin.x) * (self.size / self.scale as f32);
+        let y = (pos.y - origin.y) * (self.size / self.scale as f32);
+        let grid = self.grid_step();
+        if self.snap && grid > 0.0 {
+            pos2((x / grid).round() * grid, (y / grid).round() * grid)
+        } else {
+            pos2(x, y)
+        }
+    }
+    /// Snapping distance, a fraction of a tile when using tiles
+    fn grid_step(&self) -> f32 {
+        if self.tiles {
+            self.tile_size / self.tile_fraction.max(1) as f32
+        } else {
+            self.grid
+        }
+    }
+    /// Samples along each segment of the open path, in field coordinates.
+    fn segment_samples(&self) -> Vec<Vec<Pos2>> {
+        self.points
+            .windows(2)
+            .map(|pair| analysis::sample(pair, 20))
+            .collect()
+    }
+    /// Formats a field length in the current unit
+    fn length_text(&self, inches: f32) -> String {
+        if self.tiles {
+            format!("{:.2} tiles", inches / self.tile_size)
+        } else {
+            format!("{inches:.1} in")
+        }
     }
     /// Update field image
     #[allow(deprecated)]
     fn load_field_overlay(&mut self) {
+        self.overlay = self
+            .background_image()
+            .and_then(|bytes| RetainedImage::from_image_bytes("", bytes).ok());
+    }
+    /// The encoded image of the chosen background, if any
+    fn background_image(&self) -> Option<&[u8]> {
         match self.background {
-            Background::Game => {
-                self.overlay = Some(
-                    RetainedImage::from_image_bytes(
-                        "",
-                        include_bytes!("../assets/high-stakes-game.png"),
-                    )
-                    .unwrap(),
-                );
-            }
-            Background::Skills => {
-                self.overlay = Some(
-                    RetainedImage::from_image_bytes(
-                        "",
-                        include_bytes!("../assets/high-stakes-skills.png"),
-                    )
-                    .unwrap(),
-                );
-            }
-            Background::Custom => {
-                self.overlay = self
-                    .uploaded
-                    .as_ref()
-                    .and_then(|bytes| RetainedImage::from_image_bytes("", bytes).ok());
-            }
+            Background::Game => Some(include_bytes!("../assets/high-stakes-game.png")),
+            Background::Skills => Some(include_bytes!("../assets/high-stakes-skills.png")),
+            Background::Custom => self.uploaded.as_deref(),
+        }
+    }
+    /// Exports the open path's measurements and waypoints as a report in `format`
+    fn export_stats(&mut self, format: stats::Format) {
+        let path = &self.paths[self.current];
+        let exported = stats::report(format, &path.name, &self.points, &path.driving(&self.robot))
+            .and_then(|report| {
+                files::export(
+                    &format!(
+                        "{}-{}-stats-{}.{}",
+                        slug(&self.project_name, "project"),
+                        path.slug(),
+                        files::timestamp(),
+                        format.extension()
+                    ),
+                    report.as_bytes(),
+                )
+            });
+        match exported {
+            Ok(location) => self.toasts.add(format!("Statistics saved to {location}")),
+            Err(e) => self.toasts.add(format!("Statistics export failed: {e}")),
         }
     }
+    /// Exports a read-only page of the path for sharing publicly
+    fn export_gallery(&mut self) {
+        let path = &self.paths[self.current];
+        let page = gallery::page(
+            &format!("{}: {}", self.project_name, path.name),
+            self.size,
+            path.color,
+            path.start.as_ref().map(|start| self.robot.outline(start)),
+            &self.points,
+            self.background_image(),
+            &self.generated,
+        );
+        match files::export(
+            &format!(
+                "{}-{}-{}.html",
+                slug(&self.project_name, "project"),
+                path.slug(),
+                files::timestamp()
+            ),
+            page.as_bytes(),
+        ) {
+            Ok(location) => self.toasts.add(format!("Page saved to {location}")),
+            Err(e) => self.toasts.add(format!("Page export failed: {e}")),
+        }
+    }
+}
+
+/// How long ago something happened, given the seconds since, like "5 min ago".
+fn ago(seconds: u64) -> String {
+    match seconds {
+        0..=59 => "just now".into(),
+        60..=3599 => format!("{} min ago", seconds / 60),
+        3600..=86399 => format!("{} h ago", seconds / 3600),
+        _ => format!("{} d ago", seconds / 86400),
+    }
+}
+
+/// Lists the handle modes to pick from, returning whether the mode changed.
+fn handle_mode_picker(ui: &mut Ui, mode: &mut HandleMode) -> bool {
+    let mut changed = false;
+    for (option, desc) in [
+        (HandleMode::Mirrored, "Equal length, opposite direction"),
+        (
+            HandleMode::Aligned,
+            "Opposite direction, independent lengths",
+        ),
+        (HandleMode::Broken, "Independent handles"),
+        (
+            HandleMode::Sharp,
+            "No handles, a sharp corner to stop and turn in place at",
+        ),
+    ] {
+        changed |= ui
+            .selectable_value(mode, option, format!("{option:?}"))
+            .on_hover_text(desc)
+            .changed();
+    }
+    changed
 }
 
 impl eframe::App for PathyApp {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        // save app state
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        self.store_path();
+        self.saved_at = files::timestamp();
+        // Leave corrupt state in place until the user has dealt with it
+        let held = |key: &str| self.corrupt.iter().any(|c| c.key == key);
+        if !held(eframe::APP_KEY) {
+            // save app state
+            eframe::set_value(storage, eframe::APP_KEY, self);
+            let field = self.uploaded.as_deref().unwrap_or_default();
+            storage.set_string(
+                FIELD_KEY,
+                base64::engine::general_purpose::STANDARD.encode(field),
+            );
+        }
+        if held("path") {
+            return;
+        }
         let saved: Vec<SavePoint> = self
             .points
             .iter()
@@ -205,39 +3442,405 @@ impl eframe::App for PathyApp {
 
     /// Called each time the UI needs repainting, which may be many times per second.
     #[allow(deprecated)]
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         // Put your widgets into a `SidePanel`, `TopBottomPanel`, `CentralPanel`, `Window` or `Area`.
         // For inspiration and more examples, go to https://emilk.github.io/egui
 
+        self.toasts.show(ctx);
+        let now = ctx.input(|i| i.time);
+        if now - self.autosaved > AUTOSAVE_INTERVAL {
+            self.autosaved = now;
+            self.autosave(frame);
+        }
+        self.poll_transfer();
+        if self.picking_telemetry {
+            match files::picked(Pick::Telemetry) {
+                Some(Picked::File(name, bytes)) => {
+                    self.picking_telemetry = false;
+                    self.load_telemetry(name, &bytes);
+                }
+                Some(Picked::Cancelled) => self.picking_telemetry = false,
+                // The browser reads the file in the background
+                None => ctx.request_repaint_after(std::time::Duration::from_millis(200)),
+            }
+        }
+        self.measure_telemetry();
+        self.simulate();
+        if self.opening {
+            match files::picked(Pick::Open) {
+                Some(Picked::File(name, bytes)) => {
+                    self.opening = false;
+                    if name.ends_with(preset::EXTENSION) {
+                        self.import_preset(&bytes);
+                    } else if import::importable(&name) {
+                        self.import(&name, &bytes);
+                    } else {
+                        self.request_open(name, bytes);
+                    }
+                }
+                Some(Picked::Cancelled) => self.opening = false,
+                // The browser reads the file in the background
+                None => ctx.request_repaint_after(std::time::Duration::from_millis(200)),
+            }
+        }
+        if self.fetching {
+            if let Some(result) = files::fetched() {
+                self.fetching = false;
+                match result {
+                    Ok(bytes) => {
+                        self.background = Background::Custom;
+                        self.uploaded = Some(bytes.into());
+                        self.load_field_overlay();
+                        match self.overlay {
+                            Some(_) => self.toasts.add("Field image loaded"),
+                            None => self.toasts.add("The link isn't an image Pathy can read"),
+                        }
+                    }
+                    Err(e) => self
+                        .toasts
+                        .add(format!("Could not load the field image: {e}")),
+                }
+            }
+        }
+        // Screenshots of the field diagram are usually on the clipboard rather than saved
+        if let Some(bytes) = files::pasted_image(ctx).filter(|_| !ctx.wants_keyboard_input()) {
+            self.background = Background::Custom;
+            self.uploaded = Some(bytes.into());
+            self.load_field_overlay();
+            match self.overlay {
+                Some(_) => self.toasts.add("Field image pasted"),
+                None => self.toasts.add("The pasted image could not be read"),
+            }
+        }
+        // Structural edits change the number of points, other edits ask for it
+        self.apply_expressions();
+        if self.points.len() != self.validated || self.revalidate {
+            self.validate();
+            self.generate();
+        }
+        self.solve_constraints();
+
+        // Editing flags only last for the frame they were set in
+        for point in &self.points {
+            let point = point.borrow();
+            point.pos.borrow_mut().editing = false;
+            point.cp1.borrow_mut().editing = false;
+            point.cp2.borrow_mut().editing = false;
+        }
+
+        /* SHORTCUTS */
+        // Consumed before the cursor mode keys so ctrl+d doesn't also toggle Delete
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::D)) {
+            if let Some(point) = self.inspected_point() {
+                self.duplicate(&point);
+            }
+        }
+        if !ctx.wants_keyboard_input() {
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::A)) {
+                self.selection = self.points.iter().map(|p| p.borrow().id).collect();
+            }
+            if !self.selection.is_empty()
+                && ctx.input_mut(|i| {
+                    i.consume_key(egui::Modifiers::NONE, egui::Key::Delete)
+                        | i.consume_key(egui::Modifiers::NONE, egui::Key::Backspace)
+                })
+            {
+                self.delete_selection();
+            }
+            for event in ctx.input(|i| i.events.clone()) {
+                match event {
+                    egui::Event::Copy => self.copy_points(ctx),
+                    egui::Event::Paste(text) => self.paste_points(&text),
+                    _ => {}
+                }
+            }
+        }
+        // Escape backs out of a drag or mode first, then clears the selection
+        if self.typing.is_none()
+            && !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
+        {
+            if self.selected.is_some() || self.cursor_mode != CursorMode::Default {
+                self.cancel_drag();
+                self.cursor_mode = CursorMode::Default;
+            } else {
+                self.selection.clear();
+            }
+        }
+        if !self.selection.is_empty() && !ctx.wants_keyboard_input() {
+            let step = if self.snap { self.grid_step() } else { 1.0 };
+            let (mut dx, mut dy) = (0.0, 0.0);
+            ctx.input_mut(|i| {
+                for (key, x, y) in [
+                    (egui::Key::ArrowLeft, -step, 0.0),
+                    (egui::Key::ArrowRight, step, 0.0),
+                    (egui::Key::ArrowUp, 0.0, -step),
+                    (egui::Key::ArrowDown, 0.0, step),
+                ] {
+                    if i.consume_key(egui::Modifiers::NONE, key) {
+                        dx += x;
+                        dy += y;
+                    }
+                }
+            });
+            if dx != 0.0 || dy != 0.0 {
+                selection::nudge(&self.points, &self.selection, self.select_filter, dx, dy);
+                self.journal.record(format!(
+                    "Nudged {} selected points by ({dx:.1}, {dy:.1})",
+                    self.selection.len()
+                ));
+                self.release_expressions();
+                self.generate();
+            }
+        }
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
 
             egui::menu::bar(ui, |ui| {
                 ui.label("Pathy v2.0.0");
                 ui.separator();
+                ui.menu_button("File", |ui| {
+                    if ui
+                        .button("Save project")
+                        .on_hover_text(
+                            "Save every path and setting to a file to share or keep in git. \
+                            Browsers that allow it save back to the file the project was opened from",
+                        )
+                        .clicked()
+                    {
+                        self.save_project();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Export bundle")
+                        .on_hover_text(
+                            "Download the project with the field image inside, so it opens looking the same anywhere",
+                        )
+                        .clicked()
+                    {
+                        self.export_bundle();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Open project")
+                        .on_hover_text("Or drop a project file on the field")
+                        .clicked()
+                    {
+                        match files::pick(
+                            &format!("{PROJECT_EXTENSION},{BUNDLE_EXTENSION}"),
+                            Pick::Open,
+                        ) {
+                            Ok(()) => self.opening = true,
+                            Err(e) => self.toasts.add(e),
+                        }
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Share link")
+                        .on_hover_text("Copy a link that opens the current path")
+                        .clicked()
+                    {
+                        self.share_link(ui.ctx());
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Import path")
+                        .on_hover_text(
+                            "Open a route from another tool as new paths, like a Choreo trajectory, LemLib path, spreadsheet of waypoints or odometry log",
+                        )
+                        .clicked()
+                    {
+                        match files::pick(&import::EXTENSIONS.join(","), Pick::Open) {
+                            Ok(()) => self.opening = true,
+                            Err(e) => self.toasts.add(e),
+                        }
+                        ui.close_menu();
+                    }
+                    ui.menu_button("Recent projects", |ui| {
+                        if self.recent.is_empty() {
+                            ui.label("Projects you save or open are listed here");
+                        }
+                        let now = files::timestamp();
+                        let mut opened = None;
+                        for (i, recent) in self.recent.iter().enumerate() {
+                            let resp = ui
+                                .horizontal(|ui| {
+                                    let (rect, _) = ui.allocate_exact_size(
+                                        Vec2::splat(32.0),
+                                        egui::Sense::hover(),
+                                    );
+                                    ui.painter().rect_stroke(
+                                        rect,
+                                        2.0,
+                                        Stroke::new(1.0, Color32::DARK_GRAY),
+                                    );
+                                    let line = recent
+                                        .thumbnail
+                                        .iter()
+                                        .map(|p| rect.min + p.to_vec2() * rect.width())
+                                        .collect();
+                                    ui.painter().add(egui::Shape::line(
+                                        line,
+                                        Stroke::new(1.5, recent.color),
+                                    ));
+                                    let current = recent.name == self.project_name;
+                                    let clicked =
+                                        ui.selectable_label(current, &recent.name).clicked();
+                                    ui.weak(ago(now.saturating_sub(recent.time)));
+                                    clicked
+                                })
+                                .inner;
+                            if resp {
+                                opened = Some(i);
+                            }
+                        }
+                        if let Some(i) = opened {
+                            self.open_recent(i);
+                            ui.close_menu();
+                        }
+                    });
+                    ui.menu_button("Restore autosave", |ui| {
+                        if self.autosaves.is_empty() {
+                            ui.label("No autosaves yet");
+                        }
+                        let now = files::timestamp();
+                        let mut restored = None;
+                        for snapshot in &self.autosaves {
+                            if ui.button(ago(now.saturating_sub(snapshot.time))).clicked() {
+                                restored = Some(snapshot.clone());
+                            }
+                        }
+                        if let Some(snapshot) = restored {
+                            self.restore(&snapshot);
+                            ui.close_menu();
+                        }
+                    });
+                });
+                ui.separator();
                 ui.label("Field Size: ");
                 ui.add_enabled_ui(self.points.is_empty(), |ui| {
-                    ui.add(egui::DragValue::new(&mut self.size).suffix(" inches"));
+                    ui.add(
+                        egui::DragValue::new(&mut self.size)
+                            .range(SIZE_RANGE)
+                            .suffix(" inches"),
+                    );
                 })
                 .response
                 .on_disabled_hover_text("Field size may not be changed once path is created.");
                 ui.label("Field Scale: ");
                 ui.add(
                     egui::DragValue::new(&mut self.scale)
+                        .range(SCALE_RANGE)
                         .suffix("px")
                         .speed(2.5),
                 )
                 .on_hover_text("Screen scale of the field");
                 ui.label("Point Density: ");
-                ui.add(egui::DragValue::new(&mut self.steps).speed(2.5))
+                ui.add(
+                    egui::DragValue::new(&mut self.steps)
+                        .range(STEPS_RANGE)
+                        .speed(2.5),
+                )
                     .on_hover_text("Number of points to display for each curve");
+                ui.checkbox(&mut self.snap, "Snap").on_hover_text(format!(
+                    "Snap points to grid ({})",
+                    self.keys.snap.name().to_lowercase()
+                ));
+                if self.tiles {
+                    let label = |n: u32| match n {
+                        1 => "1 tile".to_string(),
+                        n => format!("1/{n} tile"),
+                    };
+                    ui.add_enabled_ui(self.snap, |ui| {
+                        egui::ComboBox::from_id_salt("tile_fraction")
+                            .selected_text(label(self.tile_fraction))
+                            .show_ui(ui, |ui| {
+                                for n in TILE_FRACTIONS {
+                                    ui.selectable_value(&mut self.tile_fraction, n, label(n));
+                                }
+                            })
+                    })
+                    .response
+                    .on_hover_text("Grid spacing");
+                } else {
+                    ui.add_enabled(
+                        self.snap,
+                        egui::DragValue::new(&mut self.grid)
+                            .range(0.25..=self.size)
+                            .speed(0.25)
+                            .suffix(" in"),
+                    )
+                    .on_hover_text("Grid spacing");
+                }
+                ui.menu_button("Tiles", |ui| {
+                    ui.checkbox(&mut self.tiles, "Use tiles")
+                        .on_hover_text("Measure and snap in field tiles");
+                    ui.add(
+                        DragValue::new(&mut self.tile_size)
+                            .range(1.0..=self.size)
+                            .prefix("Tile ")
+                            .suffix(" in"),
+                    );
+                });
+                if !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(self.keys.snap)) {
+                    self.snap = !self.snap;
+                }
+                ui.menu_button("Walls", |ui| {
+                    ui.checkbox(&mut self.wall_snap, "Snap to walls")
+                        .on_hover_text("Square new points up against nearby walls");
+                    ui.add(
+                        DragValue::new(&mut self.wall_reach)
+                            .range(0.0..=self.size / 2.0)
+                            .prefix("Within ")
+                            .suffix(" in"),
+                    );
+                    ui.add(
+                        DragValue::new(&mut self.wall_offset)
+                            .range(0.0..=self.size / 2.0)
+                            .prefix("Offset ")
+                            .suffix(" in"),
+                    )
+                    .on_hover_text("Half the robot's width");
+                });
+                ui.menu_button("Keys", |ui| {
+                    let letters = egui::Key::ALL
+                        .iter()
+                        .filter(|k| k.name().len() == 1 && k.name().chars().all(char::is_alphabetic));
+                    for (key, label) in self.keys.labelled() {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            egui::ComboBox::from_id_salt(("key", label))
+                                .selected_text(key.name())
+                                .show_ui(ui, |ui| {
+                                    for &letter in letters.clone() {
+                                        ui.selectable_value(key, letter, letter.name());
+                                    }
+                                });
+                        });
+                    }
+                });
+                egui::ComboBox::from_id_salt("handle_mode")
+                    .selected_text(format!("{:?}", self.handle_mode))
+                    .show_ui(ui, |ui| {
+                        handle_mode_picker(ui, &mut self.handle_mode);
+                    })
+                    .response
+                    .on_hover_text("Handle mode for new points");
                 ui.separator();
                 /* BUTTON LOGIC */
+                let keys = self.keys;
                 let modes = [
-                    (egui::Key::C, CursorMode::Create, "Create new point"),
-                    (egui::Key::I, CursorMode::Insert, "Insert point in path"),
-                    (egui::Key::D, CursorMode::Delete, "Delete a single point"),
-                    (egui::Key::T, CursorMode::Trim, "Trim path to point"),
+                    (keys.create, CursorMode::Create, "Create new point"),
+                    (keys.insert, CursorMode::Insert, "Insert point in path"),
+                    (
+                        keys.split,
+                        CursorMode::Split,
+                        "Split a segment without changing its shape",
+                    ),
+                    (keys.delete, CursorMode::Delete, "Delete a single point"),
+                    (keys.trim, CursorMode::Trim, "Trim path to point"),
+                    (keys.sketch, CursorMode::Sketch, "Sketch a path freehand"),
                 ];
                 // Custom selectable label lets us double click to return to default
                 for (key, mode, desc) in modes {
@@ -246,7 +3849,7 @@ impl eframe::App for PathyApp {
                             self.cursor_mode == mode,
                             format!("{mode:?}"), // since we derive debug
                         ))
-                        .on_hover_text(format!("{desc} ({})", format!("{:?}", key).to_lowercase()))
+                        .on_hover_text(format!("{desc} ({})", key.name().to_lowercase()))
                         .clicked()
                     {
                         if self.cursor_mode != mode {
@@ -255,17 +3858,33 @@ impl eframe::App for PathyApp {
                             self.cursor_mode = CursorMode::Default;
                         }
                     }
-                    // also check key press
-                    ctx.input(|input| {
-                        if input.key_pressed(key) {
-                            if self.cursor_mode != mode {
-                                self.cursor_mode = mode;
-                            } else {
-                                self.cursor_mode = CursorMode::Default;
-                            }
+                    // also check key press, unless it's typing into a text field
+                    if !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(key)) {
+                        if self.cursor_mode != mode {
+                            self.cursor_mode = mode;
+                        } else {
+                            self.cursor_mode = CursorMode::Default;
                         }
-                    });
+                    }
+                }
+                if self.cursor_mode == CursorMode::Sketch {
+                    ui.add(
+                        DragValue::new(&mut self.sketch_tolerance)
+                            .range(SKETCH_TOLERANCE_RANGE)
+                            .speed(0.1)
+                            .prefix("±")
+                            .suffix(" in"),
+                    )
+                    .on_hover_text("How far the fitted path may stray from the stroke");
                 }
+                ui.add_enabled(
+                    matches!(self.cursor_mode, CursorMode::Create | CursorMode::Trim),
+                    egui::Checkbox::new(&mut self.from_start, "From start"),
+                )
+                .on_hover_text(
+                    "Create points before the first point, or trim the points before the \
+                     clicked one, instead of working from the end",
+                );
                 ui.separator();
                 if ui
                     .button("Generate")
@@ -274,10 +3893,34 @@ impl eframe::App for PathyApp {
                 {
                     self.generate();
                 };
-                if ui.button("Clear").on_hover_text("Clear path").clicked() {
-                    self.points.clear();
-                    self.generate();
+                if ui
+                    .button("Clear")
+                    .on_hover_text("Clear path, keeping pinned points")
+                    .clicked()
+                {
+                    let pinned = self.points.iter().filter(|p| p.borrow().pinned).count();
+                    if pinned > 0 {
+                        self.toasts.add("Unpin the points to clear them");
+                    }
+                    if self.points.len() > pinned {
+                        self.backup();
+                        self.journal.record("Cleared path");
+                        self.points.retain(|p| p.borrow().pinned);
+                        self.generate();
+                    }
                 };
+                ui.menu_button("Edit", |ui| self.edit_menu(ui));
+                ui.menu_button("Sync", |ui| self.sync_menu(ui));
+                ui.toggle_value(&mut self.show_points, "Points")
+                    .on_hover_text("Show point list");
+                ui.toggle_value(&mut self.show_script, "Script")
+                    .on_hover_text("Build paths with code");
+                ui.toggle_value(&mut self.show_actions, "Actions")
+                    .on_hover_text("Reusable code to run at points");
+                ui.toggle_value(&mut self.show_browser, "Paths")
+                    .on_hover_text("Find paths by name or tag");
+                ui.toggle_value(&mut self.show_report, "Report")
+                    .on_hover_text("Package details for a bug report");
                 ui.separator();
                 ui.label("Field: ");
                 // store functions to lazily load images
@@ -295,18 +3938,86 @@ impl eframe::App for PathyApp {
                         self.load_field_overlay();
                     }
                 }
+                if self.background == Background::Custom {
+                    ui.menu_button("From URL", |ui| {
+                        ui.add(
+                            TextEdit::singleline(&mut self.field_url)
+                                .hint_text("https://example.com/field.png"),
+                        );
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    !self.fetching && !self.field_url.trim().is_empty(),
+                                    egui::Button::new("Load"),
+                                )
+                                .clicked()
+                            {
+                                match files::fetch(self.field_url.trim(), ctx) {
+                                    Ok(()) => self.fetching = true,
+                                    Err(e) => self.toasts.add(e),
+                                }
+                                ui.close_menu();
+                            }
+                            if self.fetching {
+                                ui.spinner();
+                            }
+                        });
+                    })
+                    .response
+                    .on_hover_text("Load the field image from a link, like the official field render");
+                }
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
                     egui::widgets::global_theme_preference_buttons(ui);
                     ui.separator();
+                    // Timed again on every change, so it follows points as they're dragged
+                    let time = self.simulation.ours.duration();
+                    let text = format!("Time {time:.1} s");
+                    let label = if time > AUTON_TIME {
+                        ui.colored_label(Color32::RED, text)
+                    } else {
+                        ui.label(text)
+                    };
+                    label.on_hover_text(format!(
+                        "Estimated time to drive the open path, against {AUTON_TIME} s of autonomous"
+                    ));
+                    let length: f32 = self
+                        .segment_samples()
+                        .iter()
+                        .flat_map(|line| line.windows(2))
+                        .map(|w| w[0].distance(w[1]))
+                        .sum();
+                    ui.label(format!("Length {}", self.length_text(length)))
+                        .on_hover_text("Distance the robot travels along the open path. Hover a segment for its length");
                 });
             });
         });
+        egui::TopBottomPanel::top("paths").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| self.path_tabs(ui));
+        });
+
+        // Compare the path against an ideal shape
+        let fit = self
+            .primitive
+            .and_then(|primitive| analysis::fit(primitive, &analysis::sample(&self.points, 20)));
 
         egui::SidePanel::right("side").show(ctx, |ui| {
             ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
-                    ui.label("Code");
+                    ui.horizontal(|ui| {
+                        ui.label("Code");
+                        if ui
+                            .add(
+                                DragValue::new(&mut self.code_samples)
+                                    .range(CODE_SAMPLES_RANGE)
+                                    .suffix(" samples"),
+                            )
+                            .on_hover_text("Samples per segment in the generated code, where a point doesn't set its own")
+                            .changed()
+                        {
+                            self.generate();
+                        }
+                    });
                     ui.separator();
                     ui.add(
                         TextEdit::multiline(&mut self.generated.clone())
@@ -314,6 +4025,8 @@ impl eframe::App for PathyApp {
                             .desired_width(f32::INFINITY),
                     );
                     let mut updated = false;
+                    // Reshaped once the inspected point is released
+                    let mut reshaped = None;
                     if let Some(point_ref) = &self.inspecting.clone() {
                         let mut point = point_ref.borrow_mut();
                         if let Some(parent) = point.parent.upgrade() {
@@ -341,22 +4054,512 @@ impl eframe::App for PathyApp {
                                         updated = true;
                                     }
                                 });
-                                if ui
-                                    .checkbox(&mut parent.borrow_mut().broken, "Broken")
-                                    .clicked()
-                                {
-                                    updated = true;
-                                }
+                                ui.horizontal(|ui| {
+                                    if handle_mode_picker(ui, &mut parent.borrow_mut().mode) {
+                                        reshaped = Some(parent.clone());
+                                        updated = true;
+                                    }
+                                });
                             });
                         }
                     }
+                    if let Some(point) = reshaped {
+                        self.reshape(&point);
+                    }
                     if updated {
-                        self.generate();
+                        self.revalidate = true;
                     }
+                    ui.collapsing("Path", |ui| {
+                        let path = &mut self.paths[self.current];
+                        let mut changed = false;
+                        ui.horizontal(|ui| {
+                            ui.label("Name");
+                            changed |= ui.text_edit_singleline(&mut path.name).changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Side");
+                            for (side, label) in [
+                                (None, "Either"),
+                                (Some(Alliance::Red), "Red"),
+                                (Some(Alliance::Blue), "Blue"),
+                            ] {
+                                changed |=
+                                    ui.selectable_value(&mut path.side, side, label).changed();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Color");
+                            changed |= ui.color_edit_button_srgba(&mut path.color).changed();
+                        });
+                        ui.horizontal(|ui| {
+                            let mut set = path.start.is_some();
+                            if ui
+                                .checkbox(&mut set, "Start pose")
+                                .on_hover_text("Where the robot is placed, reset in odometry before the route runs")
+                                .changed()
+                            {
+                                // Start on the first point, facing along the path
+                                path.start = set.then(|| {
+                                    self.points.first().map_or(
+                                        Pose {
+                                            pos: Pos2::ZERO,
+                                            heading: 0.0,
+                                        },
+                                        |first| {
+                                            let first = first.borrow();
+                                            let pos = Pos2::from(first.pos.borrow().clone());
+                                            let exit = Pos2::from(first.cp2.borrow().clone()) - pos;
+                                            Pose {
+                                                pos,
+                                                heading: first
+                                                    .heading
+                                                    .unwrap_or(exit.angle().to_degrees()),
+                                            }
+                                        },
+                                    )
+                                });
+                                changed = true;
+                            }
+                        });
+                        if let Some(start) = &mut path.start {
+                            ui.horizontal(|ui| {
+                                changed |= ui
+                                    .add(DragValue::new(&mut start.pos.x).speed(0.1).suffix(" in"))
+                                    .changed();
+                                changed |= ui
+                                    .add(DragValue::new(&mut start.pos.y).speed(0.1).suffix(" in"))
+                                    .changed();
+                                changed |= ui
+                                    .add(
+                                        DragValue::new(&mut start.heading)
+                                            .range(-180.0..=180.0)
+                                            .suffix("°"),
+                                    )
+                                    .changed();
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            let mut set = path.end.heading.is_some();
+                            changed |= ui
+                                .checkbox(&mut set, "End heading")
+                                .on_hover_text("Finish facing this way, in place of the last point's heading")
+                                .changed();
+                            if set != path.end.heading.is_some() {
+                                // Start from the heading the path already finishes at
+                                path.end.heading = set.then(|| {
+                                    let points: Vec<BezPoint> =
+                                        self.points.iter().map(|p| p.borrow().clone()).collect();
+                                    sim::headings(&points, 20, &path.driving(&self.robot))
+                                        .last()
+                                        .copied()
+                                        .unwrap_or(0.0)
+                                });
+                            }
+                            if let Some(heading) = &mut path.end.heading {
+                                changed |= ui
+                                    .add(DragValue::new(heading).range(-180.0..=180.0).suffix("°"))
+                                    .changed();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            let mut set = path.end.speed.is_some();
+                            changed |= ui
+                                .checkbox(&mut set, "End speed")
+                                .on_hover_text("Reach the end at this speed, without stopping to turn if it isn't zero")
+                                .changed();
+                            if set != path.end.speed.is_some() {
+                                path.end.speed = set.then_some(0.0);
+                            }
+                            if let Some(speed) = &mut path.end.speed {
+                                changed |= ui
+                                    .add(DragValue::new(speed).range(0.0..=500.0).suffix(" in/s"))
+                                    .changed();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            let mut set = path.profile.jerk.is_some();
+                            changed |= ui
+                                .checkbox(&mut set, "Jerk limit")
+                                .on_hover_text("Ease into and out of acceleration with an S-curve profile, for robots that tip")
+                                .changed();
+                            if set != path.profile.jerk.is_some() {
+                                path.profile.jerk = set.then_some(240.0);
+                            }
+                            if let Some(jerk) = &mut path.profile.jerk {
+                                changed |= ui
+                                    .add(DragValue::new(jerk).range(sim::JERK_RANGE).suffix(" in/s³"))
+                                    .changed();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            let mut set = path.profile.lateral.is_some();
+                            changed |= ui
+                                .checkbox(&mut set, "Curve limit")
+                                .on_hover_text("Slow down through tight curves so sideways acceleration stays under this. Exported as a speed cap per segment")
+                                .changed();
+                            if set != path.profile.lateral.is_some() {
+                                path.profile.lateral = set.then_some(150.0);
+                            }
+                            if let Some(lateral) = &mut path.profile.lateral {
+                                changed |= ui
+                                    .add(DragValue::new(lateral).range(sim::LATERAL_RANGE).suffix(" in/s²"))
+                                    .changed();
+                            }
+                        });
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label("Tags");
+                            let mut removed = None;
+                            for (i, tag) in path.tags.iter().enumerate() {
+                                if ui.button(format!("{tag} ×")).on_hover_text("Remove tag").clicked() {
+                                    removed = Some(i);
+                                }
+                            }
+                            if let Some(i) = removed {
+                                path.tags.remove(i);
+                                changed = true;
+                            }
+                            let resp = ui.add(
+                                TextEdit::singleline(&mut self.new_tag)
+                                    .hint_text("add tag")
+                                    .desired_width(70.0),
+                            );
+                            if resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                let tag = self.new_tag.trim().to_lowercase();
+                                if !tag.is_empty() && !path.tags.contains(&tag) {
+                                    path.tags.push(tag);
+                                    changed = true;
+                                }
+                                self.new_tag.clear();
+                            }
+                        });
+                        changed |= ui
+                            .add(
+                                TextEdit::multiline(&mut path.notes)
+                                    .hint_text("Notes")
+                                    .desired_rows(2),
+                            )
+                            .changed();
+                        if changed {
+                            path.modified = files::timestamp();
+                            self.generate();
+                        }
+                    });
+                    ui.collapsing("Robot", |ui| {
+                        let robot = &mut self.robot;
+                        for (value, range, label, suffix) in [
+                            (&mut robot.length, sim::SIZE_RANGE, "Length", " in"),
+                            (&mut robot.width, sim::SIZE_RANGE, "Width", " in"),
+                            (&mut robot.cruise, sim::CRUISE_RANGE, "Top speed", " in/s"),
+                            (
+                                &mut robot.acceleration,
+                                sim::ACCELERATION_RANGE,
+                                "Acceleration",
+                                " in/s²",
+                            ),
+                            (&mut robot.turn, sim::TURN_RANGE, "Turn speed", " °/s"),
+                        ] {
+                            ui.horizontal(|ui| {
+                                ui.label(label);
+                                ui.add(DragValue::new(value).range(range).suffix(suffix));
+                            });
+                        }
+                    });
+                    ui.collapsing("Partner robot", |ui| {
+                        let others: Vec<(Uuid, String)> = self
+                            .paths
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| *i != self.current)
+                            .map(|(_, p)| (p.id, p.name.clone()))
+                            .collect();
+                        let selected = self.partner().map_or("None".into(), |p| p.name.clone());
+                        let path = &mut self.paths[self.current];
+                        egui::ComboBox::from_label("Path")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut path.partner, None, "None");
+                                for (id, name) in others {
+                                    ui.selectable_value(&mut path.partner, Some(id), name);
+                                }
+                            })
+                            .response
+                            .on_hover_text("The route the other robot runs at the same time");
+                        ui.label(format!(
+                            "Takes about {:.1} s at up to {} in/s",
+                            self.simulation.ours.duration(),
+                            self.robot.cruise
+                        ));
+                        if self.simulation.theirs.is_some() {
+                            match self.simulation.collision {
+                                Some(time) => ui.colored_label(
+                                    Color32::RED,
+                                    format!("Robots collide after {time:.1} s"),
+                                ),
+                                None => ui.label("Robots stay clear of each other"),
+                            };
+                        }
+                    });
+                    ui.collapsing("Simulation", |ui| {
+                        let duration = self.simulation.duration();
+                        ui.horizontal(|ui| {
+                            let label = if self.playing { "Pause" } else { "Play" };
+                            if ui
+                                .button(label)
+                                .on_hover_text("Animate the robots driving their routes")
+                                .clicked()
+                            {
+                                // Playing from the end starts over
+                                if !self.playing && self.playhead >= duration {
+                                    self.playhead = 0.0;
+                                }
+                                self.playing = !self.playing;
+                            }
+                            if ui.button("Stop").clicked() {
+                                self.playing = false;
+                                self.playhead = 0.0;
+                            }
+                            egui::ComboBox::from_id_salt("playback speed")
+                                .selected_text(format!("{}x", self.playback_speed))
+                                .width(50.0)
+                                .show_ui(ui, |ui| {
+                                    for speed in [0.25, 0.5, 1.0, 2.0, 4.0] {
+                                        ui.selectable_value(
+                                            &mut self.playback_speed,
+                                            speed,
+                                            format!("{speed}x"),
+                                        );
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Playback speed");
+                        });
+                        ui.add(
+                            egui::Slider::new(&mut self.playhead, 0.0..=duration)
+                                .suffix(" s")
+                                .max_decimals(2),
+                        )
+                        .on_hover_text("Drag to scrub through the route");
+                        ui.label(format!(
+                            "Speeding up and slowing down at {} in/s²",
+                            self.robot.acceleration
+                        ));
+                    });
+                    ui.collapsing("Compare", |ui| {
+                        let mut options: Vec<(Comparison, String)> = self
+                            .paths
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| *i != self.current)
+                            .map(|(_, p)| (Comparison::Path(p.id), p.name.clone()))
+                            .collect();
+                        if let Some(file @ Comparison::File { name, .. }) = &self.compare {
+                            options.push((file.clone(), name.clone()));
+                        }
+                        let selected = match self.compared() {
+                            None => "None".to_string(),
+                            Some(_) => options
+                                .iter()
+                                .find(|(c, _)| Some(c) == self.compare.as_ref())
+                                .map_or(String::new(), |(_, name)| name.clone()),
+                        };
+                        egui::ComboBox::from_label("With")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.compare, None, "None");
+                                for (comparison, name) in options {
+                                    ui.selectable_value(&mut self.compare, Some(comparison), name);
+                                }
+                            })
+                            .response
+                            .on_hover_text("Drop a saved path file on the field to compare with it too");
+                        if let Some(points) = self.compared() {
+                            let theirs: Vec<BezPoint> =
+                                points.iter().cloned().map(BezPoint::from).collect();
+                            let ours: Vec<BezPoint> =
+                                self.points.iter().map(|p| p.borrow().clone()).collect();
+                            // Compared routes are taken to be driven by the same robot, from their first point
+                            let path = &self.paths[self.current];
+                            let ours = Timeline::new(&ours, &path.driving(&self.robot)).duration();
+                            let theirs = Timeline::new(
+                                &theirs,
+                                &Driving {
+                                    start: None,
+                                    ..path.driving(&self.robot)
+                                },
+                            )
+                            .duration();
+                            ui.label(format!(
+                                "Takes about {ours:.1} s, against {theirs:.1} s"
+                            ));
+                        }
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Load telemetry")
+                                .on_hover_text(
+                                    "Draw poses recorded on the robot over the path, as time, x, y, theta lines measured like LemLib: inches from the middle of the field with y up, theta clockwise from up the field",
+                                )
+                                .clicked()
+                            {
+                                self.picking_telemetry = true;
+                                if let Err(e) = files::pick(".csv,.log,.txt", Pick::Telemetry) {
+                                    self.toasts.add(e);
+                                }
+                            }
+                            if self.picking_telemetry
+                                && ui
+                                    .button("Cancel")
+                                    .on_hover_text("Stop waiting for a telemetry file")
+                                    .clicked()
+                            {
+                                self.picking_telemetry = false;
+                            }
+                            if self.telemetry.is_some() && ui.button("Clear").clicked() {
+                                self.telemetry = None;
+                            }
+                        });
+                        if let Some(telemetry) = &self.telemetry {
+                            let errors = &telemetry.errors;
+                            let mean = errors.iter().sum::<f32>() / errors.len() as f32;
+                            let (worst, max) = errors
+                                .iter()
+                                .enumerate()
+                                .fold((0, 0.0), |a, (i, &e)| if e > a.1 { (i, e) } else { a });
+                            let (start, end) =
+                                (telemetry.poses[0].0, telemetry.poses[errors.len() - 1].0);
+                            ui.label(format!(
+                                "{}: {} poses over {:.1} s",
+                                telemetry.name,
+                                errors.len(),
+                                end - start
+                            ));
+                            if !self.points.is_empty() {
+                                ui.label(format!(
+                                    "Off the path by {mean:.2} in on average, at most {max:.2} in at {:.1} s",
+                                    telemetry.poses[worst].0 - start
+                                ));
+                            }
+                        }
+                    });
+                    ui.collapsing("Primitive fit", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut self.primitive, None, "Off");
+                            for primitive in [Primitive::Line, Primitive::Arc] {
+                                ui.selectable_value(
+                                    &mut self.primitive,
+                                    Some(primitive),
+                                    format!("{primitive:?}"),
+                                );
+                            }
+                        });
+                        match fit {
+                            Some(Fit { shape, rms, max }) => {
+                                if let analysis::Shape::Arc { radius, sweep, .. } = shape {
+                                    ui.label(format!(
+                                        "Radius {radius:.2} in, sweep {:.1}°",
+                                        sweep.to_degrees().abs()
+                                    ));
+                                }
+                                ui.label(format!("RMS deviation {rms:.3} in"));
+                                ui.label(format!("Max deviation {max:.3} in"));
+                            }
+                            None if self.primitive.is_some() => {
+                                ui.label("Not enough points to fit");
+                            }
+                            None => {}
+                        }
+                    });
+                    ui.collapsing("Overlays", |ui| {
+                        ui.checkbox(&mut self.show_tangents, "Tangents")
+                            .on_hover_text("Tangent and normal arrows with headings at each point");
+                        ui.checkbox(&mut self.show_names, "Point names");
+                        ui.checkbox(&mut self.show_swept, "Swept area").on_hover_text(
+                            "Everywhere the robot covers driving the path, to check it fits through gaps",
+                        );
+                        ui.checkbox(&mut self.show_comb, "Curvature comb");
+                        ui.add(
+                            DragValue::new(&mut self.comb_scale)
+                                .range(1.0..=10000.0)
+                                .prefix("Scale "),
+                        )
+                        .on_hover_text("Spike length in inches per unit of curvature (1 / radius)");
+                    });
+                    ui.collapsing("Headings", |ui| {
+                        let mut changed = false;
+                        ui.horizontal(|ui| {
+                            for (mode, desc) in HeadingMode::ALL.into_iter().zip([
+                                "Face the direction of travel, ignoring set headings",
+                                "Turn evenly over the time between set headings, as the robot drives",
+                                "Turn evenly over the distance between set headings",
+                            ]) {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut self.paths[self.current].heading_mode,
+                                        mode,
+                                        format!("{mode:?}"),
+                                    )
+                                    .on_hover_text(desc)
+                                    .changed();
+                            }
+                        });
+                        ui.checkbox(&mut self.show_profile, "Show along path");
+                        if changed {
+                            self.paths[self.current].modified = files::timestamp();
+                            self.generate();
+                        }
+                    });
+                    ui.collapsing("Selection", |ui| self.selection_panel(ui));
                 });
             });
         });
 
+        let mut show_browser = self.show_browser;
+        egui::Window::new("Paths")
+            .open(&mut show_browser)
+            .show(ctx, |ui| self.path_browser(ui));
+        self.show_browser = show_browser;
+        let mut show_actions = self.show_actions;
+        egui::Window::new("Actions")
+            .open(&mut show_actions)
+            .show(ctx, |ui| {
+                if self.action_library(ui) {
+                    self.generate();
+                }
+            });
+        self.show_actions = show_actions;
+        let mut show_script = self.show_script;
+        egui::Window::new("Script")
+            .open(&mut show_script)
+            .show(ctx, |ui| self.script_window(ui));
+        self.show_script = show_script;
+        let mut show_report = self.show_report;
+        egui::Window::new("Report issue")
+            .open(&mut show_report)
+            .show(ctx, |ui| self.report_window(ui));
+        self.show_report = show_report;
+        self.conflict_window(ctx);
+        self.recovery_window(ctx);
+        self.open_window(ctx);
+        self.restore_window(ctx);
+        self.position_window(ctx);
+
+        egui::SidePanel::left("points").show_animated(ctx, self.show_points, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.label("Variables");
+                ui.separator();
+                let mut updated = self.variable_list(ui);
+                ui.label("Points");
+                ui.separator();
+                updated |= self.point_list(ui);
+                ui.label("Constraints");
+                ui.separator();
+                updated |= self.constraint_list(ui);
+                if updated {
+                    self.revalidate = true;
+                }
+            });
+        });
+
         egui::CentralPanel::default().show(ctx, |ui| {
             /* FIELD RENDERING */
             let (rect, resp) = ui.allocate_exact_size(
@@ -366,14 +4569,75 @@ impl eframe::App for PathyApp {
                 },
                 egui::Sense::click_and_drag(),
             );
-            // Check for dropped image
-            if self.background == Background::Custom {
-                ctx.input(|i| {
-                    if let Some(file) = i.raw.dropped_files.last() {
-                        self.uploaded = file.clone().bytes;
-                        self.load_field_overlay();
+            // Check for dropped presets, then images
+            let mut dropped = ctx.input(|i| i.raw.dropped_files.clone());
+            // Telemetry asked for from the Compare section comes before any other kind of file
+            if self.picking_telemetry && !dropped.is_empty() {
+                let file = dropped.remove(0);
+                self.picking_telemetry = false;
+                match files::dropped(&file) {
+                    Some(bytes) => self.load_telemetry(files::dropped_name(&file), &bytes),
+                    None => self.toasts.add("Could not read the dropped telemetry"),
+                }
+            }
+            let (presets, images): (Vec<_>, Vec<_>) = dropped.into_iter().partition(|file| {
+                file.name.ends_with(preset::EXTENSION)
+                    || file
+                        .path
+                        .as_ref()
+                        .is_some_and(|p| p.to_string_lossy().ends_with(preset::EXTENSION))
+            });
+            for file in &presets {
+                if let Some(bytes) = files::dropped(file) {
+                    self.import_preset(&bytes);
+                }
+            }
+            let (imports, images): (Vec<_>, Vec<_>) = images
+                .into_iter()
+                .partition(|file| import::importable(&files::dropped_name(file)));
+            for file in &imports {
+                let name = files::dropped_name(file);
+                match files::dropped(file) {
+                    Some(bytes) => self.import(&name, &bytes),
+                    None => self.toasts.add(format!("Could not read {name}")),
+                }
+            }
+            let (projects, images): (Vec<_>, Vec<_>) = images.into_iter().partition(|file| {
+                let name = files::dropped_name(file);
+                name.ends_with(PROJECT_EXTENSION) || name.ends_with(BUNDLE_EXTENSION)
+            });
+            if let Some(file) = projects.last() {
+                match files::dropped(file) {
+                    Some(bytes) => self.request_open(files::dropped_name(file), bytes),
+                    None => self.toasts.add("Could not read the dropped project"),
+                }
+            }
+            // Saved paths are compared against rather than replacing the open one
+            let (paths, images): (Vec<_>, Vec<_>) = images
+                .into_iter()
+                .partition(|file| files::dropped_name(file).ends_with(".ron"));
+            for file in &paths {
+                let name = files::dropped_name(file);
+                let points = files::dropped(file)
+                    .ok_or_else(|| "could not be read".to_string())
+                    .and_then(|bytes| String::from_utf8(bytes).map_err(|e| e.to_string()))
+                    .and_then(|text| {
+                        ron::from_str::<Vec<SavePoint>>(&text).map_err(|e| e.to_string())
+                    });
+                match points {
+                    Ok(points) => {
+                        self.toasts.add(format!("Comparing with {name}"));
+                        self.compare = Some(Comparison::File { name, points });
                     }
-                });
+                    Err(e) => self.toasts.add(format!("{name} is not a path: {e}")),
+                }
+            }
+            if self.background == Background::Custom {
+                // Native drops only give the file's path
+                if let Some(bytes) = images.last().and_then(files::dropped) {
+                    self.uploaded = Some(bytes.into());
+                    self.load_field_overlay();
+                }
             }
             match &self.overlay {
                 Some(image) => {
@@ -397,44 +4661,196 @@ impl eframe::App for PathyApp {
                 }
             }
 
+            // Draw grid lines when snapping, and tile edges when using tiles
+            let ratio = self.scale as f32 / self.size;
+            let mut grids = Vec::new();
+            if self.snap {
+                grids.push((self.grid_step() * ratio, Color32::from_white_alpha(20)));
+            }
+            if self.tiles {
+                grids.push((self.tile_size * ratio, Color32::from_white_alpha(50)));
+            }
+            for (grid_px, color) in grids {
+                if grid_px < 4.0 {
+                    continue;
+                }
+                let stroke = Stroke::new(1.0, color);
+                let mut offset = grid_px;
+                while offset < rect.width() {
+                    ui.painter()
+                        .vline(rect.min.x + offset, rect.y_range(), stroke);
+                    ui.painter()
+                        .hline(rect.x_range(), rect.min.y + offset, stroke);
+                    offset += grid_px;
+                }
+            }
+
+            // Fill the area the robot sweeps under the path
+            if self.show_swept {
+                let ratio = self.scale as f32 / self.size;
+                let simulation = &mut self.simulation;
+                let runs = simulation
+                    .swept
+                    .get_or_insert_with(|| simulation.ours.swept(SWEPT_CELL));
+                let fill = Color32::from_rgba_unmultiplied(80, 160, 255, 60);
+                for run in runs.iter() {
+                    let run = egui::Rect::from_min_max(
+                        rect.min + run.min.to_vec2() * ratio,
+                        rect.min + run.max.to_vec2() * ratio,
+                    );
+                    ui.painter().rect_filled(run, 0.0, fill);
+                }
+            }
+
+            // Draw the fitted primitive under the path
+            if let Some(fit) = &fit {
+                let ratio = self.scale as f32 / self.size;
+                let outline: Vec<Pos2> = fit
+                    .shape
+                    .outline(64)
+                    .into_iter()
+                    .map(|p| rect.min + p.to_vec2() * ratio)
+                    .collect();
+                ui.painter().extend(egui::Shape::dashed_line(
+                    &outline,
+                    Stroke::new(2.0, Color32::LIGHT_BLUE),
+                    6.0,
+                    4.0,
+                ));
+            }
+
+            // Draw the curvature comb under the path, spikes pointing away from the turn
+            if self.show_comb {
+                let ratio = self.scale as f32 / self.size;
+                let stroke = Stroke::new(1.0, Color32::from_rgb(255, 120, 200));
+                let mut tips = Vec::new();
+                for (pos, vector) in analysis::curvature(&self.points, 16) {
+                    let base = rect.min + pos.to_vec2() * ratio;
+                    let tip = base - vector * self.comb_scale * ratio;
+                    ui.painter().line_segment([base, tip], stroke);
+                    tips.push(tip);
+                }
+                ui.painter().add(egui::Shape::line(tips, stroke));
+            }
+
+            // Draw the direction of travel at each anchor, with the same heading as generated code
+            if self.show_tangents {
+                let ratio = self.scale as f32 / self.size;
+                for point in &self.points {
+                    let point = point.borrow();
+                    let pos = Pos2::from(point.pos.borrow().clone());
+                    let exit = Pos2::from(point.cp2.borrow().clone()) - pos;
+                    if exit.length() < 1e-6 {
+                        continue;
+                    }
+                    let tangent = exit.normalized();
+                    let normal = tangent.rot90();
+                    let base = rect.min + pos.to_vec2() * ratio;
+                    ui.painter().arrow(
+                        base,
+                        tangent * 36.0,
+                        Stroke::new(2.0, Color32::LIGHT_GREEN),
+                    );
+                    ui.painter()
+                        .arrow(base, normal * 24.0, Stroke::new(2.0, Color32::LIGHT_RED));
+                    ui.painter().text(
+                        base + tangent * 44.0,
+                        egui::Align2::CENTER_CENTER,
+                        format!("{:.1}°", tangent.y.atan2(tangent.x).to_degrees()),
+                        egui::FontId::proportional(12.0),
+                        Color32::WHITE,
+                    );
+                }
+            }
+
+            // Draw the interpolated heading along the path
+            if self.show_profile
+                && self.paths[self.current].heading_mode != HeadingMode::Tangent
+                && (self.paths[self.current].end.heading.is_some()
+                    || self.points.iter().any(|p| p.borrow().heading.is_some()))
+            {
+                let ratio = self.scale as f32 / self.size;
+                let mut points: Vec<BezPoint> =
+                    self.points.iter().map(|p| p.borrow().clone()).collect();
+                self.paths[self.current].end.apply(&mut points);
+                let positions = analysis::sample(&self.points, 4);
+                for (pos, heading) in positions.into_iter().zip(sim::headings(
+                    &points,
+                    4,
+                    &self.paths[self.current].driving(&self.robot),
+                )) {
+                    let base = rect.min + pos.to_vec2() * ratio;
+                    ui.painter().line_segment(
+                        [base, base + Vec2::angled(heading.to_radians()) * 12.0],
+                        Stroke::new(1.5, Color32::ORANGE),
+                    );
+                }
+            }
+
+            // Draw the compared and partner paths faintly, behind this one
+            if let Some(points) = self.compared() {
+                self.faint_path(ui, rect, points, Color32::GRAY);
+            }
+            if let Some(partner) = self.partner() {
+                self.faint_path(ui, rect, &partner.points, partner.color);
+            }
+            // Recorded poses, from green on the path to red at the tracking tolerance
+            if let Some(telemetry) = &self.telemetry {
+                let ratio = self.scale as f32 / self.size;
+                let screen: Vec<Pos2> = telemetry
+                    .poses
+                    .iter()
+                    .map(|(_, pose)| rect.min + pose.pos.to_vec2() * ratio)
+                    .collect();
+                ui.painter().add(egui::Shape::line(
+                    screen.clone(),
+                    Stroke::new(1.0, Color32::from_white_alpha(80)),
+                ));
+                for (pos, &error) in screen.into_iter().zip(&telemetry.errors) {
+                    let f = (error / TRACKING_TOLERANCE).clamp(0.0, 1.0);
+                    let color = Color32::from_rgb((255.0 * f) as u8, (255.0 * (1.0 - f)) as u8, 60);
+                    ui.painter().circle_filled(pos, 2.5, color);
+                }
+            }
+
             /* POINT RENDERING + HOVER DETECTION */
             // Render curve points
             let mut min_dis = f32::MAX;
             let mut closest: Option<Pos2> = None;
             let mut closest_idx: usize = 0;
             let mut closest_step: f32 = 0.0;
+            let path_color = self.paths[self.current].color;
             if self.points.len() >= 2 {
                 for idx in 0..self.points.len() - 1 {
                     let a = self.points[idx].borrow_mut();
                     let mut b = self.points[idx + 1].borrow_mut();
                     // evaluate each pair
+                    let steps = a.steps.unwrap_or(self.steps);
                     let draw_steps = if !b.animated {
-                        ctx.animate_value_with_time(
-                            ui.make_persistent_id(b.id),
-                            self.steps as f32,
-                            0.3,
-                        ) as usize
+                        ctx.animate_value_with_time(ui.make_persistent_id(b.id), steps as f32, 0.3)
+                            as usize
                     } else {
-                        self.steps
+                        steps
                     };
                     // Lock once animation completed
                     // So step size changes don't animate
-                    if draw_steps >= self.steps {
+                    if draw_steps >= steps {
                         b.animated = true;
                     }
                     for i in 1..draw_steps {
-                        let point = interpolate(&a, &b, i as f32 / self.steps as f32)
+                        let point = interpolate(&a, &b, i as f32 / steps as f32)
                             .screen(self.scale as f32 / self.size, rect.min);
-                        ui.painter().circle_filled(point, 2.0, Color32::YELLOW);
+                        let color = if a.reverse { REVERSE_COLOR } else { path_color };
+                        ui.painter().circle_filled(point, 2.0, color);
                         // If insert mode, find closest point
-                        if self.cursor_mode == CursorMode::Insert {
+                        if matches!(self.cursor_mode, CursorMode::Insert | CursorMode::Split) {
                             if let Some(pos) = resp.hover_pos() {
                                 let dist = point.distance_sq(pos);
                                 if dist < min_dis {
                                     min_dis = dist;
                                     closest = Some(point);
                                     closest_idx = idx;
-                                    closest_step = i as f32 / self.steps as f32;
+                                    closest_step = i as f32 / steps as f32;
                                 }
                             }
                         }
@@ -445,7 +4861,14 @@ impl eframe::App for PathyApp {
             // Draw points & check for selection
             let mut selected: Option<Rc<RefCell<Point>>> = None; // references currently selected point
             let mut idx: Option<usize> = None;
-            for (i, point) in &mut self.points.iter_mut().enumerate() {
+            // Trimming from the start marks the points before the hovered one instead of after
+            let order: Vec<usize> = if self.cursor_mode == CursorMode::Trim && self.from_start {
+                (0..self.points.len()).rev().collect()
+            } else {
+                (0..self.points.len()).collect()
+            };
+            for i in order {
+                let point = &self.points[i];
                 let res = point.borrow_mut().draw(
                     ui,
                     ctx,
@@ -472,27 +4895,365 @@ impl eframe::App for PathyApp {
             if let Some(point) = &selected {
                 self.inspecting = Some(point.clone());
             }
+            // Highlight the point picked from the point list
+            if self.show_points {
+                if let Some(point) = self.inspecting.as_ref().and_then(|p| p.try_borrow().ok()) {
+                    if point.parent.strong_count() > 0 {
+                        ui.painter().circle_stroke(
+                            point.screen(self.scale as f32 / self.size, rect.min),
+                            12.0,
+                            Stroke::new(2.0, Color32::LIGHT_BLUE),
+                        );
+                    }
+                }
+            }
+
+            // Mark the selected anchors or handles
+            for point in selection::selected(&self.points, &self.selection) {
+                let point = point.borrow();
+                let filter = self.select_filter;
+                for (part, shown) in [
+                    (&point.pos, filter == Filter::Anchors),
+                    (&point.cp1, filter.entry()),
+                    (&point.cp2, filter.exit()),
+                ] {
+                    if shown {
+                        ui.painter().circle_stroke(
+                            part.borrow()
+                                .screen(self.scale as f32 / self.size, rect.min),
+                            10.0,
+                            Stroke::new(2.0, Color32::GOLD),
+                        );
+                    }
+                }
+            }
+
+            // Label named points
+            if self.show_names {
+                for point in &self.points {
+                    let bez = point.borrow();
+                    if bez.name.is_empty() {
+                        continue;
+                    }
+                    ui.painter().text(
+                        bez.pos
+                            .borrow()
+                            .screen(self.scale as f32 / self.size, rect.min)
+                            + Vec2::new(10.0, -10.0),
+                        egui::Align2::LEFT_BOTTOM,
+                        &bez.name,
+                        egui::FontId::proportional(12.0),
+                        Color32::WHITE,
+                    );
+                }
+            }
+
+            // Outline the robot where it starts, with its front edge heavier
+            if let Some(start) = &self.paths[self.current].start {
+                let corners = self
+                    .robot
+                    .outline(start)
+                    .map(|c| rect.min + c.to_vec2() * self.scale as f32 / self.size);
+                ui.painter().add(egui::Shape::closed_line(
+                    corners.to_vec(),
+                    Stroke::new(2.0, Color32::WHITE),
+                ));
+                ui.painter()
+                    .line_segment([corners[0], corners[1]], Stroke::new(4.0, Color32::WHITE));
+            }
+
+            // Play the robots back along their routes, red while they touch
+            if self.playing || self.playhead > 0.0 {
+                let duration = self.simulation.duration();
+                if self.playing {
+                    self.playhead += ui.input(|i| i.stable_dt) * self.playback_speed;
+                    if self.playhead >= duration {
+                        self.playhead = duration;
+                        self.playing = false;
+                    }
+                    ctx.request_repaint();
+                }
+                let time = self.playhead;
+                let ratio = self.scale as f32 / self.size;
+                let Simulation { ours, theirs, .. } = &self.simulation;
+                let outlines: Vec<[Pos2; 4]> = [Some(ours), theirs.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|timeline| timeline.pose(time))
+                    .map(|pose| self.robot.outline(&pose))
+                    .collect();
+                let touching = outlines.len() == 2 && sim::overlap(&outlines[0], &outlines[1]);
+                for outline in &outlines {
+                    let corners = outline.map(|c| rect.min + c.to_vec2() * ratio);
+                    let color = if touching {
+                        Color32::RED
+                    } else {
+                        Color32::WHITE
+                    };
+                    ui.painter().add(egui::Shape::closed_line(
+                        corners.to_vec(),
+                        Stroke::new(2.0, color),
+                    ));
+                    ui.painter()
+                        .line_segment([corners[0], corners[1]], Stroke::new(4.0, color));
+                }
+            }
+
+            // Ring the points the robot stops at
+            for point in &self.points {
+                let bez = point.borrow();
+                if bez.stop
+                    || bez.wait.is_some()
+                    || !bez.actions.is_empty()
+                    || !bez.marker.is_empty()
+                {
+                    ui.painter().circle_stroke(
+                        bez.pos
+                            .borrow()
+                            .screen(self.scale as f32 / self.size, rect.min),
+                        8.0,
+                        Stroke::new(2.0, Color32::RED),
+                    );
+                }
+            }
+
+            // Label the points the robot waits at
+            for point in &self.points {
+                let bez = point.borrow();
+                let text = match (&bez.wait, bez.marker.as_str()) {
+                    (Some(Wait::Time(seconds)), _) => format!("wait {seconds:.1} s"),
+                    (Some(Wait::Until(_)), _) => "wait until".into(),
+                    (None, "") => continue,
+                    (None, marker) => format!("sync {marker}"),
+                };
+                ui.painter().text(
+                    bez.pos
+                        .borrow()
+                        .screen(self.scale as f32 / self.size, rect.min)
+                        + Vec2::new(0.0, 14.0),
+                    egui::Align2::CENTER_TOP,
+                    text,
+                    egui::FontId::proportional(11.0),
+                    Color32::LIGHT_BLUE,
+                );
+            }
+
+            // Draw heading arrows, finding the one whose tip is under the pointer
+            let mut turn_hover = None;
+            for point in &self.points {
+                let bez = point.borrow();
+                let Some(heading) = bez.heading else {
+                    continue;
+                };
+                let anchor = bez
+                    .pos
+                    .borrow()
+                    .screen(self.scale as f32 / self.size, rect.min);
+                let tip = anchor + Vec2::angled(heading.to_radians()) * HEADING_ARROW;
+                let hovered = selected.is_none()
+                    && resp.hover_pos().is_some_and(|pos| pos.distance(tip) < 6.0);
+                let active = self.turning.as_ref().is_some_and(|p| Rc::ptr_eq(p, point));
+                let color = if hovered || active {
+                    Color32::WHITE
+                } else {
+                    Color32::ORANGE
+                };
+                ui.painter()
+                    .arrow(anchor, tip - anchor, Stroke::new(2.0, color));
+                ui.painter().circle_filled(tip, 4.0, color);
+                if hovered {
+                    turn_hover = Some(point.clone());
+                }
+            }
 
             /* INPUT HANDLERS */
             if ctx.input(|i| i.pointer.button_down(egui::PointerButton::Primary))
-                && !matches!(self.cursor_mode, CursorMode::Delete | CursorMode::Trim)
+                && !matches!(
+                    self.cursor_mode,
+                    CursorMode::Delete | CursorMode::Trim | CursorMode::Sketch
+                )
             {
+                // Only a press on the arrow's tip grabs it, not a held button passing over it
+                if ctx.input(|i| i.pointer.button_pressed(egui::PointerButton::Primary))
+                    && self.selected.is_none()
+                    && self.pen.is_none()
+                {
+                    self.turning = turn_hover.clone();
+                }
                 // Lock selection in case of drag
-                if self.selected.is_none() {
-                    if let Some(point) = &selected {
+                if self.selected.is_none()
+                    && self.pen.is_none()
+                    && self.turning.is_none()
+                    && !self.drag_cancelled
+                {
+                    let pinned = |p: &Rc<RefCell<Point>>| {
+                        p.borrow()
+                            .parent
+                            .upgrade()
+                            .is_some_and(|parent| parent.borrow().pinned)
+                    };
+                    if let Some(point) = selected.as_ref().filter(|p| !pinned(p)) {
                         point.borrow_mut().locked = true;
+                        self.drag_start = Pos2::from(point.borrow().clone());
+                        if let Some(parent) = point.borrow().parent.upgrade() {
+                            let parent = parent.borrow();
+                            self.pre_drag = [&parent.pos, &parent.cp1, &parent.cp2]
+                                .map(|p| Pos2::from(p.borrow().clone()));
+                        }
                         self.selected = Some(point.clone());
                     }
                 }
             }
+            // Dragging a heading arrow turns it, in steps with shift held
+            if let Some(point) = &self.turning {
+                if let Some(pos) = ctx.pointer_interact_pos() {
+                    let anchor = point
+                        .borrow()
+                        .pos
+                        .borrow()
+                        .screen(self.scale as f32 / self.size, rect.min);
+                    let mut heading = (pos - anchor).angle().to_degrees();
+                    if ctx.input(|i| i.modifiers.shift) {
+                        heading = (heading / HEADING_SNAP).round() * HEADING_SNAP;
+                    }
+                    point.borrow_mut().heading = Some(transform::normalize(heading));
+                    self.generate();
+                }
+            }
             if ctx.input(|i| i.pointer.button_released(egui::PointerButton::Primary)) {
                 // Unlock any selection
-                if let Some(point) = &self.selected {
+                if let Some(point) = self.turning.take() {
+                    let i = self.points.iter().position(|p| Rc::ptr_eq(p, &point));
+                    if let (Some(i), Some(heading)) = (i, point.borrow().heading) {
+                        self.journal
+                            .record(format!("Turned point {} to {heading:.1}°", i + 1));
+                    }
+                }
+                self.drag_cancelled = false;
+                if let Some(point) = self.selected.take() {
                     point.borrow_mut().locked = false;
-                    self.selected = None;
+                    let to = Pos2::from(point.borrow().clone());
+                    if to != self.drag_start {
+                        self.journal.record(format!(
+                            "Dragged {} from ({:.1}, {:.1}) to ({:.1}, {:.1})",
+                            self.describe(&point),
+                            self.drag_start.x,
+                            self.drag_start.y,
+                            to.x,
+                            to.y
+                        ));
+                    }
                 }
             }
-            if resp.clicked() {
+            // Double clicking a point lets its position be typed
+            if resp.double_clicked() {
+                if let (Some(point), Some(at)) = (&selected, resp.hover_pos()) {
+                    let pos = Pos2::from(point.borrow().clone());
+                    self.typing = Some((point.clone(), at, pos));
+                }
+            }
+            // Right clicking a point opens its context menu
+            // Right clicking mid-drag cancels the drag, and on empty field leaves the mode
+            if ctx.input(|i| i.pointer.button_pressed(egui::PointerButton::Secondary))
+                && self.selected.is_some()
+            {
+                self.cancel_drag();
+            } else if resp.secondary_clicked() {
+                self.context_point = selected.as_ref().and_then(|p| p.borrow().parent.upgrade());
+                if selected.is_none() {
+                    self.cursor_mode = CursorMode::Default;
+                }
+            }
+            resp.context_menu(|ui| {
+                let Some(point) = self.context_point.clone() else {
+                    ui.close_menu();
+                    return;
+                };
+                ui.label("Handles");
+                if handle_mode_picker(ui, &mut point.borrow_mut().mode) {
+                    self.journal.record(format!(
+                        "Changed the handle mode of {}",
+                        self.describe(&point.borrow().pos)
+                    ));
+                    self.reshape(&point);
+                    self.generate();
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui
+                    .checkbox(&mut point.borrow_mut().pinned, "Pinned")
+                    .on_hover_text("Stop the point from being dragged, deleted or trimmed")
+                    .clicked()
+                {
+                    ui.close_menu();
+                }
+                let i = self.points.iter().position(|p| Rc::ptr_eq(p, &point));
+                for (label, pair) in [
+                    (
+                        "Straighten from previous",
+                        i.and_then(|i| i.checked_sub(1).map(|j| (j, i))),
+                    ),
+                    ("Straighten to next", i.map(|i| (i, i + 1))),
+                ] {
+                    let pair = pair.filter(|&(_, b)| b < self.points.len());
+                    if ui
+                        .add_enabled(pair.is_some(), egui::Button::new(label))
+                        .clicked()
+                    {
+                        if let Some((a, b)) = pair {
+                            self.journal
+                                .record(format!("Straightened segment {}", a + 1));
+                            straighten(&self.points[a].borrow(), &self.points[b].borrow());
+                            self.generate();
+                        }
+                        ui.close_menu();
+                    }
+                }
+                let next = i.map(|i| i + 1).filter(|&j| j < self.points.len());
+                if ui
+                    .add_enabled(
+                        next.is_some(),
+                        egui::Checkbox::new(&mut point.borrow_mut().reverse, "Reverse to next"),
+                    )
+                    .on_hover_text("Back through the segment to the next point")
+                    .changed()
+                {
+                    self.journal.record(format!(
+                        "Toggled reversing of {}",
+                        self.describe(&point.borrow().pos)
+                    ));
+                    self.generate();
+                    ui.close_menu();
+                }
+                if ui
+                    .add_enabled(next.is_some(), egui::Button::new("Insert midpoint to next"))
+                    .on_hover_text(
+                        "Add a point halfway along the segment without changing its shape",
+                    )
+                    .clicked()
+                {
+                    if let Some(j) = next {
+                        let (a, b) = (self.points[j - 1].clone(), self.points[j].clone());
+                        let t = analysis::midpoint(&a.borrow(), &b.borrow());
+                        let mid = split(&mut a.borrow_mut(), &mut b.borrow_mut(), t);
+                        self.journal
+                            .record(format!("Inserted a midpoint after point {j}"));
+                        self.points.insert(j, mid);
+                        self.generate();
+                    }
+                    ui.close_menu();
+                }
+                if ui.button("Duplicate (ctrl+d)").clicked() {
+                    self.duplicate(&point);
+                    ui.close_menu();
+                }
+            });
+
+            if resp.clicked() && ctx.input(|i| i.modifiers.shift) {
+                if let Some(point) = selected.as_ref().and_then(|p| p.borrow().parent.upgrade()) {
+                    self.toggle_selection(point.borrow().id);
+                }
+            } else if resp.clicked() && turn_hover.is_none() {
                 match &self.cursor_mode {
                     CursorMode::Create => {
                         if selected.is_some() {
@@ -508,60 +5269,86 @@ impl eframe::App for PathyApp {
                                 return;
                             }
                             // Calculate points relative to field
-                            let x = (pos.x - rect.min.x) * (self.size / self.scale as f32);
-                            let y = (pos.y - rect.min.y) * (self.size / self.scale as f32);
-                            if self.points.is_empty() {
-                                self.points
-                                    .push(BezPoint::new(x, y, x - 10.0, y, x + 10.0, y));
-                            } else {
-                                let Pos2 { x: ix, y: iy } = Pos2::from(
-                                    self.points.last().unwrap().borrow().cp2.borrow().clone(),
-                                )
-                                .lerp(pos2(x, y), 0.5);
-                                self.points.push(BezPoint::new(
-                                    x,
-                                    y,
-                                    ix,
-                                    iy,
-                                    2.0 * x - ix,
-                                    2.0 * y - iy,
-                                ));
-                                // setup initial animation value
-                                ctx.animate_value_with_time(
-                                    ui.make_persistent_id(self.points.last().unwrap().borrow().id),
-                                    0.0,
-                                    0.5,
-                                );
-                            }
+                            let at = self.to_field(pos, rect.min);
+                            self.journal
+                                .record(format!("Created point at ({:.1}, {:.1})", at.x, at.y));
+                            self.create_point(ui, at);
                             self.generate();
                         }
                     }
                     CursorMode::Delete => {
                         if let Some(i) = idx {
-                            self.points.remove(i);
-                            self.generate();
+                            if self.points[i].borrow().pinned {
+                                self.toasts.add("Unpin the point to delete it");
+                            } else {
+                                self.journal.record(format!("Deleted point {}", i + 1));
+                                self.points.remove(i);
+                                self.generate();
+                            }
                         }
                     }
                     CursorMode::Trim => {
                         if let Some(i) = idx {
-                            self.points.truncate(i);
-                            self.generate();
+                            let range = if self.from_start {
+                                0..i + 1
+                            } else {
+                                i..self.points.len()
+                            };
+                            if self.points[range.clone()].iter().any(|p| p.borrow().pinned) {
+                                self.toasts.add("Unpin the points to trim them");
+                            } else {
+                                if range.len() >= BACKUP_TRIM_THRESHOLD {
+                                    self.backup();
+                                }
+                                self.journal.record(format!(
+                                    "Trimmed points {} to {}",
+                                    range.start + 1,
+                                    range.end
+                                ));
+                                self.points.drain(range);
+                                self.generate();
+                            }
                         }
                     }
                     CursorMode::Insert => {
                         if let Some(pos) = closest {
-                            let x = (pos.x - rect.min.x) * (self.size / self.scale as f32);
-                            let y = (pos.y - rect.min.y) * (self.size / self.scale as f32);
+                            let Pos2 { x, y } = self.to_field(pos, rect.min);
                             // Calculate future x and ys
                             let Point { x: fx, y: fy, .. } = interpolate(
                                 &self.points[closest_idx].borrow(),
                                 &self.points[closest_idx + 1].borrow(),
                                 closest_step + 0.1,
                             );
-                            self.points.insert(
-                                closest_idx + 1,
-                                BezPoint::new(x, y, 2.0 * x - fx, 2.0 * y - fy, fx, fy),
+                            let point = BezPoint::new(x, y, 2.0 * x - fx, 2.0 * y - fy, fx, fy);
+                            point.borrow_mut().mode = self.handle_mode;
+                            if self.wall_snap {
+                                transform::snap_to_wall(
+                                    &mut point.borrow_mut(),
+                                    self.size,
+                                    self.wall_reach,
+                                    self.wall_offset,
+                                );
+                            }
+                            self.journal.record(format!(
+                                "Inserted point {} at ({x:.1}, {y:.1})",
+                                closest_idx + 2
+                            ));
+                            self.points.insert(closest_idx + 1, point);
+                            self.generate();
+                        }
+                    }
+                    CursorMode::Split => {
+                        if closest.is_some() {
+                            let point = split(
+                                &mut self.points[closest_idx].borrow_mut(),
+                                &mut self.points[closest_idx + 1].borrow_mut(),
+                                closest_step,
                             );
+                            self.journal.record(format!(
+                                "Split segment {} at {closest_step:.2}",
+                                closest_idx + 1
+                            ));
+                            self.points.insert(closest_idx + 1, point);
                             self.generate();
                         }
                     }
@@ -574,8 +5361,17 @@ impl eframe::App for PathyApp {
                 if let Some(point) = &self.selected {
                     if let Some(pos) = ctx.pointer_interact_pos() {
                         if let Ok(mut p) = point.try_borrow_mut() {
-                            p.x = (pos.x - rect.min.x) * (self.size / self.scale as f32);
-                            p.y = (pos.y - rect.min.y) * (self.size / self.scale as f32);
+                            let mut target = self.to_field(pos, rect.min);
+                            // Shift keeps the point on whichever axis it has moved along most
+                            if ctx.input(|i| i.modifiers.shift) {
+                                let delta = target - self.drag_start;
+                                if delta.x.abs() > delta.y.abs() {
+                                    target.y = self.drag_start.y;
+                                } else {
+                                    target.x = self.drag_start.x;
+                                }
+                            }
+                            Pos2 { x: p.x, y: p.y } = target;
                             changed = true;
                         }
                     }
@@ -585,6 +5381,69 @@ impl eframe::App for PathyApp {
                 }
             }
 
+            // Dragging out a new point sets its handles along the drag, like a pen tool
+            if self.cursor_mode == CursorMode::Create {
+                if resp.drag_started()
+                    && selected.is_none()
+                    && self.selected.is_none()
+                    && self.turning.is_none()
+                {
+                    if let Some(origin) = ctx
+                        .input(|i| i.pointer.press_origin())
+                        .filter(|p| rect.contains(*p))
+                    {
+                        self.pen = Some(self.create_point(ui, self.to_field(origin, rect.min)));
+                    }
+                }
+                if let Some(point) = &self.pen {
+                    if let Some(pos) = resp.interact_pointer_pos() {
+                        let point = point.borrow();
+                        let anchor = Pos2::from(point.pos.borrow().clone());
+                        let exit = self.to_field(pos, rect.min);
+                        for (handle, target) in
+                            [(&point.cp2, exit), (&point.cp1, anchor - (exit - anchor))]
+                        {
+                            let mut handle = handle.borrow_mut();
+                            Pos2 {
+                                x: handle.x,
+                                y: handle.y,
+                            } = target;
+                        }
+                    }
+                    if !resp.dragged() {
+                        self.pen = None;
+                    }
+                    self.generate();
+                }
+            }
+
+            // Sketching records the stroke while dragging, then fits points to it
+            if self.cursor_mode == CursorMode::Sketch {
+                let ratio = self.scale as f32 / self.size;
+                if resp.dragged() {
+                    if let Some(pos) = resp.interact_pointer_pos() {
+                        self.stroke.push(((pos - rect.min) / ratio).to_pos2());
+                    }
+                }
+                if resp.drag_stopped() {
+                    let fitted = sketch::fit(&self.stroke, self.sketch_tolerance);
+                    self.stroke.clear();
+                    if !fitted.is_empty() {
+                        self.journal
+                            .record(format!("Sketched {} points", fitted.len()));
+                        self.points.extend(fitted);
+                        self.generate();
+                    }
+                }
+                let stroke: Vec<Pos2> = self
+                    .stroke
+                    .iter()
+                    .map(|p| rect.min + p.to_vec2() * ratio)
+                    .collect();
+                ui.painter()
+                    .add(egui::Shape::line(stroke, Stroke::new(2.0, Color32::WHITE)));
+            }
+
             /* TOOLTIPS */
             match &self.cursor_mode {
                 CursorMode::Create => {
@@ -597,7 +5456,7 @@ impl eframe::App for PathyApp {
                             .circle_stroke(pos, 5.0, Stroke::new(2.0, Color32::YELLOW));
                     }
                 }
-                CursorMode::Insert => {
+                CursorMode::Insert | CursorMode::Split => {
                     // Display circle under closest point
                     if let Some(pos) = closest {
                         ui.painter()
@@ -608,11 +5467,41 @@ impl eframe::App for PathyApp {
             }
 
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+                if let Some(pos) = resp.hover_pos() {
+                    let field = self.to_field(pos, rect.min);
+                    ui.label(format!(
+                        "{}, {}",
+                        self.length_text(field.x),
+                        self.length_text(field.y)
+                    ));
+                    // Length of the segment under the pointer
+                    let reach = 6.0 * self.size / self.scale as f32;
+                    let hovered = self
+                        .segment_samples()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, line)| (i, analysis::distance_to(&line, field), line))
+                        .filter(|(_, d, _)| *d < reach)
+                        .min_by(|a, b| a.1.total_cmp(&b.1));
+                    if let Some((i, _, line)) = hovered {
+                        let length = line.windows(2).map(|w| w[0].distance(w[1])).sum();
+                        ui.label(format!(
+                            "Segment {} to {}: {}",
+                            i + 1,
+                            i + 2,
+                            self.length_text(length)
+                        ));
+                    }
+                }
                 if self.background == Background::Custom && self.overlay.is_none() {
-                    ui.label("Drag an drop an image to set the field background!");
+                    ui.label("Drag an drop or paste an image to set the field background!");
                 }
                 egui::warn_if_debug_build(ui);
             });
         });
+        // Edits made this frame are validated at the start of the next
+        if self.revalidate {
+            ctx.request_repaint();
+        }
     }
 }
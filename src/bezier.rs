@@ -3,6 +3,7 @@ use std::{
     rc::{Rc, Weak},
 };
 
+use crate::action::Instance;
 use crate::app::CursorMode;
 use egui::{lerp, pos2, Color32, Context, Pos2, Stroke, Ui};
 use uuid::Uuid;
@@ -31,6 +32,7 @@ extern "C" {
     fn log_many(a: &str, b: &str);
 }
 
+#[allow(unused_macros)]
 macro_rules! console_log {
     // Note that this is using the `log` function imported above during
     // `bare_bones`
@@ -50,8 +52,102 @@ pub struct BezPoint {
     pub animated: bool,
     // Previous position to keep track of offsets
     prev: Point,
-    // If the path "breaks" here (cusp, not tangent)
-    pub broken: bool,
+    // How the control points move relative to each other
+    pub mode: HandleMode,
+    // Expressions driving the position, empty if unused
+    pub x_expr: String,
+    pub y_expr: String,
+    // Pinned points can't be dragged, deleted or trimmed
+    pub pinned: bool,
+    // Target facing in degrees, for robots that can turn independently of travel
+    pub heading: Option<f32>,
+    // Speed limit through this point in inches per second
+    pub speed: Option<f32>,
+    // Whether the robot comes to a full stop here
+    pub stop: bool,
+    // Pause when the robot reaches this point
+    pub wait: Option<Wait>,
+    // Library actions run when the robot reaches this point
+    pub actions: Vec<Instance>,
+    // Whether the robot backs through the segment leaving this point
+    pub reverse: bool,
+    // Samples along the segment leaving this point, instead of the path's density
+    pub steps: Option<usize>,
+    // Label shown on the field and in exports, empty if unnamed
+    pub name: String,
+    // Sync marker the robot waits at for its partner, empty if none
+    pub marker: String,
+}
+
+/// A pause when the robot reaches a point.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub enum Wait {
+    /// Seconds to wait for, after stopping
+    Time(f32),
+    /// Robot code for a condition to wait for
+    Until(String),
+}
+
+/// Default side length of the robot, in inches.
+pub const ROBOT_SIZE: f32 = 18.0;
+
+/// Where the robot is placed before a path starts.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Pose {
+    pub pos: Pos2,
+    /// Facing in degrees, measured like point headings
+    pub heading: f32,
+}
+
+impl Pose {
+    /// Corners of a robot `length` long and `width` wide at this pose, front corners first.
+    pub fn outline(&self, length: f32, width: f32) -> [Pos2; 4] {
+        let facing = egui::Vec2::angled(self.heading.to_radians());
+        let forward = facing * length / 2.0;
+        let left = facing.rot90() * width / 2.0;
+        [
+            self.pos + forward - left,
+            self.pos + forward + left,
+            self.pos - forward + left,
+            self.pos - forward - left,
+        ]
+    }
+}
+
+/// What the robot must be doing when it reaches the end of a path, for the follower to be done.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct EndPose {
+    /// Facing in degrees, taking the place of the last point's heading
+    pub heading: Option<f32>,
+    /// Speed in inches per second, if the robot shouldn't come to rest
+    pub speed: Option<f32>,
+}
+
+impl EndPose {
+    /// Makes the end heading the last point's, so heading interpolation finishes on it.
+    pub fn apply(&self, points: &mut [BezPoint]) {
+        if let (Some(heading), Some(last)) = (self.heading, points.last_mut()) {
+            last.heading = Some(heading);
+        }
+    }
+    /// Whether the robot is still moving at the end, so it can't turn in place there.
+    pub fn moving(&self) -> bool {
+        self.speed.is_some_and(|v| v > 0.0)
+    }
+}
+
+/// How a point's control handles are tied together.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HandleMode {
+    /// Equal length, opposite direction
+    #[default]
+    Mirrored,
+    /// Opposite direction, independent lengths
+    Aligned,
+    /// Independent handles, the path "breaks" here (cusp, not tangent)
+    Broken,
+    /// Zero-length handles, a sharp corner the robot stops and turns in place at
+    Sharp,
 }
 
 /// A single selectable point.
@@ -66,13 +162,39 @@ pub struct Point {
 }
 
 /// A saved Bezier point.
-#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
 pub struct SavePoint {
     pub pos: Pos2,
     pub cp1: Pos2,
     pub cp2: Pos2,
     pub id: Uuid,
     pub broken: bool,
+    #[serde(default)]
+    pub mode: Option<HandleMode>,
+    #[serde(default)]
+    pub x_expr: String,
+    #[serde(default)]
+    pub y_expr: String,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub heading: Option<f32>,
+    #[serde(default)]
+    pub speed: Option<f32>,
+    #[serde(default)]
+    pub stop: bool,
+    #[serde(default)]
+    pub wait: Option<Wait>,
+    #[serde(default)]
+    pub actions: Vec<Instance>,
+    #[serde(default)]
+    pub reverse: bool,
+    #[serde(default)]
+    pub steps: Option<usize>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub marker: String,
 }
 
 impl Point {
@@ -112,7 +234,20 @@ impl From<BezPoint> for SavePoint {
             cp1: point.cp1.borrow().clone().into(),
             cp2: point.cp2.borrow().clone().into(),
             id: point.id,
-            broken: point.broken,
+            broken: point.broken(),
+            mode: Some(point.mode),
+            x_expr: point.x_expr,
+            y_expr: point.y_expr,
+            pinned: point.pinned,
+            heading: point.heading,
+            speed: point.speed,
+            stop: point.stop,
+            wait: point.wait,
+            actions: point.actions,
+            reverse: point.reverse,
+            steps: point.steps,
+            name: point.name,
+            marker: point.marker,
         }
     }
 }
@@ -139,7 +274,24 @@ impl From<SavePoint> for BezPoint {
             id: point.id,
             animated: true,
             prev: Point::new(point.pos.x, point.pos.y, Weak::new()),
-            broken: point.broken,
+            // Older saves only know whether the point is broken
+            mode: point.mode.unwrap_or(if point.broken {
+                HandleMode::Broken
+            } else {
+                HandleMode::Mirrored
+            }),
+            x_expr: point.x_expr,
+            y_expr: point.y_expr,
+            pinned: point.pinned,
+            heading: point.heading,
+            speed: point.speed,
+            stop: point.stop,
+            wait: point.wait,
+            actions: point.actions,
+            reverse: point.reverse,
+            steps: point.steps,
+            name: point.name,
+            marker: point.marker,
         }
     }
 }
@@ -162,9 +314,45 @@ impl BezPoint {
             id: Uuid::new_v4(),
             animated: false,
             prev: Point::new(x, y, Weak::new()),
-            broken: false,
+            mode: HandleMode::Mirrored,
+            x_expr: String::new(),
+            y_expr: String::new(),
+            pinned: false,
+            heading: None,
+            speed: None,
+            stop: false,
+            wait: None,
+            actions: Vec::new(),
+            reverse: false,
+            steps: None,
+            name: String::new(),
+            marker: String::new(),
         })
     }
+    /// Creates a new point continuing the path from this one.
+    /// Its control points lie on the line from this point's exit handle.
+    pub fn after(&self, x: f32, y: f32) -> Rc<RefCell<Self>> {
+        let Pos2 { x: ix, y: iy } = Pos2::from(self.cp2.borrow().clone()).lerp(pos2(x, y), 0.5);
+        Self::new(x, y, ix, iy, 2.0 * x - ix, 2.0 * y - iy)
+    }
+    /// Creates a new point leading into this one, for extending the path from its start.
+    /// Its control points lie on the line from this point's entry handle.
+    pub fn before(&self, x: f32, y: f32) -> Rc<RefCell<Self>> {
+        let Pos2 { x: ix, y: iy } = Pos2::from(self.cp1.borrow().clone()).lerp(pos2(x, y), 0.5);
+        Self::new(x, y, 2.0 * x - ix, 2.0 * y - iy, ix, iy)
+    }
+    /// Creates a copy of this point with a new id, shifted by `dx`, `dy`.
+    /// The copy is not driven by this point's expressions, and is not pinned.
+    pub fn duplicate(&self, dx: f32, dy: f32) -> Rc<RefCell<Self>> {
+        let mut saved = SavePoint::from(self.clone());
+        saved.id = Uuid::new_v4();
+        saved.x_expr.clear();
+        saved.y_expr.clear();
+        saved.pinned = false;
+        let copy = Self::load(saved.into());
+        copy.borrow_mut().translate(dx, dy);
+        copy
+    }
     /// Creates a new Bezier point from a saved one, instantiating its references.
     ///
     /// # Arguments
@@ -177,6 +365,31 @@ impl BezPoint {
         this.borrow_mut().prev.parent = Rc::downgrade(&this);
         this
     }
+    /// Whether the path breaks at this point.
+    pub fn broken(&self) -> bool {
+        matches!(self.mode, HandleMode::Broken | HandleMode::Sharp)
+    }
+    /// Moves the point along with its control points.
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.pos.borrow_mut().offset(dx, dy);
+        self.cp1.borrow_mut().offset(dx, dy);
+        self.cp2.borrow_mut().offset(dx, dy);
+        self.prev.offset(dx, dy);
+    }
+    /// Maps the point and its control points through a transform.
+    pub fn transform(&mut self, f: impl Fn(Pos2) -> Pos2) {
+        for point in [&self.pos, &self.cp1, &self.cp2] {
+            let mut point = point.borrow_mut();
+            Pos2 {
+                x: point.x,
+                y: point.y,
+            } = f(pos2(point.x, point.y));
+        }
+        Pos2 {
+            x: self.prev.x,
+            y: self.prev.y,
+        } = f(pos2(self.prev.x, self.prev.y));
+    }
     /// Draws the bezier point and handles, handling animations and hover states.
     /// If hovered, returns the hovered point.
     ///
@@ -209,26 +422,49 @@ impl BezPoint {
         let cp2_id = id.with(2);
 
         // Keep control points in line if unbroken
+        let smooth = self.mode == HandleMode::Aligned;
         if self.pos.borrow().locked || self.pos.borrow().editing {
             let dx = self.pos.borrow().x - self.prev.x;
             let dy = self.pos.borrow().y - self.prev.y;
             self.cp1.borrow_mut().offset(dx, dy);
             self.cp2.borrow_mut().offset(dx, dy);
             self.prev = self.pos.borrow().clone();
-        } else if !self.broken
+        } else if !self.broken()
             && (self.cp1.borrow().locked || self.cp1.borrow().editing || self.pos.borrow().editing)
         {
-            self.cp2.borrow_mut().x = 2.0 * self.pos.borrow().x - self.cp1.borrow().x;
-            self.cp2.borrow_mut().y = 2.0 * self.pos.borrow().y - self.cp1.borrow().y;
-        } else if !self.broken && (self.cp2.borrow().locked || self.cp2.borrow().editing) {
-            self.cp1.borrow_mut().x = 2.0 * self.pos.borrow().x - self.cp2.borrow().x;
-            self.cp1.borrow_mut().y = 2.0 * self.pos.borrow().y - self.cp2.borrow().y;
+            align(
+                &self.pos.borrow(),
+                &self.cp1.borrow(),
+                &mut self.cp2.borrow_mut(),
+                smooth,
+            );
+        } else if !self.broken() && (self.cp2.borrow().locked || self.cp2.borrow().editing) {
+            align(
+                &self.pos.borrow(),
+                &self.cp2.borrow(),
+                &mut self.cp1.borrow_mut(),
+                smooth,
+            );
+        }
+
+        // Sharp corners keep their handles on the anchor
+        let sharp = self.mode == HandleMode::Sharp;
+        if sharp {
+            for handle in [&self.cp1, &self.cp2] {
+                let mut handle = handle.borrow_mut();
+                handle.x = self.pos.borrow().x;
+                handle.y = self.pos.borrow().y;
+            }
         }
 
         // Ensure points are in line
-        if !self.broken {
-            self.cp2.borrow_mut().x = 2.0 * self.pos.borrow().x - self.cp1.borrow().x;
-            self.cp2.borrow_mut().y = 2.0 * self.pos.borrow().y - self.cp1.borrow().y;
+        if !self.broken() {
+            align(
+                &self.pos.borrow(),
+                &self.cp1.borrow(),
+                &mut self.cp2.borrow_mut(),
+                smooth,
+            );
         }
 
         // Main point
@@ -250,16 +486,18 @@ impl BezPoint {
         self.cp2.borrow_mut().selected = false;
         let mut selected: Option<Selected> = None;
         if let Some(hover_pos) = hover_pos {
+            // Hidden handles can't be grabbed
+            let hidden = if sharp { f32::INFINITY } else { 0.0 };
             let mut distances = [
                 (
                     Selected::CP1,
                     &mut self.cp1.borrow_mut().selected,
-                    hover_pos.distance_sq(pos2(cp1x, cp1y)),
+                    hover_pos.distance_sq(pos2(cp1x, cp1y)) + hidden,
                 ),
                 (
                     Selected::CP2,
                     &mut self.cp2.borrow_mut().selected,
-                    hover_pos.distance_sq(pos2(cp2x, cp2y)),
+                    hover_pos.distance_sq(pos2(cp2x, cp2y)) + hidden,
                 ),
             ];
             let mut point_dis = (
@@ -281,7 +519,11 @@ impl BezPoint {
         // Update point radii based on hover state
         let dont_select = matches!(
             *mode,
-            CursorMode::Delete | CursorMode::Trim | CursorMode::Insert
+            CursorMode::Delete
+                | CursorMode::Trim
+                | CursorMode::Insert
+                | CursorMode::Split
+                | CursorMode::Sketch
         );
         let p_r = lerp(
             r..=r_hov,
@@ -308,11 +550,12 @@ impl BezPoint {
             Color32::RED,
             ctx.animate_bool(
                 id,
-                (*mode == CursorMode::Trim)
-                    || ((*mode == CursorMode::Delete)
-                        && (self.pos.borrow().selected
-                            || self.cp1.borrow().selected
-                            || self.cp2.borrow().selected)),
+                !self.pinned
+                    && ((*mode == CursorMode::Trim)
+                        || ((*mode == CursorMode::Delete)
+                            && (self.pos.borrow().selected
+                                || self.cp1.borrow().selected
+                                || self.cp2.borrow().selected))),
             ),
         );
 
@@ -330,21 +573,33 @@ impl BezPoint {
         let yoffset2 = (cp2_r + 1.0) * dy2 / mag2;
 
         // Control lines
-        ui.painter().line_segment(
-            [pos2(x, y), pos2(cp1x - xoffset1, cp1y - yoffset1)],
-            Stroke::new(2.0, color),
-        );
-        ui.painter().line_segment(
-            [pos2(x, y), pos2(cp2x - xoffset2, cp2y - yoffset2)],
-            Stroke::new(2.0, color),
-        );
+        if !sharp {
+            ui.painter().line_segment(
+                [pos2(x, y), pos2(cp1x - xoffset1, cp1y - yoffset1)],
+                Stroke::new(2.0, color),
+            );
+            ui.painter().line_segment(
+                [pos2(x, y), pos2(cp2x - xoffset2, cp2y - yoffset2)],
+                Stroke::new(2.0, color),
+            );
+        }
 
         // Draw points
-        ui.painter().circle_filled(pos2(x, y), p_r, color);
-        ui.painter()
-            .circle_stroke(pos2(cp1x, cp1y), cp1_r, Stroke::new(2.0, color));
-        ui.painter()
-            .circle_stroke(pos2(cp2x, cp2y), cp2_r, Stroke::new(2.0, color));
+        if self.pinned {
+            ui.painter().rect_filled(
+                egui::Rect::from_center_size(pos2(x, y), egui::Vec2::splat(2.0 * p_r)),
+                0.0,
+                color,
+            );
+        } else {
+            ui.painter().circle_filled(pos2(x, y), p_r, color);
+        }
+        if !sharp {
+            ui.painter()
+                .circle_stroke(pos2(cp1x, cp1y), cp1_r, Stroke::new(2.0, color));
+            ui.painter()
+                .circle_stroke(pos2(cp2x, cp2y), cp2_r, Stroke::new(2.0, color));
+        }
         match selected {
             Some(Selected::P) => Some(self.pos.clone()),
             Some(Selected::CP1) => Some(self.cp1.clone()),
@@ -354,6 +609,22 @@ impl BezPoint {
     }
 }
 
+/// Places a control point opposite another about their anchor, keeping the handles collinear.
+/// Mirrors the moved handle exactly unless `smooth`, in which case `other` keeps its length.
+fn align(center: &Point, moved: &Point, other: &mut Point, smooth: bool) {
+    let dx = moved.x - center.x;
+    let dy = moved.y - center.y;
+    let len = (dx * dx + dy * dy).sqrt();
+    if !smooth || len < 1e-6 {
+        other.x = center.x - dx;
+        other.y = center.y - dy;
+        return;
+    }
+    let other_len = ((other.x - center.x).powi(2) + (other.y - center.y).powi(2)).sqrt();
+    other.x = center.x - dx / len * other_len;
+    other.y = center.y - dy / len * other_len;
+}
+
 /// Find the in-between point of a Bezier curve section at t, where t is from [0, 1].
 pub fn interpolate(a: &BezPoint, b: &BezPoint, t: f32) -> Point {
     let x = (1.0 - t).powi(3) * a.pos.borrow().x
@@ -367,6 +638,68 @@ pub fn interpolate(a: &BezPoint, b: &BezPoint, t: f32) -> Point {
     Point::new(x, y, Weak::new())
 }
 
+/// Turns the Bezier curve section between `a` and `b` into a straight line
+/// by moving the two handles onto the chord, a third of the way along from each end.
+/// Unbroken points turn their other handle to match, as if the handle had been dragged.
+pub fn straighten(a: &BezPoint, b: &BezPoint) {
+    let (start, end) = (
+        Pos2::from(a.pos.borrow().clone()),
+        Pos2::from(b.pos.borrow().clone()),
+    );
+    let third = (end - start) / 3.0;
+    for (point, moved, other, target) in [
+        (a, &a.cp2, &a.cp1, start + third),
+        (b, &b.cp1, &b.cp2, end - third),
+    ] {
+        {
+            let mut moved = moved.borrow_mut();
+            moved.x = target.x;
+            moved.y = target.y;
+        }
+        if !point.broken() {
+            align(
+                &point.pos.borrow(),
+                &moved.borrow(),
+                &mut other.borrow_mut(),
+                point.mode == HandleMode::Aligned,
+            );
+        }
+    }
+}
+
+/// Splits the Bezier curve section between `a` and `b` at t using De Casteljau subdivision,
+/// so the two halves trace exactly the same shape as the original section.
+/// `a`'s exit handle and `b`'s entry handle are shortened to fit, so mirrored ends become aligned.
+///
+/// # Returns
+/// The new point at t, to be placed between `a` and `b`.
+pub fn split(a: &mut BezPoint, b: &mut BezPoint, t: f32) -> Rc<RefCell<BezPoint>> {
+    let [p0, p1, p2, p3] = [&a.pos, &a.cp2, &b.cp1, &b.pos].map(|p| Pos2::from(p.borrow().clone()));
+    let (q0, q1, q2) = (p0.lerp(p1, t), p1.lerp(p2, t), p2.lerp(p3, t));
+    let (r0, r1) = (q0.lerp(q1, t), q1.lerp(q2, t));
+    let s = r0.lerp(r1, t);
+    for end in [&mut *a, &mut *b] {
+        if end.mode == HandleMode::Mirrored {
+            end.mode = HandleMode::Aligned;
+        }
+    }
+    for (handle, pos) in [(&a.cp2, q0), (&b.cp1, q2)] {
+        let mut handle = handle.borrow_mut();
+        handle.x = pos.x;
+        handle.y = pos.y;
+    }
+    let point = BezPoint::new(s.x, s.y, r0.x, r0.y, r1.x, r1.y);
+    {
+        let mut point = point.borrow_mut();
+        // The halves' handles are collinear but rarely the same length
+        point.mode = HandleMode::Aligned;
+        // Both halves are driven like the segment they came from
+        point.reverse = a.reverse;
+        point.steps = a.steps;
+    }
+    point
+}
+
 /*
 /// Find the in-between slope of a Bezier curve section at t, where t is from [0, 1].
 /// # Returns
@@ -0,0 +1,128 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::bezier::BezPoint;
+use egui::{Pos2, Vec2};
+use uuid::Uuid;
+
+/// Number of relaxation passes per solve.
+const ITERATIONS: usize = 10;
+/// Error below which a constraint counts as satisfied.
+const TOLERANCE: f32 = 1e-4;
+
+/// A relationship between anchor points, kept satisfied as points move.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub enum Constraint {
+    /// `b` sits exactly `dx`, `dy` away from `a`.
+    Offset { a: Uuid, b: Uuid, dx: f32, dy: f32 },
+    /// All points lie on one line.
+    Collinear(Vec<Uuid>),
+}
+
+impl Constraint {
+    /// The ids of every point this constraint references.
+    pub fn points(&self) -> Vec<Uuid> {
+        match self {
+            Constraint::Offset { a, b, .. } => vec![*a, *b],
+            Constraint::Collinear(ids) => ids.clone(),
+        }
+    }
+    /// Maps a fixed offset through a transform, so it survives moving the whole path.
+    pub fn transform_offset(&mut self, f: impl Fn(Vec2) -> Vec2) {
+        if let Constraint::Offset { dx, dy, .. } = self {
+            Vec2 { x: *dx, y: *dy } = f(Vec2::new(*dx, *dy));
+        }
+    }
+}
+
+/// Moves anchor points until every constraint is satisfied.
+/// The `fixed` point, usually the one being dragged, and pinned points are never moved.
+///
+/// # Returns
+/// Whether any point was moved.
+pub fn solve(
+    points: &[Rc<RefCell<BezPoint>>],
+    constraints: &[Constraint],
+    fixed: Option<Uuid>,
+) -> bool {
+    let find = |id: &Uuid| points.iter().find(|p| p.borrow().id == *id);
+    let pos = |p: &Rc<RefCell<BezPoint>>| Pos2::from(p.borrow().pos.borrow().clone());
+    let held = |p: &Rc<RefCell<BezPoint>>| {
+        let p = p.borrow();
+        p.pinned || fixed == Some(p.id)
+    };
+    let mut moved = false;
+    for _ in 0..ITERATIONS {
+        let mut error = 0.0;
+        for constraint in constraints {
+            match constraint {
+                Constraint::Offset { a, b, dx, dy } => {
+                    let (Some(pa), Some(pb)) = (find(a), find(b)) else {
+                        continue;
+                    };
+                    if Rc::ptr_eq(pa, pb) {
+                        continue;
+                    }
+                    let ex = pos(pb).x - pos(pa).x - dx;
+                    let ey = pos(pb).y - pos(pa).y - dy;
+                    // Split the correction unless one side is held in place
+                    let (wa, wb) = match (held(pa), held(pb)) {
+                        (true, true) => continue,
+                        (true, false) => (0.0, 1.0),
+                        (false, true) => (1.0, 0.0),
+                        (false, false) => (0.5, 0.5),
+                    };
+                    pa.borrow_mut().translate(ex * wa, ey * wa);
+                    pb.borrow_mut().translate(-ex * wb, -ey * wb);
+                    error += ex.abs() + ey.abs();
+                }
+                Constraint::Collinear(ids) => {
+                    let members: Vec<_> = ids.iter().filter_map(&find).collect();
+                    if members.len() < 3 {
+                        continue;
+                    }
+                    // Best fit line through the points
+                    let positions: Vec<Pos2> = members.iter().map(|p| pos(p)).collect();
+                    let n = positions.len() as f32;
+                    let cx = positions.iter().map(|p| p.x).sum::<f32>() / n;
+                    let cy = positions.iter().map(|p| p.y).sum::<f32>() / n;
+                    let (mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0);
+                    for p in &positions {
+                        sxx += (p.x - cx).powi(2);
+                        syy += (p.y - cy).powi(2);
+                        sxy += (p.x - cx) * (p.y - cy);
+                    }
+                    let mut angle = 0.5 * f32::atan2(2.0 * sxy, sxx - syy);
+                    // Pass the line through the held points if any are on it
+                    let anchors: Vec<Pos2> = members
+                        .iter()
+                        .zip(&positions)
+                        .filter(|(member, _)| held(member))
+                        .map(|(_, p)| *p)
+                        .collect();
+                    if let [first, second, ..] = anchors[..] {
+                        if first != second {
+                            angle = (second - first).angle();
+                        }
+                    }
+                    let (dir_y, dir_x) = angle.sin_cos();
+                    let origin = anchors.first().copied().unwrap_or(egui::pos2(cx, cy));
+                    for (member, p) in members.iter().zip(&positions) {
+                        if held(member) {
+                            continue;
+                        }
+                        let t = (p.x - origin.x) * dir_x + (p.y - origin.y) * dir_y;
+                        let ex = origin.x + dir_x * t - p.x;
+                        let ey = origin.y + dir_y * t - p.y;
+                        member.borrow_mut().translate(ex, ey);
+                        error += ex.abs() + ey.abs();
+                    }
+                }
+            }
+        }
+        if error < TOLERANCE {
+            break;
+        }
+        moved = true;
+    }
+    moved
+}
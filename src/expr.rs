@@ -0,0 +1,312 @@
+use std::{cell::RefCell, iter::Peekable, rc::Rc, str::Chars};
+
+use crate::bezier::BezPoint;
+use egui::Pos2;
+
+/// A match alliance, used to pick per-alliance variable values.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alliance {
+    #[default]
+    Red,
+    Blue,
+}
+
+/// A named number usable in point coordinate expressions.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub value: f32,
+    /// Overrides used for each alliance, if the variable differs between them
+    #[serde(default)]
+    pub red: Option<f32>,
+    #[serde(default)]
+    pub blue: Option<f32>,
+}
+
+impl Variable {
+    /// Whether the variable has different values per alliance.
+    pub fn per_alliance(&self) -> bool {
+        self.red.is_some() || self.blue.is_some()
+    }
+    /// The value of the variable for an alliance.
+    pub fn value_for(&self, alliance: Alliance) -> f32 {
+        match alliance {
+            Alliance::Red => self.red,
+            Alliance::Blue => self.blue,
+        }
+        .unwrap_or(self.value)
+    }
+}
+
+/// Resolves per-alliance overrides into plain variables.
+pub fn resolve(vars: &[Variable], alliance: Alliance) -> Vec<Variable> {
+    vars.iter()
+        .map(|v| Variable {
+            name: v.name.clone(),
+            value: v.value_for(alliance),
+            red: None,
+            blue: None,
+        })
+        .collect()
+}
+
+/// Moves points driven by expressions to their evaluated positions.
+/// Points with invalid expressions are left where they are.
+///
+/// # Returns
+/// Whether any point was moved.
+pub fn apply(points: &[Rc<RefCell<BezPoint>>], vars: &[Variable]) -> bool {
+    let mut moved = false;
+    for point in points {
+        let mut point = point.borrow_mut();
+        let Pos2 { x, y } = Pos2::from(point.pos.borrow().clone());
+        let eval = |src: &str, current: f32| {
+            if src.is_empty() {
+                current
+            } else {
+                eval(src, vars).unwrap_or(current)
+            }
+        };
+        let dx = eval(&point.x_expr, x) - x;
+        let dy = eval(&point.y_expr, y) - y;
+        if dx.abs() > 1e-6 || dy.abs() > 1e-6 {
+            point.translate(dx, dy);
+            moved = true;
+        }
+    }
+    moved
+}
+
+/// Stops points following expressions that no longer give their position, such as after an edit
+/// that knows nothing of expressions mirrored or moved them. Otherwise [`apply`] would move their
+/// anchors back, leaving their handles as the edit put them.
+///
+/// # Returns
+/// How many points had an expression cleared.
+pub fn release(points: &[Rc<RefCell<BezPoint>>], vars: &[Variable]) -> usize {
+    let mut released = 0;
+    for point in points {
+        let mut point = point.borrow_mut();
+        let point = &mut *point;
+        let Pos2 { x, y } = Pos2::from(point.pos.borrow().clone());
+        let mut moved = false;
+        for (src, current) in [(&mut point.x_expr, x), (&mut point.y_expr, y)] {
+            if src.is_empty() {
+                continue;
+            }
+            if eval(src, vars).is_ok_and(|value| (value - current).abs() > 1e-3) {
+                src.clear();
+                moved = true;
+            }
+        }
+        if moved {
+            released += 1;
+        }
+    }
+    released
+}
+
+/// Evaluates an arithmetic expression such as `approach_offset + 4in`.
+/// Supports `+ - * /`, parentheses, an optional `in` suffix on numbers,
+/// and the functions `sin`, `cos`, `tan` (in degrees), `sqrt` and `abs`.
+/// Results that aren't finite, like dividing by zero, are errors, since no point can be there.
+pub fn eval(src: &str, vars: &[Variable]) -> Result<f32, String> {
+    let mut parser = Parser {
+        chars: src.chars().peekable(),
+        vars,
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        Some(c) => Err(format!("Unexpected '{c}'")),
+        None if !value.is_finite() => Err("The result isn't a finite number".into()),
+        None => Ok(value),
+    }
+}
+
+/// Deepest that negations, parentheses and function calls may nest,
+/// so a malformed expression can't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser over an expression string.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    vars: &'a [Variable],
+    /// How many factors are being parsed inside each other
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+    /// expr = term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<f32, String> {
+        let mut value = self.term()?;
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some('+') => {
+                    self.chars.next();
+                    value += self.term()?;
+                }
+                Some('-') => {
+                    self.chars.next();
+                    value -= self.term()?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+    /// term = factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<f32, String> {
+        let mut value = self.factor()?;
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some('*') => {
+                    self.chars.next();
+                    value *= self.factor()?;
+                }
+                Some('/') => {
+                    self.chars.next();
+                    value /= self.factor()?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+    /// Parses a factor, refusing to nest deeper than [`MAX_DEPTH`].
+    fn factor(&mut self) -> Result<f32, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err("Nested too deeply".into());
+        }
+        self.depth += 1;
+        let value = self.nested();
+        self.depth -= 1;
+        value
+    }
+    /// factor = '-' factor | '(' expr ')' | number ['in'] | name ['(' expr ')']
+    fn nested(&mut self) -> Result<f32, String> {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some('-') => {
+                self.chars.next();
+                Ok(-self.factor()?)
+            }
+            Some('(') => {
+                self.chars.next();
+                let value = self.expr()?;
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some(')') => Ok(value),
+                    _ => Err("Missing ')'".into()),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                let value = number
+                    .parse::<f32>()
+                    .map_err(|_| format!("Bad number '{number}'"))?;
+                // Inches are the only unit, so the suffix is purely cosmetic
+                let unit = self.name();
+                match unit.as_str() {
+                    "" | "in" => Ok(value),
+                    _ => Err(format!("Unknown unit '{unit}'")),
+                }
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.name();
+                if self.chars.next_if_eq(&'(').is_some() {
+                    let arg = self.expr()?;
+                    self.skip_whitespace();
+                    if self.chars.next() != Some(')') {
+                        return Err("Missing ')'".into());
+                    }
+                    return match name.as_str() {
+                        "sin" => Ok(arg.to_radians().sin()),
+                        "cos" => Ok(arg.to_radians().cos()),
+                        "tan" => Ok(arg.to_radians().tan()),
+                        "sqrt" => Ok(arg.sqrt()),
+                        "abs" => Ok(arg.abs()),
+                        _ => Err(format!("Unknown function '{name}'")),
+                    };
+                }
+                self.vars
+                    .iter()
+                    .find(|v| v.name == name)
+                    .map(|v| v.value)
+                    .ok_or(format!("Unknown variable '{name}'"))
+            }
+            Some(c) => Err(format!("Unexpected '{c}'")),
+            None => Err("Unexpected end".into()),
+        }
+    }
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str, value: f32) -> Variable {
+        Variable {
+            name: name.into(),
+            value,
+            red: None,
+            blue: None,
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3", &[]), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3", &[]), Ok(9.0));
+        assert_eq!(eval("8 / 4 / 2", &[]), Ok(1.0));
+        assert_eq!(eval("2 - -3", &[]), Ok(5.0));
+    }
+
+    #[test]
+    fn units_and_functions() {
+        assert_eq!(eval("4in + 2", &[]), Ok(6.0));
+        assert!(eval("4cm", &[]).is_err());
+        assert_eq!(eval("sqrt(16) + abs(-2)", &[]), Ok(6.0));
+        assert!((eval("sin(90)", &[]).unwrap() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn names() {
+        let vars = [var("approach_offset", 4.0)];
+        assert_eq!(eval("approach_offset * 2", &vars), Ok(8.0));
+        assert!(eval("missing + 1", &vars).is_err());
+        assert!(eval("nope(1)", &vars).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(eval("1 / 0", &[]).is_err());
+        assert!(eval("(1 + 2", &[]).is_err());
+        assert!(eval("1 2", &[]).is_err());
+        assert!(eval("", &[]).is_err());
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |n| format!("{}1{}", "(".repeat(n), ")".repeat(n));
+        assert_eq!(eval(&nested(MAX_DEPTH - 1), &[]), Ok(1.0));
+        assert!(eval(&nested(MAX_DEPTH), &[]).is_err());
+        // Deep enough to overflow the stack without the limit
+        assert!(eval(&"(".repeat(100_000), &[]).is_err());
+        assert!(eval(&"-".repeat(100_000), &[]).is_err());
+    }
+}
@@ -0,0 +1,501 @@
+use crate::hooks::{self, Event};
+
+/// Seconds since the Unix epoch, used to name exported files.
+pub fn timestamp() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+/// Saves a backup file, returning where it was written.
+/// On the web this triggers a download, which the browser puts wherever it keeps downloads;
+/// on native it is written to the backups directory.
+pub fn backup(name: &str, contents: &[u8]) -> Result<String, String> {
+    save("backups", name, contents)
+}
+
+/// Saves a project file, returning where it was written.
+/// Browsers with the File System Access API write it back to the file it was opened from
+/// or first saved to; others download it. Native builds write it to the exports directory.
+pub fn save_project(name: &str, contents: &[u8]) -> Result<String, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let window = web_sys::window().ok_or("No window")?;
+        let handle = HANDLES.with(|handles| {
+            handles
+                .borrow()
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, h)| h.clone())
+        });
+        let contents = contents.to_vec();
+        let name = name.to_string();
+        if let Some(handle) = handle {
+            hooks::emit(Event::Export, || name.clone());
+            let location = name.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(e) = write(&handle, &contents).await {
+                    log::warn!("Could not write to {name}, downloading instead: {e:?}");
+                    let _ = download(&name, &contents);
+                }
+            });
+            return Ok(location);
+        }
+        if has(&window, "showSaveFilePicker") {
+            let extension = name.rsplit_once('.').map_or("", |(_, ext)| ext);
+            let options = picker_options(&format!(".{extension}"));
+            js_sys::Reflect::set(&options, &"suggestedName".into(), &name.as_str().into())
+                .map_err(|e| format!("{e:?}"))?;
+            let promise = call(&window, "showSaveFilePicker", &[options.into()])
+                .map_err(|e| format!("{e:?}"))?;
+            hooks::emit(Event::Export, || name.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                // Cancelling the picker rejects it, and nothing is saved
+                let Ok(handle) = wasm_bindgen_futures::JsFuture::from(promise).await else {
+                    return;
+                };
+                match write(&handle, &contents).await {
+                    // Kept under the project's name, which later saves use
+                    Ok(()) => remember(name, handle),
+                    Err(e) => {
+                        log::warn!("Could not write to {name}, downloading instead: {e:?}");
+                        let _ = download(&name, &contents);
+                    }
+                }
+            });
+            return Ok("the file you choose".into());
+        }
+    }
+    save("exports", name, contents)
+}
+
+/// Saves a file meant to be shared, returning where it was written.
+/// On the web this triggers a download; on native it is written to the exports directory.
+pub fn export(name: &str, contents: &[u8]) -> Result<String, String> {
+    save("exports", name, contents)
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+fn save(folder: &str, name: &str, contents: &[u8]) -> Result<String, String> {
+    hooks::emit(Event::Export, || name.into());
+    #[cfg(target_arch = "wasm32")]
+    {
+        download(name, contents)?;
+        // The browser decides where downloads go, so there's no path to report
+        Ok(format!("your browser's downloads as {name}"))
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let dir = eframe::storage_dir("pathy")
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join(folder);
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(name);
+        std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+        Ok(path.display().to_string())
+    }
+}
+
+/// Reads the contents of a file dropped onto the window.
+/// The web provides the bytes directly, native only the path.
+pub fn dropped(file: &egui::DroppedFile) -> Option<Vec<u8>> {
+    match (&file.bytes, &file.path) {
+        (Some(bytes), _) => Some(bytes.to_vec()),
+        (None, Some(path)) => std::fs::read(path).ok(),
+        _ => None,
+    }
+}
+
+/// The name of a file dropped onto the window, without its directory.
+pub fn dropped_name(file: &egui::DroppedFile) -> String {
+    match &file.path {
+        Some(path) => path
+            .file_name()
+            .map_or(file.name.clone(), |n| n.to_string_lossy().into_owned()),
+        None => file.name.clone(),
+    }
+}
+
+/// How long a download's URL is kept after clicking it, in milliseconds.
+#[cfg(target_arch = "wasm32")]
+const REVOKE_DELAY: i32 = 10_000;
+
+/// Downloads a file through the browser.
+#[cfg(target_arch = "wasm32")]
+pub fn download(name: &str, contents: &[u8]) -> Result<(), String> {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    let window = web_sys::window().ok_or("No window")?;
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(|e| format!("{e:?}"))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|e| format!("{e:?}"))?;
+    let anchor = window
+        .document()
+        .ok_or("No document")?
+        .create_element("a")
+        .map_err(|e| format!("{e:?}"))?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|e| format!("{e:?}"))?;
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+    // Some browsers start the download after the click returns, so the URL has to outlive it
+    let revoke = Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), REVOKE_DELAY)
+        .map_err(|e| format!("{e:?}"))?;
+    Ok(())
+}
+
+/// What a file is being picked for, so each part of the app only gets the files it asked for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pick {
+    /// A project, path to import or settings preset
+    Open,
+    /// Poses recorded on the robot
+    Telemetry,
+}
+
+/// How picking a file ended. Native builds have no picker, so never make one.
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub enum Picked {
+    /// The name and contents of the file
+    File(String, Vec<u8>),
+    /// The user closed the picker without choosing, or the file couldn't be read
+    Cancelled,
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// Files picked, once the browser has read them, by what they were picked for
+    static PICKED: std::cell::RefCell<Vec<(Pick, Picked)>> = Default::default();
+    /// Result of the last download started with [`fetch`], once it finishes
+    static FETCHED: std::cell::RefCell<Option<Result<Vec<u8>, String>>> = Default::default();
+    /// Encoded image pasted into the page, once the browser has read it
+    static PASTED: std::cell::RefCell<Option<Vec<u8>>> = Default::default();
+    /// Files that can be written back to through the File System Access API, by name
+    static HANDLES: std::cell::RefCell<Vec<(String, wasm_bindgen::JsValue)>> = Default::default();
+}
+
+/// Whether the browser window has a method, to detect the File System Access API.
+#[cfg(target_arch = "wasm32")]
+fn has(target: &wasm_bindgen::JsValue, method: &str) -> bool {
+    js_sys::Reflect::get(target, &method.into()).is_ok_and(|f| f.is_function())
+}
+
+/// Calls a method returning a promise on a JavaScript object.
+#[cfg(target_arch = "wasm32")]
+fn call(
+    target: &wasm_bindgen::JsValue,
+    method: &str,
+    args: &[wasm_bindgen::JsValue],
+) -> Result<js_sys::Promise, wasm_bindgen::JsValue> {
+    use wasm_bindgen::JsCast;
+
+    let function: js_sys::Function = js_sys::Reflect::get(target, &method.into())?.dyn_into()?;
+    let args: js_sys::Array = args.iter().collect();
+    js_sys::Reflect::apply(&function, target, &args)?.dyn_into()
+}
+
+/// Options for the File System Access API's pickers, offering files with the `accept` extensions.
+/// The API rejects extensions with anything but letters and digits, so those are only found through "All files".
+#[cfg(target_arch = "wasm32")]
+fn picker_options(accept: &str) -> js_sys::Object {
+    let extensions: js_sys::Array = accept
+        .split(',')
+        .map(str::trim)
+        .filter(|ext| {
+            ext.len() <= 16
+                && ext
+                    .strip_prefix('.')
+                    .is_some_and(|e| !e.is_empty() && e.chars().all(|c| c.is_ascii_alphanumeric()))
+        })
+        .map(wasm_bindgen::JsValue::from)
+        .collect();
+    let options = js_sys::Object::new();
+    if extensions.length() > 0 {
+        let types = js_sys::Object::new();
+        let mime = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&mime, &"application/octet-stream".into(), &extensions);
+        let _ = js_sys::Reflect::set(&types, &"accept".into(), &mime);
+        let _ = js_sys::Reflect::set(&options, &"types".into(), &js_sys::Array::of1(&types));
+    }
+    options
+}
+
+/// Writes the contents to a file handle, replacing what was there.
+#[cfg(target_arch = "wasm32")]
+async fn write(
+    handle: &wasm_bindgen::JsValue,
+    contents: &[u8],
+) -> Result<(), wasm_bindgen::JsValue> {
+    use wasm_bindgen_futures::JsFuture;
+
+    let writable = JsFuture::from(call(handle, "createWritable", &[])?).await?;
+    let data = js_sys::Uint8Array::from(contents);
+    JsFuture::from(call(&writable, "write", &[data.into()])?).await?;
+    JsFuture::from(call(&writable, "close", &[])?).await?;
+    Ok(())
+}
+
+/// Keeps a file handle so saves under its name write back to it.
+#[cfg(target_arch = "wasm32")]
+fn remember(name: String, handle: wasm_bindgen::JsValue) {
+    HANDLES.with(|handles| {
+        let mut handles = handles.borrow_mut();
+        handles.retain(|(n, _)| *n != name);
+        handles.push((name, handle));
+    });
+}
+
+/// Hands a picked file to [`picked`].
+#[cfg(target_arch = "wasm32")]
+fn finish(purpose: Pick, file: Picked) {
+    PICKED.with(|picked| picked.borrow_mut().push((purpose, file)));
+}
+
+/// Reads a file the user picked, handing it to [`picked`].
+#[cfg(target_arch = "wasm32")]
+async fn read(purpose: Pick, file: web_sys::File) {
+    match wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await {
+        Ok(buffer) => finish(
+            purpose,
+            Picked::File(file.name(), js_sys::Uint8Array::new(&buffer).to_vec()),
+        ),
+        Err(e) => {
+            log::warn!("Could not read {}: {e:?}", file.name());
+            finish(purpose, Picked::Cancelled);
+        }
+    }
+}
+
+/// Asks the user for a file with one of the `accept` extensions, like ".pathy".
+/// The browser reads it in the background, so it arrives later through [`picked`] for the same `purpose`,
+/// or as [`Picked::Cancelled`] if the picker is closed.
+#[cfg(target_arch = "wasm32")]
+pub fn pick(accept: &str, purpose: Pick) -> Result<(), String> {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    // Files picked through the File System Access API can be saved back to
+    let window = web_sys::window().ok_or("No window")?;
+    if has(&window, "showOpenFilePicker") {
+        let promise = call(
+            &window,
+            "showOpenFilePicker",
+            &[picker_options(accept).into()],
+        )
+        .map_err(|e| format!("{e:?}"))?;
+        wasm_bindgen_futures::spawn_local(async move {
+            // Cancelling the picker rejects it
+            let Ok(handles) = wasm_bindgen_futures::JsFuture::from(promise).await else {
+                finish(purpose, Picked::Cancelled);
+                return;
+            };
+            let handle = js_sys::Array::from(&handles).get(0);
+            let file = match call(&handle, "getFile", &[]) {
+                Ok(file) => wasm_bindgen_futures::JsFuture::from(file).await,
+                Err(e) => Err(e),
+            };
+            let Some(file) = file.ok().and_then(|f| f.dyn_into::<web_sys::File>().ok()) else {
+                log::warn!("Could not get the picked file");
+                finish(purpose, Picked::Cancelled);
+                return;
+            };
+            remember(file.name(), handle);
+            read(purpose, file).await;
+        });
+        return Ok(());
+    }
+
+    let input = window
+        .document()
+        .ok_or("No document")?
+        .create_element("input")
+        .map_err(|e| format!("{e:?}"))?
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .map_err(|e| format!("{e:?}"))?;
+    input.set_type("file");
+    input.set_accept(accept);
+    let target = input.clone();
+    let onchange = Closure::once(
+        move || match target.files().and_then(|files| files.get(0)) {
+            Some(file) => wasm_bindgen_futures::spawn_local(read(purpose, file)),
+            None => finish(purpose, Picked::Cancelled),
+        },
+    );
+    input.set_onchange(Some(onchange.as_ref().unchecked_ref()));
+    onchange.forget();
+    // Closing the dialog without choosing fires `cancel` instead of `change`
+    let oncancel = Closure::once(move || finish(purpose, Picked::Cancelled));
+    input
+        .add_event_listener_with_callback("cancel", oncancel.as_ref().unchecked_ref())
+        .map_err(|e| format!("{e:?}"))?;
+    oncancel.forget();
+    input.click();
+    Ok(())
+}
+
+/// Asks the user for a file. Native builds have no file dialog, so files are opened by dropping them on the window.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick(_accept: &str, _purpose: Pick) -> Result<(), String> {
+    Err("Drop the file on the window to open it".into())
+}
+
+/// Starts catching images pasted into the page, for [`pasted_image`].
+/// The browser only hands pasted images to a paste listener, and reads them in the background.
+#[cfg(target_arch = "wasm32")]
+pub fn listen_for_paste(ctx: &egui::Context) {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
+    };
+    let ctx = ctx.clone();
+    let onpaste = Closure::<dyn FnMut(web_sys::ClipboardEvent)>::new(
+        move |event: web_sys::ClipboardEvent| {
+            let Some(items) = event.clipboard_data().map(|data| data.items()) else {
+                return;
+            };
+            let file = (0..items.length())
+                .filter_map(|i| items.get(i))
+                .find(|item| item.kind() == "file" && item.type_().starts_with("image/"))
+                .and_then(|item| item.get_as_file().ok().flatten());
+            let Some(file) = file else {
+                return;
+            };
+            let ctx = ctx.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await {
+                    Ok(buffer) => {
+                        PASTED.with(|pasted| {
+                            *pasted.borrow_mut() = Some(js_sys::Uint8Array::new(&buffer).to_vec())
+                        });
+                        ctx.request_repaint();
+                    }
+                    Err(e) => log::warn!("Could not read the pasted image: {e:?}"),
+                }
+            });
+        },
+    );
+    let _ = document.add_event_listener_with_callback("paste", onpaste.as_ref().unchecked_ref());
+    onpaste.forget();
+}
+
+/// Native builds read the clipboard when pasting, so there is nothing to listen for.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn listen_for_paste(_ctx: &egui::Context) {}
+
+/// Takes the encoded image pasted this frame, if any.
+#[cfg(target_arch = "wasm32")]
+pub fn pasted_image(_ctx: &egui::Context) -> Option<Vec<u8>> {
+    PASTED.with(|pasted| pasted.borrow_mut().take())
+}
+
+/// Reads an image from the clipboard if it was pasted this frame, encoded as PNG.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pasted_image(ctx: &egui::Context) -> Option<Vec<u8>> {
+    // Pasting swallows the key press, leaving only its release
+    let pasted = ctx.input(|i| {
+        i.events.iter().any(|e| {
+            matches!(e, egui::Event::Key { key: egui::Key::V, pressed: false, modifiers, .. } if modifiers.command)
+        })
+    });
+    if !pasted {
+        return None;
+    }
+    let copied = arboard::Clipboard::new().ok()?.get_image().ok()?;
+    let rgba = image::RgbaImage::from_raw(
+        copied.width as u32,
+        copied.height as u32,
+        copied.bytes.into_owned(),
+    )?;
+    let mut png = Vec::new();
+    rgba.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .ok()?;
+    Some(png)
+}
+
+/// Downloads a file from a URL in the background. It arrives later through [`fetched`].
+#[cfg(target_arch = "wasm32")]
+pub fn fetch(url: &str, ctx: &egui::Context) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    let promise = web_sys::window().ok_or("No window")?.fetch_with_str(url);
+    let ctx = ctx.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        let result = match JsFuture::from(promise).await {
+            // Browsers hide why a request failed, but it's almost always the server not allowing other sites
+            Err(_) => Err(
+                "The server didn't allow Pathy to load it (CORS), or couldn't be reached. \
+                Download the image and drop it on the field instead"
+                    .to_string(),
+            ),
+            Ok(response) => {
+                let response: web_sys::Response = response.unchecked_into();
+                if response.ok() {
+                    match response.array_buffer() {
+                        Ok(buffer) => JsFuture::from(buffer)
+                            .await
+                            .map(|buffer| js_sys::Uint8Array::new(&buffer).to_vec())
+                            .map_err(|e| format!("{e:?}")),
+                        Err(e) => Err(format!("{e:?}")),
+                    }
+                } else {
+                    Err(format!("Server responded with {}", response.status()))
+                }
+            }
+        };
+        FETCHED.with(|fetched| *fetched.borrow_mut() = Some(result));
+        ctx.request_repaint();
+    });
+    Ok(())
+}
+
+/// Downloads a file from a URL. Native builds can't, since they have no HTTPS client.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn fetch(_url: &str, _ctx: &egui::Context) -> Result<(), String> {
+    Err(
+        "Loading from a URL only works in the browser. Download the file and drop it on the window"
+            .into(),
+    )
+}
+
+/// Takes the result of the download started with [`fetch`], once it finishes.
+pub fn fetched() -> Option<Result<Vec<u8>, String>> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        FETCHED.with(|fetched| fetched.borrow_mut().take())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        None
+    }
+}
+
+/// Takes how the pick started with [`pick`] for `purpose` ended, once it has.
+#[cfg_attr(not(target_arch = "wasm32"), allow(unused_variables))]
+pub fn picked(purpose: Pick) -> Option<Picked> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        PICKED.with(|picked| {
+            let mut picked = picked.borrow_mut();
+            let i = picked.iter().position(|(p, _)| *p == purpose)?;
+            Some(picked.remove(i).1)
+        })
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        None
+    }
+}
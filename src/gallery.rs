@@ -0,0 +1,118 @@
+use std::{cell::RefCell, fmt::Write, rc::Rc};
+
+use crate::bezier::{BezPoint, Point};
+use base64::Engine;
+use egui::{Color32, Pos2};
+
+/// Builds a self-contained, read-only HTML page showing the path over the field,
+/// for posting routines publicly.
+///
+/// # Arguments
+/// * `title` - Page heading.
+/// * `size` - Physical field size, the page's coordinate space.
+/// * `color` - Color the path is drawn in.
+/// * `start` - Outline of the robot where it starts, front corners first.
+/// * `background` - Field image to draw the path over, in any format browsers display.
+/// * `code` - Generated code shown under the field.
+pub fn page(
+    title: &str,
+    size: f32,
+    color: Color32,
+    start: Option<[Pos2; 4]>,
+    points: &[Rc<RefCell<BezPoint>>],
+    background: Option<&[u8]>,
+    code: &str,
+) -> String {
+    let pos = |p: &Rc<RefCell<Point>>| Pos2::from(p.borrow().clone());
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {size} {size}" width="720" height="720">"#
+    );
+    if let Some(bytes) = background {
+        let mime = image::guess_format(bytes)
+            .map(|f| f.to_mime_type())
+            .unwrap_or("image/png");
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+        let _ = write!(
+            svg,
+            r#"<image href="data:{mime};base64,{data}" width="{size}" height="{size}"/>"#
+        );
+    }
+    if let Some(corners) = start {
+        let mut d = String::new();
+        for (i, c) in corners.iter().enumerate() {
+            let _ = write!(d, "{} {} {} ", if i == 0 { "M" } else { "L" }, c.x, c.y);
+        }
+        let _ = write!(
+            svg,
+            r#"<path d="{d}Z" fill="none" stroke="white" stroke-width="{}"/>"#,
+            size / 200.0
+        );
+        // A heavier front edge shows which way the robot faces
+        let _ = write!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="white" stroke-width="{}"/>"#,
+            corners[0].x,
+            corners[0].y,
+            corners[1].x,
+            corners[1].y,
+            size / 80.0
+        );
+    }
+    if let Some(first) = points.first() {
+        // SVG draws cubic Beziers natively, so the curve matches the editor exactly
+        let start = pos(&first.borrow().pos);
+        let mut d = format!("M {} {}", start.x, start.y);
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0].borrow(), pair[1].borrow());
+            let (c1, c2, end) = (pos(&a.cp2), pos(&b.cp1), pos(&b.pos));
+            let _ = write!(
+                d,
+                " C {} {} {} {} {} {}",
+                c1.x, c1.y, c2.x, c2.y, end.x, end.y
+            );
+        }
+        let _ = write!(
+            svg,
+            r#"<path d="{d}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            color.to_hex(),
+            size / 200.0
+        );
+    }
+    for point in points {
+        let point = point.borrow();
+        let p = pos(&point.pos);
+        let _ = write!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="white"/>"#,
+            p.x,
+            p.y,
+            size / 150.0
+        );
+        if !point.name.is_empty() {
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="{}" fill="white">{}</text>"#,
+                p.x + size / 80.0,
+                p.y - size / 80.0,
+                size / 40.0,
+                escape(&point.name)
+            );
+        }
+    }
+    svg += "</svg>";
+    let title = escape(title);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>body {{ background: #0a0a0a; color: #ddd; font-family: sans-serif; margin: 2em; }} \
+         pre {{ background: #1b1b1b; padding: 1em; overflow-x: auto; }}</style>\n</head>\n<body>\n\
+         <h1>{title}</h1>\n{svg}\n<pre><code>{}</code></pre>\n</body>\n</html>\n",
+        escape(code)
+    )
+}
+
+/// Escapes text for use inside HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
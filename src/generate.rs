@@ -1,42 +1,290 @@
-use wasm_bindgen::convert::IntoWasmAbi;
-
-use crate::bezier::BezPoint;
+use crate::action::{self, Action};
+use crate::analysis::{self, HeadingMode};
+use crate::bezier::{BezPoint, EndPose, Pose, Wait};
+use crate::sim::{self, Profile, Robot};
+use crate::transform::normalize;
+use egui::Pos2;
 use std::{cell::RefCell, rc::Rc};
 
-/// Generates path code from a path.
-pub fn generate(path: &[Rc<RefCell<BezPoint>>], step: f32) -> String {
+/// Heading profile samples per segment.
+const PROFILE_STEPS: usize = 10;
+/// Samples per segment when finding its sharpest curve.
+const CURVE_STEPS: usize = 20;
+
+/// How a path is driven, beyond the shape of its points.
+pub struct Driving<'a> {
+    /// Pose odometry is reset to before anything else runs, if set
+    pub start: Option<&'a Pose>,
+    /// What the robot must be doing at the end of the path
+    pub end: &'a EndPose,
+    /// Limits on how the path is driven, for capping speed in curves
+    pub profile: &'a Profile,
+    /// How set headings are interpolated between points
+    pub mode: HeadingMode,
+    /// The robot driving it, for simulating it
+    pub robot: &'a Robot,
+}
+
+/// Generates path code from a path, prefixing generated variable names with `prefix`.
+/// Set headings are interpolated with the driving mode into a heading profile alongside each path,
+/// and actions attached to points are expanded from `library`.
+/// The path starts and finishes as `driving` says, with its speed capped in curves by the profile's lateral limit.
+pub fn generate(
+    path: &[Rc<RefCell<BezPoint>>],
+    step: f32,
+    prefix: &str,
+    driving: &Driving<'_>,
+    library: &[Action],
+) -> String {
+    let Driving {
+        start,
+        end,
+        profile,
+        mode,
+        ..
+    } = *driving;
     if path.len() < 2 {
         return "// Create two points to get started".into();
     }
-    // Group into unbroken paths
+    // Group into paths that each end at rest: at breaks, full stops, waits, actions, sync markers and changes of direction
     let mut paths: Vec<Vec<BezPoint>> = Vec::new();
     paths.push(Vec::new());
-    for i in 0..path.len() {
-        paths.last_mut().unwrap().push(path[i].borrow().clone());
-        if path[i].borrow().broken {
-            paths.push(vec![path[i].borrow().clone()]);
+    for (i, point) in path.iter().enumerate() {
+        let point = point.borrow();
+        paths.last_mut().unwrap().push(point.clone());
+        let inner = i > 0 && i < path.len() - 1;
+        let turns = inner && point.reverse != path[i - 1].borrow().reverse;
+        let pauses = point.stop
+            || point.wait.is_some()
+            || !point.actions.is_empty()
+            || !point.marker.is_empty();
+        if inner && (point.broken() || pauses || turns) {
+            paths.push(vec![point.clone()]);
         }
     }
+    end.apply(paths.last_mut().unwrap());
     let mut result: String = "// Generated by Pathy\n\n".into();
-    // TODO: set initial pose
-    for i in 0..paths.len() {
-        let name = format!("path{}", i);
-        let code = generate_single(&paths[i], step, name);
+    if let Some(Pose { pos, heading }) = start {
+        result.push_str(
+            format!(
+                "wolf.setPose({{{:.3}_in, {:.3}_in}}, {heading:.1}_deg);\n",
+                pos.x, pos.y
+            )
+            .as_str(),
+        );
+    }
+    result.push_str(&arrival(&paths[0][0], library));
+    for (i, sub) in paths.iter().enumerate() {
+        let name = format!("{prefix}path{}", i);
+        let code = generate_single(sub, step, name.clone());
         result.push_str(format!("{code}\n").as_str());
-        let heading = f32::atan2(
-            paths[i].last().unwrap().cp2.borrow().y - paths[i].last().unwrap().pos.borrow().y,
-            paths[i].last().unwrap().cp2.borrow().x - paths[i].last().unwrap().pos.borrow().x,
-        ) * (180.0 / std::f32::consts::PI);
-        result.push_str(format!("wolf.turnToHeading({heading:.1}_deg);\n\n").as_str());
+        // The path's points all share a direction, so its first point's flag covers it
+        let reversed = sub[0].reverse;
+        if reversed {
+            result.push_str(
+                format!("// Driven in reverse, backing along the path\nconst bool {name}_reversed = true;\n")
+                    .as_str(),
+            );
+        }
+        // Every segment but the last starts at one of the path's points
+        let segments = &sub[..sub.len() - 1];
+        if profile.lateral.is_some() {
+            let caps: Vec<String> = segments
+                .iter()
+                .zip(&sub[1..])
+                .map(|(a, b)| {
+                    let cap = (0..=CURVE_STEPS)
+                        .map(|j| analysis::curvature_at(a, b, j as f32 / CURVE_STEPS as f32))
+                        .map(|k| profile.curve_speed(k))
+                        .fold(f32::INFINITY, f32::min);
+                    if cap.is_finite() {
+                        format!("{cap:.1}")
+                    } else {
+                        "NAN".into()
+                    }
+                })
+                .collect();
+            result.push_str(
+                format!(
+                    "// Fastest each segment can be driven in inches per second, keeping sideways acceleration under {:.1} in/s², NAN where free\nstd::vector<double> {name}_curve_speeds = {{{}}};\n",
+                    profile.lateral.unwrap_or_default(),
+                    caps.join(", ")
+                )
+                .as_str(),
+            );
+        }
+        if sub.iter().any(|p| p.speed.is_some()) {
+            let speeds: Vec<String> = sub
+                .iter()
+                .map(|p| p.speed.map_or("NAN".into(), |v| format!("{v:.1}")))
+                .collect();
+            result.push_str(
+                format!(
+                    "// Speed limit at each waypoint in inches per second, NAN where free\nstd::vector<double> {name}_speeds = {{{}}};\n",
+                    speeds.join(", ")
+                )
+                .as_str(),
+            );
+        }
+        let mut profile = Vec::new();
+        if mode != HeadingMode::Tangent && sub.iter().any(|p| p.heading.is_some()) {
+            let headings: Vec<String> = sub
+                .iter()
+                .map(|p| p.heading.map_or("NAN".into(), |h| format!("{h:.1}")))
+                .collect();
+            result.push_str(
+                format!(
+                    "// Target heading at each waypoint in degrees, NAN where free\nstd::vector<double> {name}_headings = {{{}}};\n",
+                    headings.join(", ")
+                )
+                .as_str(),
+            );
+            // Each part is driven from rest to rest, and only the last one finishes at the end pose
+            let rest = EndPose::default();
+            let part = Driving {
+                start: None,
+                end: if i == paths.len() - 1 { end } else { &rest },
+                ..*driving
+            };
+            profile = sim::headings(sub, PROFILE_STEPS, &part);
+            let samples: Vec<String> = profile.iter().map(|h| format!("{h:.1}")).collect();
+            result.push_str(
+                format!(
+                    "// Interpolated by {mode:?}, {PROFILE_STEPS} samples per segment\nstd::vector<double> {name}_heading_profile = {{{}}};\n",
+                    samples.join(", ")
+                )
+                .as_str(),
+            );
+        }
+        let last = i + 1 == paths.len();
+        // Finish facing the end heading, the interpolated heading, or the direction of travel
+        let heading = end
+            .heading
+            .filter(|_| last)
+            .or(profile.last().copied())
+            .unwrap_or_else(|| {
+                let travel = exit_direction(sub, paths.get(i + 1).and_then(|next| next.get(1)));
+                // Backing up leaves the robot facing away from the direction of travel
+                if reversed {
+                    normalize(travel + 180.0)
+                } else {
+                    travel
+                }
+            });
+        // A robot still moving at the end can't stop to turn
+        if !(last && end.moving()) {
+            result.push_str(format!("wolf.turnToHeading({heading:.1}_deg);\n").as_str());
+        }
+        result.push_str(&arrival(sub.last().unwrap(), library));
+        result.push('\n');
+    }
+    if end.heading.is_some() || end.speed.is_some() {
+        result.push_str("// Done once the robot reaches the end facing this many degrees at this many inches per second, NAN where free\n");
+        for (label, value) in [("heading", end.heading), ("speed", end.speed)] {
+            result.push_str(
+                format!(
+                    "const double {prefix}end_{label} = {};\n",
+                    value.map_or("NAN".into(), |v| format!("{v:.1}"))
+                )
+                .as_str(),
+            );
+        }
     }
     result
 }
 
+/// Direction in degrees the robot leaves the end of `sub` in, towards `next` if the path goes on.
+/// A sharp corner has no exit handle, so the robot turns in place to face along the next segment,
+/// or keeps the direction it arrived in at the end of the path.
+fn exit_direction(sub: &[BezPoint], next: Option<&BezPoint>) -> f32 {
+    let last = sub.last().unwrap();
+    let pos = Pos2::from(last.pos.borrow().clone());
+    let mut candidates = vec![last.cp2.borrow().clone().into()];
+    if let Some(next) = next {
+        candidates.push(next.cp1.borrow().clone().into());
+        candidates.push(next.pos.borrow().clone().into());
+    }
+    let leaving = candidates.into_iter().map(|p: Pos2| p - pos);
+    // Arriving from behind, so these point backwards
+    let arriving = std::iter::once(last.cp1.borrow().clone().into())
+        .chain(
+            sub.iter()
+                .rev()
+                .skip(1)
+                .flat_map(|p| [p.cp2.borrow().clone().into(), p.pos.borrow().clone().into()]),
+        )
+        .map(|p: Pos2| pos - p);
+    leaving
+        .chain(arriving)
+        .find(|v| v.length() > 1e-3)
+        .map_or(0.0, |v| v.angle().to_degrees())
+}
+
+/// Code run once the robot reaches a point: its sync marker, its actions, then its wait.
+fn arrival(point: &BezPoint, library: &[Action]) -> String {
+    let mut code = String::new();
+    if !point.marker.is_empty() {
+        code += &action::sync(library, &point.marker);
+    }
+    code.extend(
+        point
+            .actions
+            .iter()
+            .map(|instance| action::expand(library, instance)),
+    );
+    if let Some(wait) = &point.wait {
+        code += &wait_code(wait);
+    }
+    code
+}
+
+/// Code pausing the routine for a wait.
+fn wait_code(wait: &Wait) -> String {
+    match wait {
+        Wait::Time(seconds) => format!("pros::delay({});\n", (seconds * 1000.0).round()),
+        Wait::Until(condition) if condition.trim().is_empty() => {
+            "// TODO: wait until a condition\n".into()
+        }
+        Wait::Until(condition) => format!("while (!({condition})) pros::delay(10);\n"),
+    }
+}
+
 /// Generates path code from a single Bezier path.
+/// Segments that override the sample count are solved with their own step, and their moments
+/// appended to the path's in order.
 pub fn generate_single(path: &[BezPoint], step: f32, name: String) -> String {
     if path.len() < 2 {
         return format!("std::vector<wolflib::Moment> {name} = wolf.solve({{}}, {step});");
     }
+    // Runs of segments sharing a step, as the step and the points the run starts and ends at
+    let mut runs: Vec<(f32, usize, usize)> = Vec::new();
+    for (idx, point) in path[..path.len() - 1].iter().enumerate() {
+        let own = point.steps.map_or(step, |n| 1.0 / n as f32);
+        match runs.last_mut() {
+            Some(run) if run.0 == own => run.2 = idx + 1,
+            _ => runs.push((own, idx, idx + 1)),
+        }
+    }
+    let mut result = String::new();
+    for (n, &(step, first, last)) in runs.iter().enumerate() {
+        if n == 0 {
+            result.push_str(&solve(&path[first..=last], true, step, &name));
+            continue;
+        }
+        let part = format!("{name}_part{n}");
+        result.push_str(
+            format!(
+                "\n{}\n{name}.insert({name}.end(), {part}.begin(), {part}.end());",
+                solve(&path[first..=last], false, step, &part)
+            )
+            .as_str(),
+        );
+    }
+    result
+}
+
+/// Code solving a run of segments into `name` with `step`, labelling the first point if it's the path's `start`.
+fn solve(path: &[BezPoint], start: bool, step: f32, name: &str) -> String {
     let mut result: String = format!("std::vector<wolflib::Moment> {name} = wolf.solve({{\n");
     for idx in 0..path.len() - 1 {
         let p1 = &path[idx];
@@ -55,8 +303,24 @@ pub fn generate_single(path: &[BezPoint], step: f32, name: String) -> String {
             )
             .as_str(),
         );
+        // Label segments with the names of the points they join
+        let mut names = Vec::new();
+        if idx == 0 && start && !p1.name.is_empty() {
+            names.push(format!("from {}", p1.name));
+        }
+        if !p2.name.is_empty() {
+            names.push(format!("to {}", p2.name));
+        }
+        let comment = if names.is_empty() {
+            String::new()
+        } else {
+            format!(" // {}", names.join(", "))
+        };
         if idx < path.len() - 2 {
-            result.push_str(",\n");
+            result.push_str(format!(",{comment}\n").as_str());
+        } else if !comment.is_empty() {
+            // Keep the closing brackets out of the comment
+            result.push_str(format!("{comment}\n").as_str());
         }
     }
     result.push_str(format!("}}, {step});").as_str());
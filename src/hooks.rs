@@ -0,0 +1,85 @@
+//! Callbacks a page embedding Pathy can register to follow editor activity.
+//! Each callback is called with a single string argument.
+
+/// Editor activity a host page can subscribe to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The path changed, with the points as ron
+    PathChanged,
+    /// The generated code changed, with the new code
+    Generate,
+    /// A file was saved or downloaded, with its name
+    Export,
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use super::Event;
+    use std::cell::RefCell;
+    use wasm_bindgen::prelude::*;
+
+    #[derive(Default)]
+    struct Hook {
+        callbacks: Vec<js_sys::Function>,
+        /// Last payload sent, so unchanged state isn't reported again
+        last: Option<String>,
+    }
+
+    thread_local! {
+        static HOOKS: RefCell<[Hook; 3]> = RefCell::default();
+    }
+
+    fn subscribe(event: Event, callback: js_sys::Function) {
+        HOOKS.with(|hooks| hooks.borrow_mut()[event as usize].callbacks.push(callback));
+    }
+
+    /// Calls `callback` with the path as ron whenever it changes.
+    #[wasm_bindgen]
+    pub fn on_path_changed(callback: js_sys::Function) {
+        subscribe(Event::PathChanged, callback);
+    }
+
+    /// Calls `callback` with the generated code whenever it changes.
+    #[wasm_bindgen]
+    pub fn on_generate(callback: js_sys::Function) {
+        subscribe(Event::Generate, callback);
+    }
+
+    /// Calls `callback` with the file name whenever a file is downloaded.
+    #[wasm_bindgen]
+    pub fn on_export(callback: js_sys::Function) {
+        subscribe(Event::Export, callback);
+    }
+
+    pub fn emit(event: Event, payload: impl FnOnce() -> String) {
+        let Some((callbacks, payload)) = HOOKS.with(|hooks| {
+            let mut hooks = hooks.borrow_mut();
+            let hook = &mut hooks[event as usize];
+            if hook.callbacks.is_empty() {
+                return None;
+            }
+            let payload = payload();
+            if event != Event::Export && hook.last.as_ref() == Some(&payload) {
+                return None;
+            }
+            hook.last = Some(payload.clone());
+            Some((hook.callbacks.clone(), payload))
+        }) else {
+            return;
+        };
+        // Called outside the borrow so callbacks can subscribe others
+        for callback in callbacks {
+            if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(&payload)) {
+                log::warn!("Pathy {event:?} hook failed: {e:?}");
+            }
+        }
+    }
+}
+
+/// Tells the host page's callbacks for `event` about it, building the payload only if any are registered.
+#[cfg(target_arch = "wasm32")]
+pub use web::emit;
+
+/// Tells the host page's callbacks for `event` about it. There is no host page outside the browser.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn emit(_event: Event, _payload: impl FnOnce() -> String) {}
@@ -0,0 +1,367 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::bezier::{BezPoint, Pose};
+use crate::sketch;
+use crate::transform::normalize;
+use crate::validate;
+use egui::Pos2;
+use serde_json::Value;
+
+/// Inches in a meter, since Choreo works in meters.
+const INCHES_PER_METER: f32 = 39.3701;
+/// Closest odometry readings are kept to each other in inches, so a resting robot's jitter
+/// isn't fitted as path.
+const LOG_SPACING: f32 = 1.0;
+
+/// Extensions of the files other tools make that can be imported.
+pub const EXTENSIONS: &[&str] = &[".traj", ".chor", ".txt", ".csv", ".log"];
+
+/// A route read from another tool's file, in field inches.
+pub struct Imported {
+    pub name: String,
+    pub route: Route,
+    /// Where the robot started, if the file records it
+    pub start: Option<Pose>,
+}
+
+pub enum Route {
+    /// Closely spaced positions along the route, which curves are fitted to
+    Samples(Vec<Pos2>),
+    /// Positions the path passes through, with the heading to face there in degrees if set
+    Waypoints(Vec<(Pos2, Option<f32>)>),
+}
+
+impl Imported {
+    /// Builds the route's path, fitting samples to within `tolerance` inches.
+    pub fn points(&self, tolerance: f32) -> Vec<Rc<RefCell<BezPoint>>> {
+        match &self.route {
+            Route::Samples(samples) => sketch::fit(samples, tolerance),
+            Route::Waypoints(waypoints) => {
+                // Collapsed handles are opened along the path by validation
+                let points: Vec<_> = waypoints
+                    .iter()
+                    .map(|(pos, heading)| {
+                        let point = BezPoint::new(pos.x, pos.y, pos.x, pos.y, pos.x, pos.y);
+                        point.borrow_mut().heading = *heading;
+                        point
+                    })
+                    .collect();
+                for i in 0..points.len() {
+                    validate::open_handles(&points, i);
+                }
+                points
+            }
+        }
+    }
+}
+
+/// Whether a file can be imported, judging by its name.
+pub fn importable(name: &str) -> bool {
+    EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// Reads the routes from the file called `name`, on a field `size` inches across.
+pub fn read(name: &str, bytes: &[u8], size: f32) -> Result<Vec<Imported>, String> {
+    let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    if name.ends_with(".traj") || name.ends_with(".chor") {
+        choreo(stem, text, size)
+    } else if name.ends_with(".txt") {
+        lemlib(stem, text, size)
+    } else if name.ends_with(".csv") {
+        csv(stem, text)
+    } else if name.ends_with(".log") {
+        odometry(stem, text, size)
+    } else {
+        Err("Pathy can't import this kind of file".into())
+    }
+}
+
+/// Reads the trajectories from a Choreo `.traj` or `.chor` file.
+/// Choreo measures in meters from the bottom left corner, so samples are converted
+/// to inches from the top left.
+fn choreo(stem: &str, text: &str, size: f32) -> Result<Vec<Imported>, String> {
+    let root: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let positions = |samples: &[Value]| -> Vec<Pos2> {
+        samples
+            .iter()
+            .filter_map(|s| {
+                let x = s.get("x")?.as_f64()? as f32;
+                let y = s.get("y")?.as_f64()? as f32;
+                Some(Pos2::new(x * INCHES_PER_METER, size - y * INCHES_PER_METER))
+            })
+            .collect()
+    };
+    let mut routes = Vec::new();
+    if let Some(paths) = root.get("paths").and_then(Value::as_object) {
+        // Older projects hold every path along with its trajectory
+        for (name, path) in paths {
+            if let Some(samples) = path.get("trajectory").and_then(Value::as_array) {
+                routes.push(Imported {
+                    name: name.clone(),
+                    route: Route::Samples(positions(samples)),
+                    start: None,
+                });
+            }
+        }
+    } else {
+        // Trajectory files, newest layout first
+        let samples = root
+            .get("trajectory")
+            .and_then(|t| t.get("samples"))
+            .or_else(|| root.get("traj").and_then(|t| t.get("samples")))
+            .or_else(|| root.get("samples"))
+            .and_then(Value::as_array);
+        if let Some(samples) = samples {
+            routes.push(Imported {
+                name: root
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or(stem)
+                    .into(),
+                route: Route::Samples(positions(samples)),
+                start: None,
+            });
+        }
+    }
+    routes.retain(|imported| matches!(&imported.route, Route::Samples(s) if s.len() >= 2));
+    if routes.is_empty() {
+        return Err(
+            "No generated trajectories found. Newer Choreo projects keep them in .traj files"
+                .into(),
+        );
+    }
+    Ok(routes)
+}
+
+/// Reads a LemLib path asset: one `x, y, speed` line per sample, ending at `endData`.
+/// LemLib measures in inches from the middle of the field with y up.
+fn lemlib(stem: &str, text: &str, size: f32) -> Result<Vec<Imported>, String> {
+    let mut samples = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line == "endData" {
+            break;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let values: Vec<f32> = line
+            .split(',')
+            .map(|v| v.trim().parse::<f32>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Line {} is not x, y, speed", i + 1))?;
+        let [x, y, ..] = values[..] else {
+            return Err(format!("Line {} is not x, y, speed", i + 1));
+        };
+        samples.push(field(x, y, size));
+    }
+    if samples.len() < 2 {
+        return Err("Fewer than two samples".into());
+    }
+    Ok(vec![Imported {
+        name: stem.into(),
+        route: Route::Samples(samples),
+        start: None,
+    }])
+}
+
+/// Converts a position measured like LemLib does, in inches from the middle of the field with y up,
+/// to Pathy's inches from the top left with y down.
+fn field(x: f32, y: f32, size: f32) -> Pos2 {
+    Pos2::new(size / 2.0 + x, size / 2.0 - y)
+}
+
+/// Reads waypoints from a spreadsheet: one `x, y` or `x, y, heading` row per waypoint,
+/// in inches from the top left and degrees, after an optional header row.
+fn csv(stem: &str, text: &str) -> Result<Vec<Imported>, String> {
+    let mut waypoints = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let cells: Vec<&str> = line
+            .split(',')
+            .map(|c| c.trim().trim_matches('"'))
+            .collect();
+        if cells.iter().all(|c| c.is_empty()) {
+            continue;
+        }
+        let number = |j: usize| {
+            cells
+                .get(j)
+                .filter(|c| !c.is_empty())
+                .map(|c| c.parse::<f32>())
+        };
+        match (number(0), number(1), number(2)) {
+            (Some(Ok(x)), Some(Ok(y)), None) => waypoints.push((Pos2::new(x, y), None)),
+            (Some(Ok(x)), Some(Ok(y)), Some(Ok(heading))) => {
+                waypoints.push((Pos2::new(x, y), Some(heading)))
+            }
+            // Column names
+            _ if i == 0 => {}
+            _ => return Err(format!("Row {} is not x, y or x, y, heading", i + 1)),
+        }
+    }
+    if waypoints.len() < 2 {
+        return Err("Fewer than two waypoints".into());
+    }
+    Ok(vec![Imported {
+        name: stem.into(),
+        route: Route::Waypoints(waypoints),
+        start: None,
+    }])
+}
+
+/// Reads recorded robot poses: `time, x, y, theta` lines in inches and degrees, separated by
+/// commas or spaces, in time order. Other lines, like anything else printed to the terminal, are skipped.
+/// Poses are measured the way LemLib's odometry reports them, on a field `size` inches across:
+/// from the middle of the field with y up, and theta clockwise from facing up the field.
+pub fn poses(text: &str, size: f32) -> Vec<(f32, Pose)> {
+    let mut poses: Vec<(f32, Pose)> = text
+        .lines()
+        .filter_map(|line| {
+            let values: Vec<f32> = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse().ok())
+                .collect::<Option<_>>()?;
+            match values[..] {
+                [time, x, y, theta] => Some((
+                    time,
+                    Pose {
+                        pos: field(x, y, size),
+                        // Facing up the field is a heading of -90 degrees in Pathy
+                        heading: normalize(theta - 90.0),
+                    },
+                )),
+                _ => None,
+            }
+        })
+        .collect();
+    // Lines can arrive out of order over a wireless connection
+    poses.sort_by(|a, b| a.0.total_cmp(&b.0));
+    poses
+}
+
+/// Reads a recorded odometry log, see [`poses`].
+fn odometry(stem: &str, text: &str, size: f32) -> Result<Vec<Imported>, String> {
+    let poses = poses(text, size);
+    let Some(&(_, start)) = poses.first() else {
+        return Err("No time, x, y, theta lines".into());
+    };
+    let mut samples = vec![start.pos];
+    for (_, pose) in &poses {
+        if samples.last().unwrap().distance(pose.pos) >= LOG_SPACING {
+            samples.push(pose.pos);
+        }
+    }
+    if samples.len() < 2 {
+        return Err("The robot didn't move".into());
+    }
+    Ok(vec![Imported {
+        name: stem.into(),
+        route: Route::Samples(samples),
+        start: Some(start),
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: f32 = 144.0;
+
+    fn samples(imported: &Imported) -> &[Pos2] {
+        match &imported.route {
+            Route::Samples(samples) => samples,
+            Route::Waypoints(_) => panic!("expected samples"),
+        }
+    }
+
+    fn close(a: Pos2, b: Pos2) -> bool {
+        a.distance(b) < 1e-3
+    }
+
+    #[test]
+    fn lemlib_is_centered_with_y_up() {
+        let text = "0, 0, 50\n24, 36, 50\n-72, -72, 0\nendData\n200\n";
+        let routes = read("skills.txt", text.as_bytes(), SIZE).unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].name, "skills");
+        let samples = samples(&routes[0]);
+        assert_eq!(samples.len(), 3);
+        assert!(close(samples[0], Pos2::new(72.0, 72.0)));
+        assert!(close(samples[1], Pos2::new(96.0, 36.0)));
+        assert!(close(samples[2], Pos2::new(0.0, 144.0)));
+        assert!(read("bad.txt", b"0, 0, 50\nforward\n", SIZE).is_err());
+    }
+
+    #[test]
+    fn odometry_theta_is_clockwise_from_up() {
+        let text = "Starting log\n0.1 0 0 0\n0.0, -12, 0, 90\nnot a pose\n0.2 0 12 180\n";
+        let poses = poses(text, SIZE);
+        // Sorted by time, with the other lines skipped
+        assert_eq!(poses.len(), 3);
+        let times: Vec<f32> = poses.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, [0.0, 0.1, 0.2]);
+        assert!(close(poses[0].1.pos, Pos2::new(60.0, 72.0)));
+        assert_eq!(poses[0].1.heading, 0.0);
+        // Facing up the field
+        assert!(close(poses[1].1.pos, Pos2::new(72.0, 72.0)));
+        assert_eq!(poses[1].1.heading, -90.0);
+        assert!(close(poses[2].1.pos, Pos2::new(72.0, 60.0)));
+        assert_eq!(poses[2].1.heading, 90.0);
+        let routes = read("match.log", text.as_bytes(), SIZE).unwrap();
+        assert_eq!(routes[0].start.map(|p| p.heading), Some(0.0));
+        assert_eq!(samples(&routes[0]).len(), 3);
+        assert!(read("still.log", b"0 0 0 0\n1 0 0.5 0\n", SIZE).is_err());
+    }
+
+    #[test]
+    fn choreo_meters_from_the_bottom_left() {
+        let text = r#"{
+            "name": "Auto",
+            "trajectory": { "samples": [
+                { "t": 0.0, "x": 0.0, "y": 0.0 },
+                { "t": 1.0, "x": 1.0, "y": 2.0 }
+            ] }
+        }"#;
+        let routes = read("auto.traj", text.as_bytes(), SIZE).unwrap();
+        assert_eq!(routes[0].name, "Auto");
+        let first = samples(&routes[0]);
+        assert!(close(first[0], Pos2::new(0.0, SIZE)));
+        assert!(close(
+            first[1],
+            Pos2::new(INCHES_PER_METER, SIZE - 2.0 * INCHES_PER_METER)
+        ));
+        // Older projects keep a trajectory on each path
+        let project = r#"{ "paths": { "Left": { "trajectory": [
+            { "x": 0.5, "y": 0.5 }, { "x": 1.0, "y": 0.5 }
+        ] } } }"#;
+        let routes = read("old.chor", project.as_bytes(), SIZE).unwrap();
+        assert_eq!(routes[0].name, "Left");
+        assert_eq!(samples(&routes[0]).len(), 2);
+        assert!(read("empty.traj", b"{}", SIZE).is_err());
+    }
+
+    #[test]
+    fn csv_skips_the_header_and_rejects_bad_rows() {
+        let text = "\"x\",\"y\",\"heading\"\n10, 20\n30, 40, 90\n\n";
+        let routes = read("plan.csv", text.as_bytes(), SIZE).unwrap();
+        let Route::Waypoints(waypoints) = &routes[0].route else {
+            panic!("expected waypoints");
+        };
+        assert_eq!(
+            waypoints,
+            &[
+                (Pos2::new(10.0, 20.0), None),
+                (Pos2::new(30.0, 40.0), Some(90.0))
+            ]
+        );
+        let bad = read("plan.csv", b"x,y\n10, 20\nten, 40\n", SIZE);
+        assert_eq!(
+            bad.err().as_deref(),
+            Some("Row 3 is not x, y or x, y, heading")
+        );
+        // Only the first row can be column names
+        assert!(read("plan.csv", b"10, 20\nx, y\n30, 40\n", SIZE).is_err());
+    }
+}
@@ -1,6 +1,29 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod action;
+mod analysis;
 mod app;
 mod bezier;
+mod constraint;
+mod expr;
+mod files;
+mod gallery;
 mod generate;
+mod hooks;
+mod import;
+mod migrate;
+mod pattern;
+mod preset;
+mod recovery;
+mod report;
+mod script;
+mod selection;
+mod share;
+mod sim;
+mod sketch;
+mod stats;
+mod sync;
+mod toast;
+mod transform;
+mod validate;
 pub use app::PathyApp;
@@ -0,0 +1,134 @@
+//! Upgrades state saved by older versions of Pathy.
+//!
+//! Fields added with a default don't need a migration. A change that does, like moving or
+//! reinterpreting saved data, bumps [`VERSION`] and adds a step to [`STEPS`].
+
+use crate::app::{NamedPath, PathyApp, CODE_SAMPLES_RANGE};
+use crate::files;
+
+/// Version of the saved state this build writes.
+pub const VERSION: u32 = 1;
+
+/// Upgrades from each version to the next, so `STEPS[v]` takes version `v` to `v + 1`.
+/// Each returns whether it rewrote anything, since state only needs backing up if one did.
+const STEPS: [fn(&mut PathyApp) -> bool; VERSION as usize] = [first_path];
+
+/// Brings saved state up to the current version, then repairs what any version could have saved wrong.
+/// State from a newer version of Pathy is refused, since this build could lose what it doesn't know about.
+///
+/// * `raw` - The state as it was read, backed up if a step rewrites it. `None` when the original
+///   is kept anyway, like a project file being opened.
+///
+/// # Returns
+/// A note on the backup to show the user, if a step rewrote saved state.
+pub fn upgrade(app: &mut PathyApp, raw: Option<&str>) -> Result<Option<String>, String> {
+    let from = app.version;
+    if from > VERSION {
+        return Err(format!(
+            "Saved by a newer version of Pathy (format {from}, this is {VERSION})"
+        ));
+    }
+    let mut rewritten = false;
+    for step in &STEPS[from as usize..] {
+        rewritten |= step(app);
+    }
+    app.version = VERSION;
+    repair(app);
+    let note = raw.filter(|raw| rewritten && !raw.is_empty()).map(|raw| {
+        let name = format!("pathy-format-{from}-{}.ron", files::timestamp());
+        match files::backup(&name, raw.as_bytes()) {
+            Ok(location) => format!("Backed up state from an older version to {location}"),
+            Err(e) => format!("Could not back up state from an older version: {e}"),
+        }
+    });
+    Ok(note)
+}
+
+/// Makes sure there is a path open and every path can be simulated and generated, whatever was saved.
+/// A hand-merged file can have no paths, point past the end of them, or hold out of range limits
+/// and sample counts.
+fn repair(app: &mut PathyApp) {
+    for path in &mut app.paths {
+        path.profile.clamp();
+        // A count of zero divides by zero in generated code
+        for point in &mut path.points {
+            if let Some(steps) = &mut point.steps {
+                *steps = (*steps).clamp(*CODE_SAMPLES_RANGE.start(), *CODE_SAMPLES_RANGE.end());
+            }
+        }
+    }
+    if app.paths.is_empty() {
+        app.paths.push(NamedPath {
+            name: "Path 1".into(),
+            ..Default::default()
+        });
+    }
+    app.current = app.current.min(app.paths.len() - 1);
+}
+
+/// Unversioned state can be from before projects held a list of paths, when the only path was
+/// saved on its own. Its points are still loaded from there, into the first path that the missing
+/// `paths` field defaults to, so nothing needs moving.
+fn first_path(_app: &mut PathyApp) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// App state as Pathy 2.0 saved it, before saves were versioned or held more than one path.
+    const UNVERSIONED: &str = r#"(
+        size: 140.5,
+        scale: 720,
+        uploaded: None,
+        background: Game,
+        steps: 100,
+        generated: "// Generated by Pathy",
+    )"#;
+
+    #[test]
+    fn unversioned_save_upgrades_without_a_backup() {
+        let mut app: PathyApp = ron::from_str(UNVERSIONED).unwrap();
+        assert_eq!(app.version, 0);
+        // Nothing is rewritten, so there's nothing to back up
+        assert_eq!(upgrade(&mut app, Some(UNVERSIONED)), Ok(None));
+        assert_eq!(app.version, VERSION);
+        assert_eq!(app.size, 140.5);
+        assert_eq!(app.paths.len(), 1);
+        assert_eq!(app.current, 0);
+    }
+
+    #[test]
+    fn newer_save_is_refused() {
+        let mut app = PathyApp {
+            version: VERSION + 1,
+            ..Default::default()
+        };
+        assert!(upgrade(&mut app, None).is_err());
+    }
+
+    #[test]
+    fn sample_counts_are_brought_into_range() {
+        let point = |steps| {
+            let mut point: crate::bezier::SavePoint =
+                crate::bezier::BezPoint::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+                    .borrow()
+                    .clone()
+                    .into();
+            point.steps = steps;
+            point
+        };
+        let mut app = PathyApp {
+            version: VERSION,
+            paths: vec![NamedPath {
+                points: vec![point(Some(0)), point(None), point(Some(5000))],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        upgrade(&mut app, None).unwrap();
+        let steps: Vec<_> = app.paths[0].points.iter().map(|p| p.steps).collect();
+        assert_eq!(steps, [Some(2), None, Some(1000)]);
+    }
+}
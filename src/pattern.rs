@@ -0,0 +1,87 @@
+use std::{cell::RefCell, f32::consts::PI, rc::Rc};
+
+use crate::bezier::BezPoint;
+use egui::{vec2, Pos2, Vec2};
+
+/// A standard test path for drivetrain tuning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    Line,
+    SCurve,
+    FigureEight,
+    Spiral,
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 4] = [
+        Pattern::Line,
+        Pattern::SCurve,
+        Pattern::FigureEight,
+        Pattern::Spiral,
+    ];
+
+    /// Builds the pattern, `size` inches across, centered on `center`.
+    pub fn build(self, size: f32, center: Pos2) -> Vec<Rc<RefCell<BezPoint>>> {
+        let s = size;
+        match self {
+            // Each curve returns its position and derivative at t
+            Pattern::Line => sample(center, 1, 0.0, 1.0, |t| {
+                (vec2(s * (t - 0.5), 0.0), vec2(s, 0.0))
+            }),
+            // Lane change of half the size
+            Pattern::SCurve => sample(center, 2, 0.0, 1.0, |t| {
+                (
+                    vec2(s * (t - 0.5), s / 4.0 * (1.0 - (PI * t).cos()) - s / 4.0),
+                    vec2(s, s / 4.0 * PI * (PI * t).sin()),
+                )
+            }),
+            // Lemniscate of Gerono
+            Pattern::FigureEight => sample(center, 8, 0.0, 2.0 * PI, |t| {
+                (
+                    vec2(s / 2.0 * t.sin(), s / 4.0 * (2.0 * t).sin()),
+                    vec2(s / 2.0 * t.cos(), s / 2.0 * (2.0 * t).cos()),
+                )
+            }),
+            // Two turns, shrinking to a quarter of the starting radius
+            Pattern::Spiral => {
+                let turns = 4.0 * PI;
+                let shrink = 0.75 * s / 2.0 / turns;
+                sample(center, 16, 0.0, turns, |t| {
+                    let r = s / 2.0 - shrink * t;
+                    let (sin, cos) = t.sin_cos();
+                    (
+                        vec2(r * cos, r * sin),
+                        vec2(-shrink * cos - r * sin, -shrink * sin + r * cos),
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Approximates a parametric curve with `segments` bezier segments over [t0, t1].
+/// Handles follow the curve's derivative, as in a cubic Hermite spline.
+fn sample(
+    center: Pos2,
+    segments: usize,
+    t0: f32,
+    t1: f32,
+    f: impl Fn(f32) -> (Vec2, Vec2),
+) -> Vec<Rc<RefCell<BezPoint>>> {
+    let dt = (t1 - t0) / segments as f32;
+    (0..=segments)
+        .map(|i| {
+            let (pos, tangent) = f(t0 + dt * i as f32);
+            let pos = center + pos;
+            let handle = tangent * dt / 3.0;
+            BezPoint::new(
+                pos.x,
+                pos.y,
+                pos.x - handle.x,
+                pos.y - handle.y,
+                pos.x + handle.x,
+                pos.y + handle.y,
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,160 @@
+use crate::action::Action;
+use crate::app::{
+    Keybinds, PathyApp, CODE_SAMPLES_RANGE, SCALE_RANGE, SIZE_RANGE, SKETCH_TOLERANCE_RANGE,
+    STEPS_RANGE, TILE_FRACTIONS,
+};
+use crate::bezier::HandleMode;
+use crate::sim::Robot;
+
+/// File extension for exported settings presets.
+pub const EXTENSION: &str = "pathy-preset.ron";
+
+/// Editor, field, robot and code generation settings that can be shared between team members as a file,
+/// with the keybinds and action library, so everyone follows the same conventions. Paths are left out.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Preset {
+    /// Physical field size
+    pub size: f32,
+    /// Screen scale
+    pub scale: u32,
+    /// Generated code density
+    pub steps: usize,
+    pub snap: bool,
+    pub grid: f32,
+    pub tiles: bool,
+    pub tile_size: f32,
+    pub tile_fraction: u32,
+    pub wall_snap: bool,
+    pub wall_reach: f32,
+    pub wall_offset: f32,
+    /// Handle mode given to new points
+    pub handle_mode: HandleMode,
+    /// Keys for the cursor modes and snapping
+    pub keys: Keybinds,
+    /// Samples per segment in generated code
+    pub code_samples: usize,
+    /// Furthest sketched and imported paths may stray from what they were fitted to
+    pub sketch_tolerance: f32,
+    pub show_names: bool,
+    pub show_tangents: bool,
+    pub show_profile: bool,
+    pub show_swept: bool,
+    /// Size and drive limits of the robot
+    pub robot: Robot,
+    /// Code templates attached to points
+    pub actions: Vec<Action>,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Self::capture(&PathyApp::default())
+    }
+}
+
+impl Preset {
+    /// Reads the shareable settings from the app.
+    pub fn capture(app: &PathyApp) -> Self {
+        Self {
+            size: app.size,
+            scale: app.scale,
+            steps: app.steps,
+            snap: app.snap,
+            grid: app.grid,
+            tiles: app.tiles,
+            tile_size: app.tile_size,
+            tile_fraction: app.tile_fraction,
+            wall_snap: app.wall_snap,
+            wall_reach: app.wall_reach,
+            wall_offset: app.wall_offset,
+            handle_mode: app.handle_mode,
+            keys: app.keys,
+            code_samples: app.code_samples,
+            sketch_tolerance: app.sketch_tolerance,
+            show_names: app.show_names,
+            show_tangents: app.show_tangents,
+            show_profile: app.show_profile,
+            show_swept: app.show_swept,
+            robot: app.robot,
+            actions: app.actions.clone(),
+        }
+    }
+    /// Brings values read from a file into the ranges the editor allows, so a bad value can't
+    /// break drawing or the simulation. Values that aren't numbers go back to their defaults.
+    pub fn clamp(&mut self) {
+        let default = PathyApp::default();
+        let within = |value: f32, default: f32, min: f32, max: f32| {
+            if value.is_nan() {
+                default
+            } else {
+                value.clamp(min, max)
+            }
+        };
+        self.size = within(
+            self.size,
+            default.size,
+            *SIZE_RANGE.start(),
+            *SIZE_RANGE.end(),
+        );
+        self.scale = self.scale.clamp(*SCALE_RANGE.start(), *SCALE_RANGE.end());
+        self.steps = self.steps.clamp(*STEPS_RANGE.start(), *STEPS_RANGE.end());
+        self.code_samples = self
+            .code_samples
+            .clamp(*CODE_SAMPLES_RANGE.start(), *CODE_SAMPLES_RANGE.end());
+        self.grid = within(self.grid, default.grid, 0.25, self.size);
+        self.tile_size = within(self.tile_size, default.tile_size, 1.0, self.size);
+        if !TILE_FRACTIONS.contains(&self.tile_fraction) {
+            self.tile_fraction = default.tile_fraction;
+        }
+        self.wall_reach = within(self.wall_reach, default.wall_reach, 0.0, self.size / 2.0);
+        self.wall_offset = within(self.wall_offset, default.wall_offset, 0.0, self.size / 2.0);
+        self.sketch_tolerance = within(
+            self.sketch_tolerance,
+            default.sketch_tolerance,
+            *SKETCH_TOLERANCE_RANGE.start(),
+            *SKETCH_TOLERANCE_RANGE.end(),
+        );
+        self.robot.clamp();
+    }
+    /// Applies the settings to the app, clamped to the ranges the editor allows.
+    /// The field size is left alone once a path exists, like in the top bar.
+    /// Actions replace library actions of the same name, and the rest are added.
+    ///
+    /// # Returns
+    /// Whether every setting was applied.
+    pub fn apply(&self, app: &mut PathyApp) -> bool {
+        let sized = app.points.is_empty() || app.size == self.size;
+        let mut preset = self.clone();
+        if !app.points.is_empty() {
+            preset.size = app.size;
+        }
+        preset.clamp();
+        app.size = preset.size;
+        app.scale = preset.scale;
+        app.steps = preset.steps;
+        app.snap = preset.snap;
+        app.grid = preset.grid;
+        app.tiles = preset.tiles;
+        app.tile_size = preset.tile_size;
+        app.tile_fraction = preset.tile_fraction;
+        app.wall_snap = preset.wall_snap;
+        app.wall_reach = preset.wall_reach;
+        app.wall_offset = preset.wall_offset;
+        app.handle_mode = preset.handle_mode;
+        app.keys = preset.keys;
+        app.code_samples = preset.code_samples;
+        app.sketch_tolerance = preset.sketch_tolerance;
+        app.show_names = preset.show_names;
+        app.show_tangents = preset.show_tangents;
+        app.show_profile = preset.show_profile;
+        app.show_swept = preset.show_swept;
+        app.robot = preset.robot;
+        for action in preset.actions {
+            match app.actions.iter_mut().find(|a| a.name == action.name) {
+                Some(existing) => *existing = action,
+                None => app.actions.push(action),
+            }
+        }
+        sized
+    }
+}
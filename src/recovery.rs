@@ -0,0 +1,96 @@
+use crate::bezier::SavePoint;
+
+/// Persisted state that could not be loaded, kept as-is so it isn't lost.
+pub struct Corrupt {
+    /// Storage key it was saved under
+    pub key: String,
+    pub raw: String,
+    /// Why it could not be used
+    pub error: String,
+}
+
+/// Reads a stored value, or `None` if nothing is stored.
+/// Unlike `eframe::get_value`, a value that fails to parse is returned as an error with its raw text.
+pub fn load<T: serde::de::DeserializeOwned>(
+    storage: &dyn eframe::Storage,
+    key: &str,
+) -> Result<Option<T>, Corrupt> {
+    let Some(raw) = storage.get_string(key) else {
+        return Ok(None);
+    };
+    ron::from_str(&raw).map(Some).map_err(|e| Corrupt {
+        key: key.into(),
+        error: e.to_string(),
+        raw,
+    })
+}
+
+/// The points that still parse from a corrupt saved path, such as one cut off part way through.
+pub fn salvage(raw: &str) -> Vec<SavePoint> {
+    let inner = raw.trim().trim_start_matches('[').trim_end_matches(']');
+    // Split on commas between points, skipping those inside points or strings
+    let mut items = Vec::new();
+    let (mut depth, mut quoted, mut escaped, mut start) = (0, false, false, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if quoted => {}
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&inner[start..]);
+    items
+        .into_iter()
+        .filter_map(|item| ron::from_str(item).ok())
+        .collect()
+}
+
+/// Number of autosaves kept, the oldest being overwritten first.
+pub const AUTOSAVE_SLOTS: usize = 10;
+
+/// A copy of the project taken in the background.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// When it was taken, in seconds since the Unix epoch
+    pub time: u64,
+    pub project: String,
+}
+
+fn slot_key(slot: usize) -> String {
+    format!("autosave-{slot}")
+}
+
+/// Every readable autosave, newest first.
+pub fn autosaves(storage: &dyn eframe::Storage) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = (0..AUTOSAVE_SLOTS)
+        .filter_map(|slot| storage.get_string(&slot_key(slot)))
+        .filter_map(|raw| ron::from_str(&raw).ok())
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.time));
+    snapshots
+}
+
+/// Writes a snapshot to an empty autosave slot, or over the oldest one.
+pub fn autosave(storage: &mut dyn eframe::Storage, snapshot: &Snapshot) {
+    let age = |slot: usize| {
+        storage
+            .get_string(&slot_key(slot))
+            .and_then(|raw| ron::from_str::<Snapshot>(&raw).ok())
+            .map_or(0, |s| s.time)
+    };
+    let slot = (0..AUTOSAVE_SLOTS)
+        .min_by_key(|&slot| age(slot))
+        .unwrap_or(0);
+    if let Ok(text) = ron::to_string(snapshot) {
+        storage.set_string(&slot_key(slot), text);
+        storage.flush();
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::action::{Instance, SYNC};
+use crate::bezier::Wait;
+use crate::preset::Preset;
+use crate::sync::Project;
+
+/// How many recent edits are remembered for issue reports.
+const JOURNAL: usize = 100;
+
+/// Recent edits to the project, oldest first, so a reported problem can be retraced.
+/// Entries name paths and points by position, never by what the user called them.
+#[derive(Default)]
+pub struct Journal {
+    edits: VecDeque<String>,
+}
+
+impl Journal {
+    /// Remembers an edit, forgetting the oldest once full.
+    pub fn record(&mut self, edit: impl Into<String>) {
+        if self.edits.len() == JOURNAL {
+            self.edits.pop_front();
+        }
+        self.edits.push_back(edit.into());
+    }
+    /// Recent edits, oldest first.
+    pub fn edits(&self) -> impl Iterator<Item = &String> {
+        self.edits.iter()
+    }
+}
+
+/// Everything needed to reproduce a reported problem.
+/// Leaves out the project name and sync settings, which may identify the team or hold credentials,
+/// and replaces text typed into the project with placeholders.
+/// The settings' action library is left out too, since the project carries it anonymized.
+#[derive(serde::Serialize)]
+pub struct Report {
+    pub version: &'static str,
+    pub platform: String,
+    /// What the user says went wrong
+    pub description: String,
+    pub settings: Preset,
+    pub project: Project,
+    /// Recent edits, oldest first
+    pub edits: Vec<String>,
+}
+
+impl Report {
+    pub fn new(
+        description: String,
+        mut settings: Preset,
+        project: Project,
+        edits: Vec<String>,
+    ) -> Self {
+        settings.actions.clear();
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            platform: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+            description,
+            settings,
+            project: anonymize(project),
+            edits,
+        }
+    }
+    /// The report as a file to attach to an issue.
+    pub fn bundle(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, Default::default()).map_err(|e| e.to_string())
+    }
+}
+
+/// Gives each distinct text the same numbered placeholder wherever it appears,
+/// so names that link things, like sync markers and actions, still match up.
+struct Placeholders {
+    kind: &'static str,
+    seen: HashMap<String, String>,
+}
+
+impl Placeholders {
+    fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            seen: HashMap::new(),
+        }
+    }
+    fn get(&mut self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        let next = self.seen.len() + 1;
+        self.seen
+            .entry(text.into())
+            .or_insert_with(|| format!("{} {next}", self.kind))
+            .clone()
+    }
+}
+
+/// Replaces the text typed into a project, like names, notes and robot code, with placeholders.
+/// Positions, settings and variables are kept, since they're what reproduces a problem.
+pub fn anonymize(mut project: Project) -> Project {
+    let mut tags = Placeholders::new("tag");
+    let mut markers = Placeholders::new("marker");
+    let mut actions = Placeholders::new("action");
+    let mut action = |name: &str| {
+        // The sync action is looked up by name, so it keeps it
+        if name == SYNC {
+            name.into()
+        } else {
+            actions.get(name)
+        }
+    };
+    for (i, path) in project.paths.iter_mut().enumerate() {
+        path.name = format!("Path {}", i + 1);
+        if !path.notes.is_empty() {
+            path.notes = "notes".into();
+        }
+        for tag in &mut path.tags {
+            *tag = tags.get(tag);
+        }
+        for (j, point) in path.points.iter_mut().enumerate() {
+            if !point.name.is_empty() {
+                point.name = format!("point {}", j + 1);
+            }
+            point.marker = markers.get(&point.marker);
+            if let Some(Wait::Until(code)) = &mut point.wait {
+                *code = "condition".into();
+            }
+            for Instance { action: name, args } in &mut point.actions {
+                *name = action(name);
+                for (k, arg) in args.iter_mut().enumerate() {
+                    *arg = format!("arg{}", k + 1);
+                }
+            }
+        }
+    }
+    for library in &mut project.actions {
+        library.name = action(&library.name);
+        for (k, param) in library.params.iter_mut().enumerate() {
+            *param = format!("p{}", k + 1);
+        }
+        let args: Vec<String> = library.params.iter().map(|p| format!("{{{p}}}")).collect();
+        library.template = format!("action({});", args.join(", "));
+    }
+    project
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+
+    #[test]
+    fn bundle_leaves_out_robot_code() {
+        let library = vec![Action {
+            name: "secret_intake".into(),
+            params: vec!["power".into()],
+            template: "claw.spin(power * 42);".into(),
+        }];
+        let settings = Preset {
+            actions: library.clone(),
+            ..Default::default()
+        };
+        let project = Project::new(Vec::new(), 0, Vec::new(), library);
+        let bundle = Report::new(String::new(), settings, project, Vec::new())
+            .bundle()
+            .unwrap();
+        for text in ["secret_intake", "power", "claw.spin"] {
+            assert!(!bundle.contains(text), "{text} is in the report");
+        }
+    }
+}
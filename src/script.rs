@@ -0,0 +1,278 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::bezier::BezPoint;
+use crate::expr::{self, Variable};
+
+/// Most points a script may create, so runaway loops don't hang the app.
+const MAX_POINTS: usize = 10_000;
+/// Most statements and loop passes a script may run, so loops that create nothing can't hang the app either.
+const MAX_STEPS: usize = 1_000_000;
+
+/// A parsed script statement. Expressions are kept as source and evaluated on execution.
+#[derive(Debug)]
+enum Stmt {
+    /// `let name = expr`
+    Let(String, String),
+    /// `for name in start..end { body }`
+    For(String, String, String, Vec<Stmt>),
+    /// `path.call(args).call(args)...`
+    Path(Vec<(String, Vec<String>)>),
+}
+
+/// Runs a path script, returning the points it builds.
+///
+/// Scripts are made of statements separated by newlines or `;`:
+/// * `let r = 24` defines a local variable.
+/// * `for i in 0..8 { ... }` repeats a block, with `i` counting up from the start (exclusive end).
+/// * `path.add(x, y)` appends a point, `path.heading(deg)` points the last point's handles,
+///   and `path.clear()` removes every point. Calls may be chained.
+///
+/// Any expression may use project variables and the functions supported by [`expr::eval`].
+pub fn run(src: &str, vars: &[Variable]) -> Result<Vec<Rc<RefCell<BezPoint>>>, String> {
+    let mut parser = Parser { src, pos: 0 };
+    let stmts = parser.block()?;
+    if parser.pos < src.len() {
+        return Err(parser.error("Unexpected '}'"));
+    }
+    let mut vars = vars.to_vec();
+    let mut points = Vec::new();
+    let mut steps = 0;
+    execute(&stmts, &mut vars, &mut points, &mut steps)?;
+    Ok(points)
+}
+
+/// Runs statements, counting each one and each loop pass in `steps`.
+fn execute(
+    stmts: &[Stmt],
+    vars: &mut Vec<Variable>,
+    points: &mut Vec<Rc<RefCell<BezPoint>>>,
+    steps: &mut usize,
+) -> Result<(), String> {
+    for stmt in stmts {
+        step(steps)?;
+        match stmt {
+            Stmt::Let(name, value) => {
+                let value = expr::eval(value, vars)?;
+                set(vars, name, value);
+            }
+            Stmt::For(name, start, end, body) => {
+                let start = expr::eval(start, vars)?.round() as i64;
+                let end = expr::eval(end, vars)?.round() as i64;
+                for i in start..end {
+                    step(steps)?;
+                    set(vars, name, i as f32);
+                    execute(body, vars, points, steps)?;
+                }
+            }
+            Stmt::Path(calls) => {
+                for (name, args) in calls {
+                    let args = args
+                        .iter()
+                        .map(|arg| expr::eval(arg, vars))
+                        .collect::<Result<Vec<f32>, String>>()?;
+                    call(name, &args, points)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Counts a step of the script, failing once it has run too long.
+fn step(steps: &mut usize) -> Result<(), String> {
+    *steps += 1;
+    if *steps > MAX_STEPS {
+        return Err(format!("Scripts may run at most {MAX_STEPS} steps"));
+    }
+    Ok(())
+}
+
+/// Runs a single `path.` method.
+fn call(name: &str, args: &[f32], points: &mut Vec<Rc<RefCell<BezPoint>>>) -> Result<(), String> {
+    match (name, args) {
+        ("add", &[x, y]) => {
+            if points.len() >= MAX_POINTS {
+                return Err(format!("Scripts may create at most {MAX_POINTS} points"));
+            }
+            let point = match points.last() {
+                Some(last) => last.borrow().after(x, y),
+                None => BezPoint::new(x, y, x - 10.0, y, x + 10.0, y),
+            };
+            points.push(point);
+        }
+        ("heading", &[degrees]) => {
+            let last = points.last().ok_or("heading() needs a point first")?;
+            let last = last.borrow();
+            let pos = last.pos.borrow().clone();
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            for (handle, sign) in [(&last.cp1, -1.0), (&last.cp2, 1.0)] {
+                let mut handle = handle.borrow_mut();
+                let len = ((handle.x - pos.x).powi(2) + (handle.y - pos.y).powi(2)).sqrt();
+                handle.x = pos.x + sign * len * cos;
+                handle.y = pos.y + sign * len * sin;
+            }
+        }
+        ("clear", &[]) => points.clear(),
+        ("add", _) => return Err("add() takes x and y".into()),
+        ("heading", _) => return Err("heading() takes an angle".into()),
+        ("clear", _) => return Err("clear() takes no arguments".into()),
+        _ => return Err(format!("Unknown method path.{name}()")),
+    }
+    Ok(())
+}
+
+/// Sets a script variable, shadowing any existing one.
+fn set(vars: &mut Vec<Variable>, name: &str, value: f32) {
+    match vars.iter_mut().find(|v| v.name == name) {
+        Some(var) => {
+            var.value = value;
+            var.red = None;
+            var.blue = None;
+        }
+        None => vars.push(Variable {
+            name: name.to_string(),
+            value,
+            red: None,
+            blue: None,
+        }),
+    }
+}
+
+/// Splits a script into statements.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+    fn error(&self, message: &str) -> String {
+        let line = self.src[..self.pos].matches('\n').count() + 1;
+        format!("Line {line}: {message}")
+    }
+    /// Skips whitespace, statement separators and `//` comments.
+    fn skip(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ';');
+            self.pos += rest.len() - trimmed.len();
+            if self.rest().starts_with("//") {
+                self.pos += self.rest().find('\n').unwrap_or(self.rest().len());
+            } else {
+                return;
+            }
+        }
+    }
+    /// Skips spaces without leaving the current line.
+    fn skip_inline(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    }
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        self.skip_inline();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{token}'")))
+        }
+    }
+    fn ident(&mut self) -> Result<String, String> {
+        self.skip_inline();
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.error("Expected a name"));
+        }
+        let ident = self.rest()[..len].to_string();
+        self.pos += len;
+        Ok(ident)
+    }
+    /// Reads source up to (not including) the first of `ends` outside parentheses.
+    fn until(&mut self, ends: &[&str]) -> String {
+        let start = self.pos;
+        let mut depth = 0;
+        while self.pos < self.src.len() {
+            let rest = self.rest();
+            if depth == 0 && ends.iter().any(|end| rest.starts_with(end)) {
+                break;
+            }
+            let c = rest.chars().next().unwrap();
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            self.pos += c.len_utf8();
+        }
+        self.src[start..self.pos].trim().to_string()
+    }
+    /// Parses statements until a closing brace or the end of the script.
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        let mut stmts = Vec::new();
+        loop {
+            self.skip();
+            if self.rest().is_empty() || self.rest().starts_with('}') {
+                return Ok(stmts);
+            }
+            stmts.push(self.stmt()?);
+        }
+    }
+    fn stmt(&mut self) -> Result<Stmt, String> {
+        match self.ident()?.as_str() {
+            "let" => {
+                let name = self.ident()?;
+                self.expect("=")?;
+                Ok(Stmt::Let(name, self.until(&["\n", ";", "}"])))
+            }
+            "for" => {
+                let name = self.ident()?;
+                if self.ident()? != "in" {
+                    return Err(self.error("Expected 'in'"));
+                }
+                let start = self.until(&[".."]);
+                self.expect("..")?;
+                let end = self.until(&["{"]);
+                self.expect("{")?;
+                let body = self.block()?;
+                self.expect("}")?;
+                Ok(Stmt::For(name, start, end, body))
+            }
+            "path" => {
+                let mut calls = Vec::new();
+                loop {
+                    self.skip_inline();
+                    if !self.rest().starts_with('.') {
+                        break;
+                    }
+                    self.pos += 1;
+                    let name = self.ident()?;
+                    self.expect("(")?;
+                    let mut args = Vec::new();
+                    loop {
+                        let arg = self.until(&[",", ")"]);
+                        if !arg.is_empty() {
+                            args.push(arg);
+                        }
+                        if self.rest().starts_with(',') {
+                            self.pos += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    self.expect(")")?;
+                    calls.push((name, args));
+                }
+                if calls.is_empty() {
+                    return Err(self.error("Expected a path method, e.g. path.add(x, y)"));
+                }
+                Ok(Stmt::Path(calls))
+            }
+            other => Err(self.error(&format!("Unknown statement '{other}'"))),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::analysis;
+use crate::bezier::BezPoint;
+use crate::transform::Axis;
+use egui::Pos2;
+use uuid::Uuid;
+
+/// Which part of the selected points an edit applies to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    Anchors,
+    EntryHandles,
+    ExitHandles,
+    AllHandles,
+}
+
+impl Filter {
+    pub const ALL: [Filter; 4] = [
+        Filter::Anchors,
+        Filter::EntryHandles,
+        Filter::ExitHandles,
+        Filter::AllHandles,
+    ];
+
+    /// Whether the filter affects the entry (cp1) handle.
+    pub fn entry(self) -> bool {
+        matches!(self, Filter::EntryHandles | Filter::AllHandles)
+    }
+    /// Whether the filter affects the exit (cp2) handle.
+    pub fn exit(self) -> bool {
+        matches!(self, Filter::ExitHandles | Filter::AllHandles)
+    }
+}
+
+/// The selected points, in path order.
+pub fn selected<'a>(
+    points: &'a [Rc<RefCell<BezPoint>>],
+    selection: &'a [Uuid],
+) -> impl Iterator<Item = &'a Rc<RefCell<BezPoint>>> {
+    points.iter().filter(|p| selection.contains(&p.borrow().id))
+}
+
+/// The selected points that can be moved, leaving out pinned ones, in path order.
+pub fn movable<'a>(
+    points: &'a [Rc<RefCell<BezPoint>>],
+    selection: &'a [Uuid],
+) -> impl Iterator<Item = &'a Rc<RefCell<BezPoint>>> {
+    selected(points, selection).filter(|p| !p.borrow().pinned)
+}
+
+/// Moves the filtered part of every selected point by `dx`, `dy`. Pinned points stay put.
+pub fn nudge(
+    points: &[Rc<RefCell<BezPoint>>],
+    selection: &[Uuid],
+    filter: Filter,
+    dx: f32,
+    dy: f32,
+) {
+    for point in movable(points, selection) {
+        let mut point = point.borrow_mut();
+        if filter == Filter::Anchors {
+            point.translate(dx, dy);
+            continue;
+        }
+        // Flag a single moved handle as edited so the other one follows its mode
+        for (handle, moved) in [(&point.cp1, filter.entry()), (&point.cp2, filter.exit())] {
+            if moved {
+                let mut handle = handle.borrow_mut();
+                handle.offset(dx, dy);
+                handle.editing = filter != Filter::AllHandles;
+            }
+        }
+    }
+}
+
+/// Scales the filtered handles of every selected point about their anchor. Pinned points stay put.
+pub fn scale_handles(
+    points: &[Rc<RefCell<BezPoint>>],
+    selection: &[Uuid],
+    filter: Filter,
+    factor: f32,
+) {
+    for point in movable(points, selection) {
+        let point = point.borrow();
+        let pos = point.pos.borrow().clone();
+        for (handle, scaled) in [(&point.cp1, filter.entry()), (&point.cp2, filter.exit())] {
+            if scaled {
+                let mut handle = handle.borrow_mut();
+                handle.x = pos.x + (handle.x - pos.x) * factor;
+                handle.y = pos.y + (handle.y - pos.y) * factor;
+                handle.editing = filter != Filter::AllHandles;
+            }
+        }
+    }
+}
+
+/// Lines the selected anchors up with the first selected one, moving their handles along.
+/// `Horizontal` gives every point the same y, `Vertical` the same x. Pinned points stay put.
+pub fn align(points: &[Rc<RefCell<BezPoint>>], selection: &[Uuid], axis: Axis) {
+    let Some(first) = selected(points, selection).next() else {
+        return;
+    };
+    let target = Pos2::from(first.borrow().pos.borrow().clone());
+    for point in selected(points, selection).skip(1) {
+        let mut point = point.borrow_mut();
+        if point.pinned {
+            continue;
+        }
+        let pos = Pos2::from(point.pos.borrow().clone());
+        match axis {
+            Axis::Horizontal => point.translate(0.0, target.y - pos.y),
+            Axis::Vertical => point.translate(target.x - pos.x, 0.0),
+        }
+    }
+}
+
+/// Spaces the selected anchors at equal intervals between the first and last selected one,
+/// either along the straight line between them or along the current curve. Pinned points stay put.
+pub fn distribute(points: &[Rc<RefCell<BezPoint>>], selection: &[Uuid], along_curve: bool) {
+    let indices: Vec<usize> = (0..points.len())
+        .filter(|&i| selection.contains(&points[i].borrow().id))
+        .collect();
+    if indices.len() < 3 {
+        return;
+    }
+    let (first, last) = (indices[0], indices[indices.len() - 1]);
+    let pos = |i: usize| Pos2::from(points[i].borrow().pos.borrow().clone());
+    let (start, end) = (pos(first), pos(last));
+    // Sample the curve before anything moves, with cumulative lengths
+    let samples = analysis::sample(&points[first..=last], 50);
+    let mut lengths = vec![0.0];
+    for pair in samples.windows(2) {
+        lengths.push(lengths.last().unwrap() + pair[0].distance(pair[1]));
+    }
+    let total = *lengths.last().unwrap();
+    let spans = (indices.len() - 1) as f32;
+    for (k, &i) in indices.iter().enumerate().skip(1).take(indices.len() - 2) {
+        if points[i].borrow().pinned {
+            continue;
+        }
+        let t = k as f32 / spans;
+        let target = if along_curve {
+            let length = t * total;
+            let j = lengths
+                .partition_point(|l| *l < length)
+                .clamp(1, samples.len() - 1);
+            let span = lengths[j] - lengths[j - 1];
+            let f = if span > 0.0 {
+                (length - lengths[j - 1]) / span
+            } else {
+                0.0
+            };
+            samples[j - 1].lerp(samples[j], f)
+        } else {
+            start.lerp(end, t)
+        };
+        let offset = target - pos(i);
+        points[i].borrow_mut().translate(offset.x, offset.y);
+    }
+}
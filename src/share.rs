@@ -0,0 +1,54 @@
+use base64::Engine;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Start of the URL fragment holding a shared path.
+pub const FRAGMENT: &str = "path=";
+/// Where Pathy is hosted, for links made outside the browser.
+const HOSTED: &str = "https://750w.github.io/pathy/";
+/// Largest unpacked link accepted, so a bad link can't exhaust memory.
+const MAX_SIZE: usize = 1 << 20;
+
+/// Packs a value into text that fits in a URL: ron, deflated, then URL-safe base64.
+pub fn pack<T: Serialize>(value: &T) -> Result<String, String> {
+    let text = ron::to_string(value).map_err(|e| e.to_string())?;
+    let compressed = miniz_oxide::deflate::compress_to_vec(text.as_bytes(), 9);
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed))
+}
+
+/// Reverses [`pack`].
+pub fn unpack<T: DeserializeOwned>(packed: &str) -> Result<T, String> {
+    let compressed = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(packed.trim())
+        .map_err(|e| e.to_string())?;
+    let bytes = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_SIZE)
+        .map_err(|e| e.to_string())?;
+    let text = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+    ron::from_str(&text).map_err(|e| e.to_string())
+}
+
+/// A link to this page that opens with the packed value.
+pub fn link(packed: &str) -> String {
+    #[cfg(target_arch = "wasm32")]
+    let page = web_sys::window()
+        .and_then(|w| w.location().href().ok())
+        .map(|href| href.split('#').next().unwrap_or_default().to_string())
+        .unwrap_or_else(|| HOSTED.into());
+    #[cfg(not(target_arch = "wasm32"))]
+    let page = HOSTED.to_string();
+    format!("{page}#{FRAGMENT}{packed}")
+}
+
+/// Takes the packed value out of the link the page was opened with, if there is one.
+/// The fragment is cleared so reloading doesn't open it again.
+pub fn opened() -> Option<String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let location = web_sys::window()?.location();
+        let hash = location.hash().ok()?;
+        let packed = hash.strip_prefix('#')?.strip_prefix(FRAGMENT)?.to_string();
+        let _ = location.set_hash("");
+        Some(packed)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    None
+}
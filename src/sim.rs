@@ -0,0 +1,970 @@
+//! Timing routes as if driven, so two robots sharing the field can be played back together.
+
+use crate::analysis::{self, HeadingMode};
+use crate::bezier::{interpolate, BezPoint, HandleMode, Pose, Wait, ROBOT_SIZE};
+use crate::generate::Driving;
+use crate::transform::normalize;
+use egui::Pos2;
+
+/// Speed robots drive at by default where the path sets no limit, in inches per second.
+pub const CRUISE_SPEED: f32 = 40.0;
+/// How quickly robots speed up and slow down by default, in inches per second squared.
+pub const ACCELERATION: f32 = 60.0;
+/// How quickly robots turn in place by default, in degrees per second.
+pub const TURN_SPEED: f32 = 180.0;
+/// Range of robot lengths and widths allowed, in inches.
+pub const SIZE_RANGE: std::ops::RangeInclusive<f32> = 1.0..=48.0;
+/// Range of cruise speeds allowed, in inches per second.
+pub const CRUISE_RANGE: std::ops::RangeInclusive<f32> = 1.0..=200.0;
+/// Range of accelerations allowed, in inches per second squared.
+pub const ACCELERATION_RANGE: std::ops::RangeInclusive<f32> = 1.0..=1000.0;
+/// Range of turn speeds allowed, in degrees per second.
+pub const TURN_RANGE: std::ops::RangeInclusive<f32> = 1.0..=1440.0;
+/// Furthest the start pose can be from the first point before the robot drives over to it, in inches.
+const START_TOLERANCE: f32 = 0.5;
+/// Samples per segment when timing a route.
+const STEPS: usize = 20;
+/// Seconds between frames of a route driven with a jerk limit.
+const FRAME_TIME: f32 = 0.02;
+/// Most frames one stretch of a route driven with a jerk limit is split into.
+const MAX_FRAMES: usize = 100_000;
+/// Range of jerk limits allowed, in inches per second cubed.
+pub const JERK_RANGE: std::ops::RangeInclusive<f32> = 1.0..=10000.0;
+/// Range of lateral acceleration limits allowed, in inches per second squared.
+pub const LATERAL_RANGE: std::ops::RangeInclusive<f32> = 1.0..=2000.0;
+/// Furthest the robot moves between poses stamped into the swept area, in inches.
+const SWEEP_STEP: f32 = 1.0;
+/// Most the robot turns between poses stamped into the swept area, in degrees.
+const SWEEP_TURN: f32 = 5.0;
+
+/// The robot driving the routes: its footprint and how fast it can go.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct Robot {
+    /// Front to back, in inches
+    pub length: f32,
+    /// Side to side, in inches
+    pub width: f32,
+    /// Speed where the path sets no limit, in inches per second
+    pub cruise: f32,
+    /// How quickly it speeds up and slows down, in inches per second squared
+    pub acceleration: f32,
+    /// How quickly it turns in place, in degrees per second
+    pub turn: f32,
+}
+
+impl Default for Robot {
+    fn default() -> Self {
+        Self {
+            length: ROBOT_SIZE,
+            width: ROBOT_SIZE,
+            cruise: CRUISE_SPEED,
+            acceleration: ACCELERATION,
+            turn: TURN_SPEED,
+        }
+    }
+}
+
+impl Robot {
+    /// Brings values read from a file into the ranges the editor allows, so a bad value can't stall the simulation.
+    /// Values that aren't numbers go back to their defaults.
+    pub fn clamp(&mut self) {
+        let default = Self::default();
+        let within = |value: f32, default: f32, range: &std::ops::RangeInclusive<f32>| {
+            if value.is_nan() {
+                default
+            } else {
+                value.clamp(*range.start(), *range.end())
+            }
+        };
+        self.length = within(self.length, default.length, &SIZE_RANGE);
+        self.width = within(self.width, default.width, &SIZE_RANGE);
+        self.cruise = within(self.cruise, default.cruise, &CRUISE_RANGE);
+        self.acceleration = within(self.acceleration, default.acceleration, &ACCELERATION_RANGE);
+        self.turn = within(self.turn, default.turn, &TURN_RANGE);
+    }
+    /// Corners of the robot at a pose, front corners first.
+    pub fn outline(&self, pose: &Pose) -> [Pos2; 4] {
+        pose.outline(self.length, self.width)
+    }
+}
+
+/// Limits on how a path is driven, beyond its speed limits.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    /// Fastest the acceleration may change, in inches per second cubed, for an S-curve profile
+    pub jerk: Option<f32>,
+    /// Largest sideways acceleration allowed in curves, in inches per second squared
+    pub lateral: Option<f32>,
+}
+
+impl Profile {
+    /// Brings limits read from a file into the ranges the editor allows, so a bad value can't stall the simulation.
+    /// Limits that aren't numbers are dropped.
+    pub fn clamp(&mut self) {
+        let within = |limit: Option<f32>, range: &std::ops::RangeInclusive<f32>| {
+            limit
+                .filter(|v| !v.is_nan())
+                .map(|v| v.clamp(*range.start(), *range.end()))
+        };
+        self.jerk = within(self.jerk, &JERK_RANGE);
+        self.lateral = within(self.lateral, &LATERAL_RANGE);
+    }
+    /// Fastest the robot can take a curve of `curvature` without exceeding the lateral limit.
+    pub fn curve_speed(&self, curvature: f32) -> f32 {
+        match self.lateral {
+            Some(lateral) if curvature > 0.0 => (lateral / curvature).sqrt(),
+            _ => f32::INFINITY,
+        }
+    }
+}
+
+/// A route timed as if driven, as poses at increasing times in seconds.
+#[derive(Clone, Debug, Default)]
+pub struct Timeline {
+    /// The robot driving it
+    robot: Robot,
+    frames: Vec<(f32, Pose)>,
+    /// How far along the route each frame is in inches, and the segment of the path it's on
+    travel: Vec<(f32, usize)>,
+    /// Sync markers in the order they're reached, with the frame they're reached at
+    markers: Vec<(String, usize)>,
+    /// When each of the route's samples is reached, in seconds
+    reached: Vec<f32>,
+}
+
+impl Timeline {
+    /// Times a route driven with a trapezoidal profile: speeding up and slowing down at the robot's acceleration
+    /// up to its cruise speed, the path's speed limits or the profile's limit in curves, and coming
+    /// to rest wherever the generated code does, unless the end pose keeps the robot moving at the end.
+    /// The `profile` can smooth the changes in acceleration into an S-curve.
+    /// Timed waits pause the robot; waits on robot code conditions are taken to be over
+    /// as soon as they start. A robot placed at a start pose off the path drives straight to
+    /// the first point, then turns at the robot's turn speed to face along the path. It turns in place
+    /// at sharp corners too.
+    pub fn new(points: &[BezPoint], driving: &Driving<'_>) -> Self {
+        let profile = driving.profile;
+        let robot = *driving.robot;
+        let mut timeline = Self {
+            robot,
+            ..Self::default()
+        };
+        if points.len() < 2 {
+            return timeline;
+        }
+        let mut points = points.to_vec();
+        driving.end.apply(&mut points);
+        let points = points.as_slice();
+        let exit = driving
+            .end
+            .speed
+            .filter(|_| driving.end.moving())
+            .unwrap_or(0.0);
+        let headings = headings(points, STEPS, driving);
+        let poses: Vec<Pose> = headings
+            .iter()
+            .enumerate()
+            .map(|(k, &heading)| {
+                let seg = (k / STEPS).min(points.len() - 2);
+                let t = (k - seg * STEPS) as f32 / STEPS as f32;
+                let pos = interpolate(&points[seg], &points[seg + 1], t).into();
+                Pose { pos, heading }
+            })
+            .collect();
+        timeline.reached = vec![0.0; poses.len()];
+        // Fastest allowed at each sample, by speed limits and then by how sharply the path curves
+        let limits: Vec<f32> = (0..poses.len())
+            .map(|k| {
+                let seg = (k / STEPS).min(points.len() - 2);
+                let t = (k - seg * STEPS) as f32 / STEPS as f32;
+                let (a, b) = (&points[seg], &points[seg + 1]);
+                [a.speed, b.speed]
+                    .into_iter()
+                    .flatten()
+                    .fold(robot.cruise, f32::min)
+                    .min(profile.curve_speed(analysis::curvature_at(a, b, t)))
+            })
+            .collect();
+        let mut time = 0.0;
+        let mut from = 0;
+        if let Some(start) = driving.start {
+            timeline.push(0.0, *start, 0.0, 0);
+            if start.pos.distance(poses[0].pos) > START_TOLERANCE {
+                let approach: Vec<Pose> = (0..=STEPS)
+                    .map(|j| Pose {
+                        pos: start.pos.lerp(poses[0].pos, j as f32 / STEPS as f32),
+                        heading: start.heading,
+                    })
+                    .collect();
+                time = timeline.drive(&approach, &[robot.cruise; STEPS + 1], 0, profile, 0.0, time);
+            }
+            time = timeline.turn(poses[0].heading, time);
+        }
+        for (i, point) in points.iter().enumerate() {
+            let k = i * STEPS;
+            if i == 0 {
+                if driving.start.is_none() {
+                    timeline.push(0.0, poses[0], 0.0, 0);
+                }
+            } else if rests(points, i) {
+                let last = i == points.len() - 1;
+                let sharp = !last && point.mode == HandleMode::Sharp;
+                let mut span = poses[from..=k].to_vec();
+                if sharp {
+                    // The robot arrives facing along the segment it drove, then turns in place
+                    span[k - from].heading = poses[k - 1].heading;
+                }
+                let speed = if last { exit } else { 0.0 };
+                let first = timeline.frames.len() - 1;
+                time = timeline.drive(&span, &limits[from..=k], from, profile, speed, time);
+                timeline.reach(first, &span, from);
+                if sharp {
+                    time = timeline.turn(poses[k].heading, time);
+                }
+                from = k;
+            } else {
+                continue;
+            }
+            if !point.marker.is_empty() {
+                let frame = timeline.frames.len() - 1;
+                timeline.markers.push((point.marker.clone(), frame));
+            }
+            if let Some(Wait::Time(seconds)) = point.wait {
+                time += seconds;
+                let distance = timeline.travelled();
+                timeline.push(time, poses[k], distance, i.saturating_sub(1));
+            }
+        }
+        timeline
+    }
+    /// Adds a frame at `time`, `distance` along the route on `segment`.
+    fn push(&mut self, time: f32, pose: Pose, distance: f32, segment: usize) {
+        self.frames.push((time, pose));
+        self.travel.push((distance, segment));
+    }
+    /// Turns in place from the last frame to face `heading`, and returns the time it finishes.
+    fn turn(&mut self, heading: f32, time: f32) -> f32 {
+        let (Some(&(_, pose)), Some(&(distance, segment))) =
+            (self.frames.last(), self.travel.last())
+        else {
+            return time;
+        };
+        let angle = normalize(heading - pose.heading).abs();
+        if angle < 1e-3 {
+            return time;
+        }
+        let time = time + angle / self.robot.turn;
+        self.push(
+            time,
+            Pose {
+                pos: pose.pos,
+                heading,
+            },
+            distance,
+            segment,
+        );
+        time
+    }
+    /// Records when each of `poses`, the route's samples from `from`, is reached,
+    /// from the frames driving them, which start at frame `first`.
+    fn reach(&mut self, first: usize, poses: &[Pose], from: usize) {
+        let start = self.travel[first].0;
+        let mut along = 0.0;
+        let mut j = first;
+        for (k, pose) in poses.iter().enumerate() {
+            if k > 0 {
+                along += poses[k - 1].pos.distance(pose.pos);
+            }
+            let distance = start + along;
+            while j + 1 < self.travel.len() && self.travel[j].0 < distance {
+                j += 1;
+            }
+            let (d0, d1) = (self.travel[j.saturating_sub(1)].0, self.travel[j].0);
+            self.reached[from + k] = if j > first && d1 > d0 {
+                let f = ((distance - d0) / (d1 - d0)).clamp(0.0, 1.0);
+                self.frames[j - 1].0 + (self.frames[j].0 - self.frames[j - 1].0) * f
+            } else {
+                self.frames[j].0
+            };
+        }
+    }
+    /// When the robot reaches parameter `u` of the route, counted in segments, in seconds.
+    pub fn time_at(&self, u: f32) -> f32 {
+        let Some(&end) = self.reached.last() else {
+            return 0.0;
+        };
+        let x = (u * STEPS as f32).max(0.0);
+        let k = x as usize;
+        if k + 1 >= self.reached.len() {
+            return end;
+        }
+        let f = x - k as f32;
+        self.reached[k] + (self.reached[k + 1] - self.reached[k]) * f
+    }
+    /// How far along the route the last frame is, in inches.
+    fn travelled(&self) -> f32 {
+        self.travel.last().map_or(0.0, |(distance, _)| *distance)
+    }
+    /// Drives from rest at the first pose to the last, arriving at `exit` speed or less, starting at `time`,
+    /// and returns the time it arrives. The poses start at sample `from` of the route.
+    fn drive(
+        &mut self,
+        poses: &[Pose],
+        limits: &[f32],
+        from: usize,
+        profile: &Profile,
+        exit: f32,
+        time: f32,
+    ) -> f32 {
+        let start = self.travelled();
+        let distances: Vec<f32> = std::iter::once(0.0)
+            .chain(poses.windows(2).map(|w| w[0].pos.distance(w[1].pos)))
+            .scan(0.0, |total, d| {
+                *total += d;
+                Some(*total)
+            })
+            .collect();
+        let acceleration = self.robot.acceleration;
+        let mut speeds = limits.to_vec();
+        let last = speeds.len() - 1;
+        speeds[0] = 0.0;
+        speeds[last] = speeds[last].min(exit);
+        // Limit speeds to what the robot can reach accelerating forwards, then braking backwards
+        for k in 1..speeds.len() {
+            let d = distances[k] - distances[k - 1];
+            speeds[k] = speeds[k].min((speeds[k - 1].powi(2) + 2.0 * acceleration * d).sqrt());
+        }
+        for k in (0..last).rev() {
+            let d = distances[k + 1] - distances[k];
+            speeds[k] = speeds[k].min((speeds[k + 1].powi(2) + 2.0 * acceleration * d).sqrt());
+        }
+        let mut times = vec![0.0];
+        for k in 1..speeds.len() {
+            let speed = (speeds[k - 1] + speeds[k]) / 2.0;
+            let d = distances[k] - distances[k - 1];
+            times.push(times[k - 1] + if speed > 0.0 { d / speed } else { 0.0 });
+        }
+        let Some(jerk) = profile.jerk.filter(|j| *j > 0.0) else {
+            for k in 1..poses.len() {
+                self.push(
+                    time + times[k],
+                    poses[k],
+                    start + distances[k],
+                    (from + k - 1) / STEPS,
+                );
+            }
+            return time + times[last];
+        };
+        // The trapezoid speeds up or slows down evenly between samples, so its acceleration is never
+        // more than the robot's. Averaging its progress over the time it takes to swing from full
+        // acceleration to full braking turns each change in acceleration into a ramp no steeper than the jerk limit
+        let window = 2.0 * acceleration / jerk;
+        // Area under the trapezoid's progress up to each sample, worked in double precision
+        // since the average is the difference of two of them
+        let mut areas = vec![0.0f64];
+        for k in 1..speeds.len() {
+            let dt = f64::from(times[k] - times[k - 1]);
+            let (v0, v1) = (f64::from(speeds[k - 1]), f64::from(speeds[k]));
+            areas.push(
+                areas[k - 1] + f64::from(distances[k - 1]) * dt + dt * dt * (2.0 * v0 + v1) / 6.0,
+            );
+        }
+        let area = |t: f32| {
+            let k = times.partition_point(|&tk| tk <= t);
+            if k == 0 {
+                return 0.0;
+            }
+            if k >= times.len() {
+                // Carrying on at the exit speed, so a robot still moving at the end doesn't slow down for it
+                let tau = f64::from(t - times[last]);
+                return areas[last]
+                    + f64::from(distances[last]) * tau
+                    + f64::from(speeds[last]) * tau * tau / 2.0;
+            }
+            let dt = f64::from(times[k] - times[k - 1]);
+            let tau = f64::from(t - times[k - 1]);
+            let v0 = f64::from(speeds[k - 1]);
+            let rate = (f64::from(speeds[k]) - v0) / dt;
+            areas[k - 1]
+                + f64::from(distances[k - 1]) * tau
+                + v0 * tau * tau / 2.0
+                + rate * tau * tau * tau / 6.0
+        };
+        let duration = times[last] + window;
+        let frames = ((duration / FRAME_TIME).ceil().max(1.0) as usize).min(MAX_FRAMES);
+        for n in 1..=frames {
+            let t = duration * n as f32 / frames as f32;
+            let s =
+                (((area(t) - area(t - window)) / f64::from(window)) as f32).min(distances[last]);
+            let k = distances.partition_point(|&d| d <= s).clamp(1, last);
+            self.push(
+                time + t,
+                along(poses, &distances, s),
+                start + s,
+                (from + k - 1) / STEPS,
+            );
+            if speeds[last] > 0.0 && s >= distances[last] {
+                return time + t;
+            }
+        }
+        time + duration
+    }
+    /// Seconds until the route is finished.
+    pub fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |(time, _)| *time)
+    }
+    /// Largest change in speed along the route on each of the first `count` segments,
+    /// in inches per second squared, from the speeds between frames.
+    pub fn accelerations(&self, count: usize) -> Vec<f32> {
+        let mut largest = vec![0.0; count];
+        // Speed between each pair of frames, at the time halfway between them
+        let speeds: Vec<(f32, f32, usize)> = self
+            .frames
+            .windows(2)
+            .zip(self.travel.windows(2))
+            .filter(|(frames, _)| frames[1].0 > frames[0].0)
+            .map(|(frames, travel)| {
+                let (t0, t1) = (frames[0].0, frames[1].0);
+                let ((d0, _), (d1, segment)) = (travel[0], travel[1]);
+                ((t0 + t1) / 2.0, (d1 - d0) / (t1 - t0), segment)
+            })
+            .collect();
+        for pair in speeds.windows(2) {
+            let ((t0, v0, _), (t1, v1, segment)) = (pair[0], pair[1]);
+            if let Some(max) = largest.get_mut(segment) {
+                *max = f32::max(*max, ((v1 - v0) / (t1 - t0)).abs());
+            }
+        }
+        largest
+    }
+    /// Where the robot is `time` seconds in, or `None` for an empty route.
+    /// Before the start and after the end the robot is held in place.
+    pub fn pose(&self, time: f32) -> Option<Pose> {
+        let i = self.frames.partition_point(|(t, _)| *t <= time);
+        if i == 0 || i == self.frames.len() {
+            return self.frames.get(i.saturating_sub(1)).map(|(_, pose)| *pose);
+        }
+        let ((t0, a), (t1, b)) = (self.frames[i - 1], self.frames[i]);
+        let f = if t1 > t0 {
+            (time - t0) / (t1 - t0)
+        } else {
+            1.0
+        };
+        Some(Pose {
+            pos: a.pos.lerp(b.pos, f),
+            heading: normalize(a.heading + normalize(b.heading - a.heading) * f),
+        })
+    }
+    /// The field the robot covers over the route, as non-overlapping rectangles on a grid of
+    /// `cell` inches, so it can be filled evenly.
+    /// A cell is covered when its center is inside the robot at some pose.
+    pub fn swept(&self, cell: f32) -> Vec<egui::Rect> {
+        let mut outlines = Vec::new();
+        for pair in self.frames.windows(2) {
+            let (a, b) = (pair[0].1, pair[1].1);
+            let turn = normalize(b.heading - a.heading);
+            let steps = (a.pos.distance(b.pos) / SWEEP_STEP)
+                .max(turn.abs() / SWEEP_TURN)
+                .ceil()
+                .max(1.0) as usize;
+            for i in 0..steps {
+                let f = i as f32 / steps as f32;
+                let pose = Pose {
+                    pos: a.pos.lerp(b.pos, f),
+                    heading: a.heading + turn * f,
+                };
+                outlines.push(self.robot.outline(&pose));
+            }
+        }
+        if let Some((_, last)) = self.frames.last() {
+            outlines.push(self.robot.outline(last));
+        }
+        let Some(bounds) = outlines
+            .iter()
+            .map(|o| egui::Rect::from_points(o))
+            .reduce(|a, b| a.union(b))
+        else {
+            return Vec::new();
+        };
+
+        let columns = (bounds.width() / cell).ceil() as usize + 1;
+        let rows = (bounds.height() / cell).ceil() as usize + 1;
+        let mut covered = vec![false; columns * rows];
+        for outline in &outlines {
+            let area = egui::Rect::from_points(outline);
+            let first = ((area.min - bounds.min) / cell).floor();
+            let last = ((area.max - bounds.min) / cell).ceil();
+            for row in first.y as usize..(last.y as usize).min(rows) {
+                for column in first.x as usize..(last.x as usize).min(columns) {
+                    let center =
+                        bounds.min + egui::vec2(column as f32 + 0.5, row as f32 + 0.5) * cell;
+                    if contains(outline, center) {
+                        covered[row * columns + column] = true;
+                    }
+                }
+            }
+        }
+
+        // Join each row's covered cells into runs
+        let mut runs = Vec::new();
+        for row in 0..rows {
+            let mut column = 0;
+            while column < columns {
+                if !covered[row * columns + column] {
+                    column += 1;
+                    continue;
+                }
+                let start = column;
+                while column < columns && covered[row * columns + column] {
+                    column += 1;
+                }
+                let min = bounds.min + egui::vec2(start as f32, row as f32) * cell;
+                runs.push(egui::Rect::from_min_max(
+                    min,
+                    min + egui::vec2((column - start) as f32, 1.0) * cell,
+                ));
+            }
+        }
+        runs
+    }
+    /// Holds the robot where it is at frame `i` for `delay` seconds, pushing back everything after.
+    fn hold(&mut self, i: usize, delay: f32) {
+        let (time, pose) = self.frames[i];
+        for frame in &mut self.frames[i + 1..] {
+            frame.0 += delay;
+        }
+        self.frames.insert(i + 1, (time + delay, pose));
+        self.travel.insert(i + 1, self.travel[i]);
+        for reached in &mut self.reached {
+            if *reached > time {
+                *reached += delay;
+            }
+        }
+        for marker in &mut self.markers {
+            if marker.1 > i {
+                marker.1 += 1;
+            }
+        }
+    }
+}
+
+/// The pose `s` inches along poses that are `distances` along the route.
+fn along(poses: &[Pose], distances: &[f32], s: f32) -> Pose {
+    let k = distances.partition_point(|&d| d <= s);
+    if k == 0 || k >= poses.len() {
+        return poses[k.min(poses.len() - 1)];
+    }
+    let (a, b) = (poses[k - 1], poses[k]);
+    let span = distances[k] - distances[k - 1];
+    let f = if span > 0.0 {
+        (s - distances[k - 1]) / span
+    } else {
+        1.0
+    };
+    Pose {
+        pos: a.pos.lerp(b.pos, f),
+        heading: normalize(a.heading + normalize(b.heading - a.heading) * f),
+    }
+}
+
+/// Whether the robot comes to rest at point `i`, where the generated code splits the path.
+fn rests(points: &[BezPoint], i: usize) -> bool {
+    let point = &points[i];
+    let inner = i > 0 && i < points.len() - 1;
+    !inner
+        || point.broken()
+        || point.stop
+        || point.wait.is_some()
+        || !point.actions.is_empty()
+        || !point.marker.is_empty()
+        || point.reverse != points[i - 1].reverse
+}
+
+/// Samples the target heading in degrees along a path like [`analysis::headings`], timing it driven
+/// as `driving` says when headings turn over time. Headings only change how long the robot turns in place,
+/// so the route is timed turning by distance.
+pub fn headings(points: &[BezPoint], steps: usize, driving: &Driving<'_>) -> Vec<f32> {
+    if driving.mode != HeadingMode::Time || points.len() < 2 || steps == 0 {
+        return analysis::headings(points, steps, driving.mode, &[]);
+    }
+    let timeline = Timeline::new(
+        points,
+        &Driving {
+            mode: HeadingMode::Distance,
+            ..*driving
+        },
+    );
+    let times: Vec<f32> = (0..=(points.len() - 1) * steps)
+        .map(|k| timeline.time_at(k as f32 / steps as f32))
+        .collect();
+    analysis::headings(points, steps, driving.mode, &times)
+}
+
+/// Lines two robots' timelines up at their shared sync markers, holding whichever robot gets to
+/// a marker first until the other arrives. Markers are matched in the order they're reached.
+pub fn sync(a: &mut Timeline, b: &mut Timeline) {
+    let mut from = 0;
+    for m in 0..a.markers.len() {
+        let Some(j) = b.markers[from..]
+            .iter()
+            .position(|(name, _)| *name == a.markers[m].0)
+        else {
+            continue;
+        };
+        let j = from + j;
+        from = j + 1;
+        let (ia, ib) = (a.markers[m].1, b.markers[j].1);
+        let delay = a.frames[ia].0 - b.frames[ib].0;
+        if delay > 0.0 {
+            b.hold(ib, delay);
+        } else if delay < 0.0 {
+            a.hold(ia, -delay);
+        }
+    }
+}
+
+/// The first time, checked every `step` seconds, that the two robots touch.
+pub fn collision(a: &Timeline, b: &Timeline, step: f32) -> Option<f32> {
+    let end = a.duration().max(b.duration());
+    let mut time = 0.0;
+    while time <= end {
+        if let (Some(pa), Some(pb)) = (a.pose(time), b.pose(time)) {
+            if overlap(&a.robot.outline(&pa), &b.robot.outline(&pb)) {
+                return Some(time);
+            }
+        }
+        time += step;
+    }
+    None
+}
+
+/// Whether a position is inside a robot outline.
+fn contains(outline: &[Pos2; 4], p: Pos2) -> bool {
+    let side = |i: usize| {
+        (outline[(i + 1) % 4] - outline[i])
+            .rot90()
+            .dot(p - outline[i])
+    };
+    let (a, b, c, d) = (side(0), side(1), side(2), side(3));
+    (a >= 0.0 && b >= 0.0 && c >= 0.0 && d >= 0.0) || (a <= 0.0 && b <= 0.0 && c <= 0.0 && d <= 0.0)
+}
+
+/// Whether two robot outlines overlap, by looking for an edge that separates them.
+pub fn overlap(a: &[Pos2; 4], b: &[Pos2; 4]) -> bool {
+    let edges =
+        |q: &[Pos2; 4]| -> [egui::Vec2; 4] { std::array::from_fn(|i| q[(i + 1) % 4] - q[i]) };
+    let range = |q: &[Pos2; 4], axis: egui::Vec2| {
+        q.iter()
+            .map(|p| p.to_vec2().dot(axis))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| {
+                (lo.min(d), hi.max(d))
+            })
+    };
+    !edges(a).into_iter().chain(edges(b)).any(|edge| {
+        let axis = edge.rot90();
+        let ((a0, a1), (b0, b1)) = (range(a, axis), range(b, axis));
+        a1 < b0 || b1 < a0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bezier::EndPose;
+
+    /// A straight route through `xs` along `y`, with handles a third of the way to each neighbour
+    /// so it's driven at an even pace in the Bezier parameter.
+    fn straight(xs: &[f32], y: f32) -> Vec<BezPoint> {
+        xs.iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let before = if i > 0 { x - xs[i - 1] } else { xs[1] - x };
+                let after = xs.get(i + 1).map_or(before, |next| next - x);
+                BezPoint::new(x, y, x - before / 3.0, y, x + after / 3.0, y)
+                    .borrow()
+                    .clone()
+            })
+            .collect()
+    }
+
+    fn timeline(points: &[BezPoint], profile: &Profile, mode: HeadingMode) -> Timeline {
+        let driving = Driving {
+            start: None,
+            end: &EndPose::default(),
+            profile,
+            mode,
+            robot: &Robot::default(),
+        };
+        Timeline::new(points, &driving)
+    }
+
+    #[test]
+    fn time_headings_follow_the_profile() {
+        let mut points = straight(&[0.0, 100.0], 50.0);
+        points[0].heading = Some(0.0);
+        points[1].heading = Some(90.0);
+        let driving = Driving {
+            start: None,
+            end: &EndPose::default(),
+            profile: &Profile::default(),
+            mode: HeadingMode::Time,
+            robot: &Robot::default(),
+        };
+        let by_time = headings(&points, 4, &driving);
+        let by_distance = analysis::headings(&points, 4, HeadingMode::Distance, &[]);
+        assert_eq!(by_time.len(), 5);
+        assert_eq!((by_time[0], by_time[4]), (0.0, 90.0));
+        // Speeding up makes the first quarter take longer than a quarter of the time
+        assert!((by_distance[1] - 22.5).abs() < 0.1);
+        let quarter = ACCELERATION / 2.0 * (CRUISE_SPEED / ACCELERATION).powi(2);
+        let accelerating = CRUISE_SPEED / ACCELERATION;
+        let reached = accelerating + (25.0 - quarter) / CRUISE_SPEED;
+        let total = 2.0 * accelerating + (100.0 - 2.0 * quarter) / CRUISE_SPEED;
+        assert!((by_time[1] - 90.0 * reached / total).abs() < 0.5);
+        // Braking mirrors speeding up, so halfway is reached halfway through
+        assert!((by_time[2] - 45.0).abs() < 0.5);
+        let timed = timeline(&points, &Profile::default(), HeadingMode::Time);
+        assert!((timed.duration() - total).abs() < 0.05);
+    }
+
+    #[test]
+    fn sync_holds_the_earlier_robot() {
+        let mut ours = straight(&[0.0, 50.0, 100.0], 20.0);
+        ours[1].marker = "score".into();
+        let mut theirs = straight(&[0.0, 25.0, 50.0, 75.0], 80.0);
+        theirs[1].marker = "score".into();
+        theirs[2].marker = "park".into();
+        let profile = Profile::default();
+        let mut a = timeline(&ours, &profile, HeadingMode::Distance);
+        let mut b = timeline(&theirs, &profile, HeadingMode::Distance);
+        let arrival = |t: &Timeline, m: usize| t.frames[t.markers[m].1];
+        let (ours_at, theirs_at) = (arrival(&a, 0).0, arrival(&b, 0).0);
+        let park = arrival(&b, 1);
+        let (ours_end, theirs_end) = (a.duration(), b.duration());
+        // Driving 25 inches instead of 50 gets them there first
+        let delay = ours_at - theirs_at;
+        assert!(delay > 0.1);
+        sync(&mut a, &mut b);
+        assert_eq!(a.duration(), ours_end);
+        assert!((b.duration() - (theirs_end + delay)).abs() < 1e-4);
+        // They wait at the marker, then leave together
+        let held = b.markers[0].1;
+        assert_eq!(b.frames[held].0, theirs_at);
+        assert_eq!(b.frames[held].1.pos, egui::pos2(25.0, 80.0));
+        assert!((b.frames[held + 1].0 - ours_at).abs() < 1e-4);
+        assert_eq!(b.frames[held + 1].1.pos, egui::pos2(25.0, 80.0));
+        // Later markers still point at the frame they're reached at
+        let (time, pose) = arrival(&b, 1);
+        assert_eq!(pose.pos, park.1.pos);
+        assert_eq!(pose.pos, egui::pos2(50.0, 80.0));
+        assert!((time - (park.0 + delay)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn unmatched_markers_are_left_alone() {
+        let mut ours = straight(&[0.0, 50.0, 100.0], 20.0);
+        ours[1].marker = "score".into();
+        let mut theirs = straight(&[0.0, 25.0, 50.0], 80.0);
+        theirs[1].marker = "park".into();
+        let profile = Profile::default();
+        let mut a = timeline(&ours, &profile, HeadingMode::Distance);
+        let mut b = timeline(&theirs, &profile, HeadingMode::Distance);
+        let (frames_a, frames_b) = (a.frames.clone(), b.frames.clone());
+        sync(&mut a, &mut b);
+        assert_eq!(a.frames, frames_a);
+        assert_eq!(b.frames, frames_b);
+    }
+
+    #[test]
+    fn overlap_of_rotated_outlines() {
+        let pose = |x: f32, y: f32, heading: f32| Pose {
+            pos: egui::pos2(x, y),
+            heading,
+        };
+        let diamond = pose(0.0, 0.0, 45.0).outline(10.0, 10.0);
+        // The diamond's tip reaches 7.07 inches out
+        assert!(overlap(&diamond, &pose(12.0, 0.0, 0.0).outline(10.0, 10.0)));
+        assert!(!overlap(
+            &diamond,
+            &pose(12.5, 0.0, 0.0).outline(10.0, 10.0)
+        ));
+        // Their bounding boxes overlap, but the diamond's edge separates them
+        assert!(!overlap(&diamond, &pose(9.0, 9.0, 0.0).outline(10.0, 10.0)));
+        assert!(overlap(&diamond, &pose(8.0, 8.0, 0.0).outline(10.0, 10.0)));
+        // Long and thin, crossed over each other
+        let bar = pose(0.0, 0.0, 30.0).outline(40.0, 2.0);
+        assert!(overlap(&bar, &pose(0.0, 0.0, -60.0).outline(40.0, 2.0)));
+        assert!(!overlap(&bar, &pose(0.0, 10.0, 30.0).outline(40.0, 2.0)));
+    }
+
+    #[test]
+    fn head_on_routes_collide() {
+        let profile = Profile::default();
+        let a = timeline(
+            &straight(&[0.0, 100.0], 50.0),
+            &profile,
+            HeadingMode::Tangent,
+        );
+        let b = timeline(
+            &straight(&[100.0, 0.0], 50.0),
+            &profile,
+            HeadingMode::Tangent,
+        );
+        let time = collision(&a, &b, 0.05).unwrap();
+        let (pa, pb) = (a.pose(time).unwrap(), b.pose(time).unwrap());
+        assert!(pa.pos.distance(pb.pos) <= ROBOT_SIZE + 0.1);
+        // Passing in lanes further apart than the robot is wide
+        let c = timeline(
+            &straight(&[100.0, 0.0], 80.0),
+            &profile,
+            HeadingMode::Tangent,
+        );
+        assert_eq!(collision(&a, &c, 0.05), None);
+    }
+
+    /// Speed between each pair of frames, with the time halfway between them.
+    fn speeds(timeline: &Timeline) -> Vec<(f32, f32)> {
+        timeline
+            .frames
+            .windows(2)
+            .zip(timeline.travel.windows(2))
+            .filter(|(frames, _)| frames[1].0 > frames[0].0)
+            .map(|(frames, travel)| {
+                let dt = frames[1].0 - frames[0].0;
+                (
+                    (frames[0].0 + frames[1].0) / 2.0,
+                    (travel[1].0 - travel[0].0) / dt,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn trapezoid_matches_closed_form() {
+        let profile = Profile::default();
+        let accelerating = CRUISE_SPEED / ACCELERATION;
+        let ramp = CRUISE_SPEED * accelerating / 2.0;
+        // Long enough to reach cruise speed
+        let long = timeline(
+            &straight(&[0.0, 100.0], 50.0),
+            &profile,
+            HeadingMode::Distance,
+        );
+        let expected = 2.0 * accelerating + (100.0 - 2.0 * ramp) / CRUISE_SPEED;
+        assert!((long.duration() - expected).abs() < 0.02);
+        // Too short, so it starts braking before reaching it
+        let short = timeline(
+            &straight(&[0.0, 10.0], 50.0),
+            &profile,
+            HeadingMode::Distance,
+        );
+        let expected = 2.0 * (10.0 / ACCELERATION).sqrt();
+        assert!((short.duration() - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn jerk_limit_smooths_acceleration() {
+        let points = straight(&[0.0, 60.0, 120.0], 50.0);
+        let trapezoid = timeline(&points, &Profile::default(), HeadingMode::Distance);
+        let jerk = 300.0;
+        let profile = Profile {
+            jerk: Some(jerk),
+            ..Profile::default()
+        };
+        let smooth = timeline(&points, &profile, HeadingMode::Distance);
+        assert!(smooth.duration() >= trapezoid.duration());
+        // Easing through each change in acceleration takes as long as swinging from full speeding up to full braking
+        assert!(
+            (smooth.duration() - trapezoid.duration() - 2.0 * ACCELERATION / jerk).abs() < 0.05
+        );
+        let speeds = speeds(&smooth);
+        let accelerations: Vec<(f32, f32)> = speeds
+            .windows(2)
+            .map(|w| {
+                (
+                    (w[0].0 + w[1].0) / 2.0,
+                    (w[1].1 - w[0].1) / (w[1].0 - w[0].0),
+                )
+            })
+            .collect();
+        let peak = accelerations
+            .iter()
+            .fold(0.0, |m: f32, (_, a)| m.max(a.abs()));
+        assert!(peak <= ACCELERATION * 1.01);
+        for w in accelerations.windows(2) {
+            let change = (w[1].1 - w[0].1).abs() / (w[1].0 - w[0].0);
+            assert!(change <= jerk * 1.01, "jerk of {change} at {}", w[0].0);
+        }
+        assert_eq!(smooth.travelled(), trapezoid.travelled());
+    }
+
+    #[test]
+    fn jerk_limit_keeps_moving_at_a_moving_end() {
+        let points = straight(&[0.0, 100.0], 50.0);
+        let profile = Profile {
+            jerk: Some(300.0),
+            ..Profile::default()
+        };
+        let moving = EndPose {
+            heading: None,
+            speed: Some(CRUISE_SPEED),
+        };
+        let driving = Driving {
+            start: None,
+            end: &moving,
+            profile: &profile,
+            mode: HeadingMode::Distance,
+            robot: &Robot::default(),
+        };
+        let through = Timeline::new(&points, &driving);
+        let stopping = timeline(&points, &profile, HeadingMode::Distance);
+        // It finishes as soon as it gets there, still at cruise speed, without easing to a stop
+        assert!(through.duration() < stopping.duration() - 0.5);
+        assert!((through.travelled() - stopping.travelled()).abs() < 1e-3);
+        let (_, last) = *speeds(&through).last().unwrap();
+        assert!(last > CRUISE_SPEED * 0.9);
+    }
+
+    #[test]
+    fn jerk_limit_caps_frames() {
+        let profile = Profile {
+            jerk: Some(1e-3),
+            ..Profile::default()
+        };
+        let slow = timeline(
+            &straight(&[0.0, 100.0], 50.0),
+            &profile,
+            HeadingMode::Distance,
+        );
+        assert!(slow.frames.len() <= MAX_FRAMES + 1);
+        assert!((slow.travelled() - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn curve_speed_caps_tight_arcs() {
+        assert_eq!(Profile::default().curve_speed(0.1), f32::INFINITY);
+        let profile = Profile {
+            lateral: Some(20.0),
+            ..Profile::default()
+        };
+        assert_eq!(profile.curve_speed(0.0), f32::INFINITY);
+        // A 20 inch radius taken at 20 inches per second pulls 20 inches per second squared sideways
+        assert!((profile.curve_speed(1.0 / 20.0) - 20.0).abs() < 1e-4);
+        // A quarter circle of radius 20 about (50, 50)
+        let k = 0.5523 * 20.0;
+        let arc = [
+            BezPoint::new(50.0, 30.0, 50.0 - k, 30.0, 50.0 + k, 30.0),
+            BezPoint::new(70.0, 50.0, 70.0, 50.0 - k, 70.0, 50.0 + k),
+        ]
+        .map(|p| p.borrow().clone());
+        let fastest = |profile: &Profile| {
+            speeds(&timeline(&arc, profile, HeadingMode::Tangent))
+                .into_iter()
+                .fold(0.0, |m: f32, (_, v)| m.max(v))
+        };
+        assert!(fastest(&Profile::default()) > 25.0);
+        let capped = fastest(&profile);
+        assert!(capped <= 20.0 * 1.02 && capped > 19.0, "peaked at {capped}");
+    }
+}
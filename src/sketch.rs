@@ -0,0 +1,175 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::bezier::{BezPoint, HandleMode};
+use egui::{Pos2, Vec2};
+
+/// Most times a segment's parameters are refined before it is split instead.
+const REFINE_ITERATIONS: usize = 4;
+
+/// Fits as few Bezier points as possible to a freehand stroke, so that no stroke sample
+/// is further than `tolerance` from the path (Schneider's least-squares algorithm).
+pub fn fit(stroke: &[Pos2], tolerance: f32) -> Vec<Rc<RefCell<BezPoint>>> {
+    // Repeated samples give zero-length tangents
+    let mut samples: Vec<Pos2> = Vec::with_capacity(stroke.len());
+    for &p in stroke {
+        if !matches!(samples.last(), Some(last) if last.distance(p) <= 1e-3) {
+            samples.push(p);
+        }
+    }
+    if samples.len() < 2 {
+        return Vec::new();
+    }
+    let n = samples.len();
+    let start = (samples[1] - samples[0]).normalized();
+    let end = (samples[n - 2] - samples[n - 1]).normalized();
+    let mut segments = Vec::new();
+    fit_cubic(&samples, start, end, tolerance, &mut segments);
+
+    // Neighbouring segments share an anchor, taking its handles from either side
+    let mut points = Vec::with_capacity(segments.len() + 1);
+    for i in 0..=segments.len() {
+        let (pos, cp1, cp2) = match (i.checked_sub(1).map(|j| segments[j]), segments.get(i)) {
+            (Some(before), Some(after)) => (after[0], before[2], after[1]),
+            (None, Some(after)) => (after[0], after[0] - (after[1] - after[0]), after[1]),
+            (Some(before), None) => (before[3], before[2], before[3] - (before[2] - before[3])),
+            (None, None) => unreachable!(),
+        };
+        let point = BezPoint::new(pos.x, pos.y, cp1.x, cp1.y, cp2.x, cp2.y);
+        // Split points keep their tangent but not equal handle lengths
+        point.borrow_mut().mode = HandleMode::Aligned;
+        points.push(point);
+    }
+    points
+}
+
+/// Fits cubic segments to the samples, splitting where a single cubic is not close enough.
+fn fit_cubic(
+    samples: &[Pos2],
+    start: Vec2,
+    end: Vec2,
+    tolerance: f32,
+    segments: &mut Vec<[Pos2; 4]>,
+) {
+    let (first, last) = (samples[0], samples[samples.len() - 1]);
+    if samples.len() == 2 {
+        let third = first.distance(last) / 3.0;
+        segments.push([first, first + start * third, last + end * third, last]);
+        return;
+    }
+    let mut params = chord_lengths(samples);
+    let mut curve = generate(samples, &params, start, end);
+    let (mut error, mut split) = max_error(samples, &curve, &params);
+    if error < tolerance {
+        segments.push(curve);
+        return;
+    }
+    // Close misses can often be fixed by refining the parameters rather than splitting
+    if error < tolerance * 4.0 {
+        for _ in 0..REFINE_ITERATIONS {
+            params = params
+                .iter()
+                .zip(samples)
+                .map(|(&t, &p)| refine(&curve, p, t))
+                .collect();
+            curve = generate(samples, &params, start, end);
+            (error, split) = max_error(samples, &curve, &params);
+            if error < tolerance {
+                segments.push(curve);
+                return;
+            }
+        }
+    }
+    let center = (samples[split - 1] - samples[split + 1]).normalized();
+    fit_cubic(&samples[..=split], start, center, tolerance, segments);
+    fit_cubic(&samples[split..], -center, end, tolerance, segments);
+}
+
+/// Parameters for each sample proportional to the distance along the stroke.
+fn chord_lengths(samples: &[Pos2]) -> Vec<f32> {
+    let mut params = vec![0.0];
+    for pair in samples.windows(2) {
+        params.push(params.last().unwrap() + pair[0].distance(pair[1]));
+    }
+    let total = *params.last().unwrap();
+    params.iter().map(|d| d / total).collect()
+}
+
+/// Least-squares cubic through the end samples, with handles along the given tangents.
+fn generate(samples: &[Pos2], params: &[f32], start: Vec2, end: Vec2) -> [Pos2; 4] {
+    let (first, last) = (samples[0], samples[samples.len() - 1]);
+    let mut c = [[0.0; 2]; 2];
+    let mut x = [0.0; 2];
+    for (&t, &p) in params.iter().zip(samples) {
+        let u = 1.0 - t;
+        let a1 = start * 3.0 * u * u * t;
+        let a2 = end * 3.0 * u * t * t;
+        c[0][0] += a1.dot(a1);
+        c[0][1] += a1.dot(a2);
+        c[1][1] += a2.dot(a2);
+        let base = first.to_vec2() * (u * u * u + 3.0 * u * u * t)
+            + last.to_vec2() * (3.0 * u * t * t + t * t * t);
+        let rest = p.to_vec2() - base;
+        x[0] += a1.dot(rest);
+        x[1] += a2.dot(rest);
+    }
+    c[1][0] = c[0][1];
+    let det = c[0][0] * c[1][1] - c[0][1] * c[1][0];
+    let (mut alpha1, mut alpha2) = if det.abs() > 1e-12 {
+        (
+            (x[0] * c[1][1] - x[1] * c[0][1]) / det,
+            (c[0][0] * x[1] - c[1][0] * x[0]) / det,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+    // Fall back to a third of the chord when the solution is degenerate
+    let chord = first.distance(last);
+    if alpha1 < chord * 1e-6 || alpha2 < chord * 1e-6 {
+        alpha1 = chord / 3.0;
+        alpha2 = chord / 3.0;
+    }
+    [first, first + start * alpha1, last + end * alpha2, last]
+}
+
+/// The sample furthest from the curve, and its distance.
+fn max_error(samples: &[Pos2], curve: &[Pos2; 4], params: &[f32]) -> (f32, usize) {
+    let mut worst = (0.0, samples.len() / 2);
+    for i in 1..samples.len() - 1 {
+        let distance = at(curve, params[i]).distance(samples[i]);
+        if distance > worst.0 {
+            worst = (distance, i);
+        }
+    }
+    worst
+}
+
+/// A Newton-Raphson step towards the parameter of the closest point on the curve.
+fn refine(curve: &[Pos2; 4], p: Pos2, t: f32) -> f32 {
+    let d = at(curve, t) - p;
+    let d1 = derivative(curve, t);
+    let d2 = second_derivative(curve, t);
+    let denominator = d1.dot(d1) + d.dot(d2);
+    if denominator.abs() < 1e-12 {
+        t
+    } else {
+        (t - d.dot(d1) / denominator).clamp(0.0, 1.0)
+    }
+}
+
+fn at(c: &[Pos2; 4], t: f32) -> Pos2 {
+    let u = 1.0 - t;
+    (c[0].to_vec2() * u * u * u
+        + c[1].to_vec2() * 3.0 * u * u * t
+        + c[2].to_vec2() * 3.0 * u * t * t
+        + c[3].to_vec2() * t * t * t)
+        .to_pos2()
+}
+
+fn derivative(c: &[Pos2; 4], t: f32) -> Vec2 {
+    let u = 1.0 - t;
+    (c[1] - c[0]) * 3.0 * u * u + (c[2] - c[1]) * 6.0 * u * t + (c[3] - c[2]) * 3.0 * t * t
+}
+
+fn second_derivative(c: &[Pos2; 4], t: f32) -> Vec2 {
+    (c[2] - c[1] - (c[1] - c[0])) * 6.0 * (1.0 - t) + (c[3] - c[2] - (c[2] - c[1])) * 6.0 * t
+}
@@ -0,0 +1,215 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::analysis;
+use crate::bezier::{BezPoint, Wait};
+use crate::generate::Driving;
+use crate::sim::Timeline;
+
+/// Samples per segment when measuring.
+const STEPS: usize = 20;
+
+/// How a report is written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Tables for strategy meetings and notes
+    Markdown,
+    /// Machine-readable, for scripts and spreadsheets
+    Json,
+}
+
+impl Format {
+    /// File extension of reports in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// Measurements of the stretch of path between two points, in inches and seconds.
+#[derive(serde::Serialize)]
+pub struct Segment {
+    pub length: f32,
+    /// Time to drive it at the fastest speed allowed along it, leaving out speeding up, slowing down and waits
+    pub time: f32,
+    /// Sharpest curvature, in 1 / inches
+    pub curvature: f32,
+    /// Fastest the robot drives it, given speed limits and the profile's limit in curves
+    pub speed: f32,
+    /// Largest sideways acceleration from turning at that speed
+    pub lateral: f32,
+    /// Largest change in speed driving it, speeding up or slowing down, as simulated
+    pub acceleration: f32,
+}
+
+/// A waypoint as listed in a report.
+#[derive(serde::Serialize)]
+struct Waypoint<'a> {
+    name: &'a str,
+    x: f32,
+    y: f32,
+    heading: Option<f32>,
+    speed: Option<f32>,
+    wait: Option<&'a Wait>,
+}
+
+/// Everything a report says about a path.
+#[derive(serde::Serialize)]
+struct Report<'a> {
+    name: &'a str,
+    /// Inches
+    length: f32,
+    /// Estimated seconds to drive it, with waits
+    time: f32,
+    /// Fastest the robot drives, in inches per second
+    cruise: f32,
+    /// Sharpest curvature, in 1 / inches
+    curvature: f32,
+    segments: Vec<Segment>,
+    waypoints: Vec<Waypoint<'a>>,
+}
+
+/// Measures each segment of a path, with the speed limits it's `driving` with and
+/// accelerations from its `timeline`.
+pub fn segments(
+    points: &[Rc<RefCell<BezPoint>>],
+    timeline: &Timeline,
+    driving: &Driving<'_>,
+) -> Vec<Segment> {
+    let profile = driving.profile;
+    let accelerations = timeline.accelerations(points.len().saturating_sub(1));
+    points
+        .windows(2)
+        .zip(accelerations)
+        .map(|(pair, acceleration)| {
+            let samples = analysis::curvature(pair, STEPS);
+            let limit = [pair[0].borrow().speed, pair[1].borrow().speed]
+                .into_iter()
+                .flatten()
+                .fold(driving.robot.cruise, f32::min);
+            let mut segment = Segment {
+                length: 0.0,
+                time: 0.0,
+                curvature: 0.0,
+                speed: 0.0,
+                lateral: 0.0,
+                acceleration,
+            };
+            for (i, (pos, k)) in samples.iter().enumerate() {
+                let curvature = k.length();
+                let speed = limit.min(profile.curve_speed(curvature));
+                if let Some((prev, _)) = i.checked_sub(1).map(|j| samples[j]) {
+                    let d = prev.distance(*pos);
+                    segment.length += d;
+                    segment.time += d / speed;
+                }
+                segment.curvature = segment.curvature.max(curvature);
+                segment.speed = segment.speed.max(speed);
+                segment.lateral = segment.lateral.max(speed * speed * curvature);
+            }
+            segment
+        })
+        .collect()
+}
+
+/// A report of a path's measurements and waypoints in `format`.
+pub fn report(
+    format: Format,
+    name: &str,
+    points: &[Rc<RefCell<BezPoint>>],
+    driving: &Driving<'_>,
+) -> Result<String, String> {
+    let plain: Vec<BezPoint> = points.iter().map(|p| p.borrow().clone()).collect();
+    let timeline = Timeline::new(&plain, driving);
+    let segments = segments(points, &timeline, driving);
+    let report = Report {
+        name,
+        length: segments.iter().map(|s| s.length).sum(),
+        time: timeline.duration(),
+        cruise: driving.robot.cruise,
+        curvature: segments.iter().map(|s| s.curvature).fold(0.0, f32::max),
+        waypoints: plain
+            .iter()
+            .map(|point| {
+                let pos = point.pos.borrow();
+                Waypoint {
+                    name: &point.name,
+                    x: pos.x,
+                    y: pos.y,
+                    heading: point.heading,
+                    speed: point.speed,
+                    wait: point.wait.as_ref(),
+                }
+            })
+            .collect(),
+        segments,
+    };
+    match format {
+        Format::Markdown => Ok(markdown(&report)),
+        Format::Json => serde_json::to_string_pretty(&report).map_err(|e| e.to_string()),
+    }
+}
+
+/// The report as Markdown tables.
+fn markdown(report: &Report<'_>) -> String {
+    let Report {
+        name,
+        length,
+        time: duration,
+        cruise,
+        curvature,
+        segments,
+        waypoints,
+    } = report;
+    let mut report = format!("# {name}\n\n");
+    report += "| | |\n|---|---|\n";
+    report += &format!("| Length | {length:.1} in |\n");
+    report += &format!("| Estimated time | {duration:.2} s at up to {cruise} in/s, with waits |\n");
+    if *curvature > 0.0 {
+        report += &format!(
+            "| Max curvature | {curvature:.4} 1/in (radius {:.1} in) |\n",
+            1.0 / curvature
+        );
+    } else {
+        report += "| Max curvature | 0 (straight) |\n";
+    }
+
+    report += "\n## Segments\n\n";
+    report += "| Segment | Length (in) | Time (s) | Max curvature (1/in) | Max speed (in/s) | Max lateral acceleration (in/s²) | Max acceleration (in/s²) |\n";
+    report += "|---|---|---|---|---|---|---|\n";
+    for (i, s) in segments.iter().enumerate() {
+        report += &format!(
+            "| {} → {} | {:.1} | {:.2} | {:.4} | {:.1} | {:.1} | {:.1} |\n",
+            i + 1,
+            i + 2,
+            s.length,
+            s.time,
+            s.curvature,
+            s.speed,
+            s.lateral,
+            s.acceleration
+        );
+    }
+
+    report += "\n## Waypoints\n\n";
+    report += "| # | Name | X (in) | Y (in) | Heading (°) | Speed limit (in/s) | Wait |\n";
+    report += "|---|---|---|---|---|---|---|\n";
+    for (i, point) in waypoints.iter().enumerate() {
+        let wait = match point.wait {
+            None => String::new(),
+            Some(Wait::Time(seconds)) => format!("{seconds} s"),
+            Some(Wait::Until(condition)) => format!("until `{}`", condition.replace('|', "\\|")),
+        };
+        report += &format!(
+            "| {} | {} | {:.2} | {:.2} | {} | {} | {wait} |\n",
+            i + 1,
+            point.name.replace('|', "\\|"),
+            point.x,
+            point.y,
+            point.heading.map_or(String::new(), |h| format!("{h:.1}")),
+            point.speed.map_or(String::new(), |v| format!("{v:.1}")),
+        );
+    }
+    report
+}
@@ -0,0 +1,695 @@
+use crate::action::Action;
+use crate::app::NamedPath;
+use crate::bezier::SavePoint;
+use crate::constraint::Constraint;
+use crate::expr::Variable;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Everything needed to restore a project on another machine.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default)]
+pub struct Project {
+    #[serde(default)]
+    pub paths: Vec<NamedPath>,
+    /// Index of the open path
+    #[serde(default)]
+    pub current: usize,
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    /// The only path's points, in projects pushed before they held several paths
+    #[serde(default, skip_serializing)]
+    points: Vec<SavePoint>,
+    #[serde(default, skip_serializing)]
+    constraints: Vec<Constraint>,
+}
+
+/// A part of a project that can be kept or replaced independently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    /// One path, with its constraints, by id
+    Path(Uuid),
+    Variables,
+    Actions,
+}
+
+impl Project {
+    pub fn new(
+        paths: Vec<NamedPath>,
+        current: usize,
+        variables: Vec<Variable>,
+        actions: Vec<Action>,
+    ) -> Self {
+        Self {
+            paths,
+            current,
+            variables,
+            actions,
+            ..Default::default()
+        }
+    }
+    /// Reads a pushed project, moving the path of one pushed before projects held several into a path of its own.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut project: Self = ron::from_str(text).map_err(|e| e.to_string())?;
+        if project.paths.is_empty() {
+            project.paths.push(NamedPath {
+                name: "Path 1".into(),
+                points: std::mem::take(&mut project.points),
+                constraints: std::mem::take(&mut project.constraints),
+                ..Default::default()
+            });
+        }
+        project.current = project.current.min(project.paths.len() - 1);
+        for path in &mut project.paths {
+            path.profile.clamp();
+        }
+        Ok(project)
+    }
+    fn path(&self, id: Uuid) -> Option<&NamedPath> {
+        self.paths.iter().find(|p| p.id == id)
+    }
+    /// A name for a part, looking in this version and `other` for paths.
+    pub fn label(&self, part: Part, other: &Project) -> String {
+        match part {
+            Part::Path(id) => self
+                .path(id)
+                .or_else(|| other.path(id))
+                .map_or("Path".into(), |p| p.name.clone()),
+            Part::Variables => "Variables".into(),
+            Part::Actions => "Actions".into(),
+        }
+    }
+    /// A short description of a part, used to compare versions.
+    pub fn summary(&self, part: Part) -> String {
+        match part {
+            Part::Path(id) => match self.path(id) {
+                Some(path) => format!(
+                    "{} points, {} constraints",
+                    path.points.len(),
+                    path.constraints.len()
+                ),
+                None => "missing".into(),
+            },
+            Part::Variables => format!("{} variables", self.variables.len()),
+            Part::Actions => format!("{} actions", self.actions.len()),
+        }
+    }
+    /// The parts that differ from another version of the project, paths in this version's order first.
+    pub fn differences(&self, other: &Project) -> Vec<Part> {
+        let mut ids: Vec<Uuid> = self.paths.iter().map(|p| p.id).collect();
+        for path in &other.paths {
+            if !ids.contains(&path.id) {
+                ids.push(path.id);
+            }
+        }
+        let paths = ids.into_iter().map(|id| {
+            let differs = match (self.path(id), other.path(id)) {
+                (Some(a), Some(b)) => !same_path(a, b),
+                _ => true,
+            };
+            (Part::Path(id), differs)
+        });
+        paths
+            .chain([
+                (
+                    Part::Variables,
+                    text(&self.variables) != text(&other.variables),
+                ),
+                (Part::Actions, text(&self.actions) != text(&other.actions)),
+            ])
+            .filter(|(_, differs)| *differs)
+            .map(|(part, _)| part)
+            .collect()
+    }
+    /// Replaces the given parts with another version's. A path missing from the other version is removed,
+    /// and one only in the other version is added.
+    pub fn merge(mut self, other: Project, parts: &[Part]) -> Project {
+        for part in parts {
+            match *part {
+                Part::Path(id) => {
+                    let theirs = other.path(id).cloned();
+                    match (self.paths.iter().position(|p| p.id == id), theirs) {
+                        (Some(i), Some(path)) => self.paths[i] = path,
+                        (Some(i), None) => {
+                            self.paths.remove(i);
+                        }
+                        (None, Some(path)) => self.paths.push(path),
+                        (None, None) => {}
+                    }
+                }
+                Part::Variables => self.variables = other.variables.clone(),
+                Part::Actions => self.actions = other.actions.clone(),
+            }
+        }
+        self
+    }
+}
+
+/// Whether two versions of a path are the same, ignoring when they were changed.
+fn same_path(a: &NamedPath, b: &NamedPath) -> bool {
+    let modified = |p: &NamedPath| NamedPath {
+        modified: 0,
+        ..p.clone()
+    };
+    text(&modified(a)) == text(&modified(b))
+}
+
+/// Serialized form of a value, for comparing versions.
+fn text(value: &impl serde::Serialize) -> String {
+    ron::to_string(value).unwrap_or_default()
+}
+
+/// Longest a request to a sync server may take before it's given up on, in seconds.
+#[cfg(target_arch = "wasm32")]
+const TIMEOUT: u32 = 10;
+
+/// The result of a sync request, filled in when it finishes in the background.
+pub struct Pending<T>(Arc<Mutex<Option<Result<T, String>>>>);
+
+impl<T> Clone for Pending<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Pending<T> {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(None)))
+    }
+    fn ready(result: Result<T, String>) -> Self {
+        let pending = Self::new();
+        pending.finish(result);
+        pending
+    }
+    fn finish(&self, result: Result<T, String>) {
+        if let Ok(mut slot) = self.0.lock() {
+            *slot = Some(result);
+        }
+    }
+    /// Takes the result, once the request has finished.
+    pub fn take(&self) -> Option<Result<T, String>> {
+        self.0.lock().ok()?.take()
+    }
+}
+
+/// A push or pull waiting on the sync backend.
+pub enum Transfer {
+    Push(Pending<()>),
+    /// The stored project's text, or `None` if there is none by that name
+    Pull(Pending<Option<String>>),
+}
+
+/// Somewhere projects can be stored and fetched by name.
+/// Requests run in the background, and repaint `ctx` when they finish.
+pub trait Adapter {
+    /// Reads a stored project, or `None` if there is none by that name.
+    fn load(&self, name: &str, ctx: &egui::Context) -> Pending<Option<String>>;
+    /// Stores a project, replacing any with the same name.
+    fn store(&self, name: &str, contents: String, ctx: &egui::Context) -> Pending<()>;
+}
+
+/// The configured storage backend.
+/// Servers are only reached from the browser, which provides the HTTPS client.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub enum Backend {
+    /// This machine only
+    #[default]
+    Local,
+    /// A WebDAV server, or any that stores files with plain `GET` and `PUT` requests
+    Remote {
+        /// Folder URL projects are stored under
+        url: String,
+        /// Basic auth credentials, left empty for public endpoints
+        username: String,
+        /// Kept only until Pathy closes, so it isn't written anywhere
+        #[serde(skip)]
+        password: String,
+    },
+    /// An S3-compatible object store, such as AWS S3, MinIO or Cloudflare R2
+    S3 {
+        /// Service URL, like `https://s3.us-east-1.amazonaws.com`
+        endpoint: String,
+        /// Signing region, `auto` for stores that ignore it
+        region: String,
+        bucket: String,
+        access_key: String,
+        /// Kept only until Pathy closes, so it isn't written anywhere
+        #[serde(skip)]
+        secret_key: String,
+    },
+}
+
+impl Backend {
+    /// Whether this backend stores projects on a server.
+    pub fn remote(&self) -> bool {
+        !matches!(self, Backend::Local)
+    }
+    /// The adapter for this backend, refusing to send credentials where they could be read on the way.
+    #[cfg(target_arch = "wasm32")]
+    pub fn adapter(&self) -> Result<Box<dyn Adapter>, String> {
+        match self {
+            Backend::Local => Ok(Box::new(Local)),
+            Backend::Remote {
+                url,
+                username,
+                password,
+            } => {
+                let credentials = !username.is_empty() || !password.is_empty();
+                if credentials && !url.starts_with("https://") {
+                    return Err("Credentials are only sent to https:// servers".into());
+                }
+                Ok(Box::new(Remote {
+                    url: url.clone(),
+                    username: username.clone(),
+                    password: password.clone(),
+                }))
+            }
+            Backend::S3 {
+                endpoint,
+                region,
+                bucket,
+                access_key,
+                secret_key,
+            } => {
+                if !endpoint.starts_with("https://") {
+                    return Err("Credentials are only sent to https:// servers".into());
+                }
+                if bucket.is_empty() || access_key.is_empty() || secret_key.is_empty() {
+                    return Err("Enter a bucket, access key and secret key".into());
+                }
+                Ok(Box::new(S3 {
+                    endpoint: endpoint.trim_end_matches('/').into(),
+                    region: region.clone(),
+                    bucket: bucket.clone(),
+                    access_key: access_key.clone(),
+                    secret_key: secret_key.clone(),
+                }))
+            }
+        }
+    }
+    /// The adapter for this backend. There is no HTTPS client outside the browser,
+    /// so only local storage is available.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn adapter(&self) -> Result<Box<dyn Adapter>, String> {
+        match self {
+            Backend::Local => Ok(Box::new(Local)),
+            _ => Err("Servers can only be synced with from the web version of Pathy".into()),
+        }
+    }
+}
+
+/// A project name made safe to use as a file name or URL path segment,
+/// by percent-encoding everything but letters, digits and `-_.~`.
+fn encode(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Stores projects in browser local storage on the web, or the app's storage directory on native.
+pub struct Local;
+
+#[cfg(target_arch = "wasm32")]
+impl Local {
+    fn storage() -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .ok_or("No window")?
+            .local_storage()
+            .map_err(|e| format!("{e:?}"))?
+            .ok_or_else(|| "Local storage is unavailable".into())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Adapter for Local {
+    fn load(&self, name: &str, _ctx: &egui::Context) -> Pending<Option<String>> {
+        Pending::ready(Self::storage().and_then(|storage| {
+            storage
+                .get_item(&format!("pathy-project-{name}"))
+                .map_err(|e| format!("{e:?}"))
+        }))
+    }
+    fn store(&self, name: &str, contents: String, _ctx: &egui::Context) -> Pending<()> {
+        Pending::ready(Self::storage().and_then(|storage| {
+            storage
+                .set_item(&format!("pathy-project-{name}"), &contents)
+                .map_err(|e| format!("{e:?}"))
+        }))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Local {
+    fn path(name: &str) -> std::path::PathBuf {
+        eframe::storage_dir("pathy")
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join("projects")
+            .join(format!("{}.ron", encode(name)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Adapter for Local {
+    fn load(&self, name: &str, _ctx: &egui::Context) -> Pending<Option<String>> {
+        Pending::ready(match std::fs::read_to_string(Self::path(name)) {
+            Ok(text) => Ok(Some(text)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        })
+    }
+    fn store(&self, name: &str, contents: String, _ctx: &egui::Context) -> Pending<()> {
+        let path = Self::path(name);
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, contents))
+            .map_err(|e| e.to_string());
+        Pending::ready(result)
+    }
+}
+
+/// Stores projects on a WebDAV server as `<url>/<name>.ron`.
+#[cfg(target_arch = "wasm32")]
+pub struct Remote {
+    url: String,
+    username: String,
+    password: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Remote {
+    fn location(&self, name: &str) -> String {
+        format!("{}/{}.ron", self.url.trim_end_matches('/'), encode(name))
+    }
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        use base64::Engine;
+
+        if self.username.is_empty() && self.password.is_empty() {
+            return Vec::new();
+        }
+        let credentials = format!("{}:{}", self.username, self.password);
+        vec![(
+            "Authorization",
+            format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            ),
+        )]
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Adapter for Remote {
+    fn load(&self, name: &str, ctx: &egui::Context) -> Pending<Option<String>> {
+        let pending = Pending::new();
+        let done = pending.clone();
+        request(
+            "GET",
+            self.location(name),
+            self.headers(),
+            None,
+            ctx,
+            move |response| done.finish(response.and_then(loaded)),
+        );
+        pending
+    }
+    fn store(&self, name: &str, contents: String, ctx: &egui::Context) -> Pending<()> {
+        let pending = Pending::new();
+        let done = pending.clone();
+        request(
+            "PUT",
+            self.location(name),
+            self.headers(),
+            Some(contents),
+            ctx,
+            move |response| done.finish(response.and_then(stored)),
+        );
+        pending
+    }
+}
+
+/// Stores projects in an S3 bucket as `<name>.ron`, signing requests with AWS Signature Version 4.
+/// Objects are addressed by path, `<endpoint>/<bucket>/<name>.ron`, which every S3-compatible store accepts.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone)]
+pub struct S3 {
+    endpoint: String,
+    region: String,
+    bucket: String,
+    access_key: String,
+    secret_key: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl S3 {
+    /// Signs and sends a request for a project in the background.
+    fn send(
+        &self,
+        method: &'static str,
+        name: &str,
+        body: Option<String>,
+        ctx: &egui::Context,
+        done: impl FnOnce(Result<(u16, String), String>) + 'static,
+    ) {
+        let rest = self.endpoint.trim_start_matches("https://");
+        let (host, base) = rest.split_once('/').unwrap_or((rest, ""));
+        let base = if base.is_empty() {
+            String::new()
+        } else {
+            format!("/{base}")
+        };
+        let path = format!("{base}/{}/{}.ron", encode(&self.bucket), encode(name));
+        let url = format!("https://{host}{path}");
+        let (s3, host, ctx) = (self.clone(), host.to_string(), ctx.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            let payload = body.as_deref().unwrap_or_default();
+            match s3.sign(method, &host, &path, payload).await {
+                Ok(headers) => request(method, url, headers, body, &ctx, done),
+                Err(e) => {
+                    done(Err(e));
+                    ctx.request_repaint();
+                }
+            }
+        });
+    }
+    /// The headers authorizing a request, as described in
+    /// <https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html>.
+    /// The browser sends `Host` itself, but it is still part of the signature.
+    async fn sign(
+        &self,
+        method: &str,
+        host: &str,
+        path: &str,
+        payload: &str,
+    ) -> Result<Vec<(&'static str, String)>, String> {
+        // ISO 8601 basic format, like 20130524T000000Z
+        let time: String = String::from(js_sys::Date::new_0().to_iso_string())
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .take(15)
+            .chain(['Z'])
+            .collect();
+        let date = &time[..8];
+        let payload_hash = hex(&crypto::sha256(payload.as_bytes()).await?);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{time}\n\n{signed_headers}\n{payload_hash}"
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{time}\n{scope}\n{}",
+            hex(&crypto::sha256(canonical.as_bytes()).await?)
+        );
+        let mut key = format!("AWS4{}", self.secret_key).into_bytes();
+        for part in [date, &self.region, "s3", "aws4_request"] {
+            key = crypto::hmac(&key, part.as_bytes()).await?;
+        }
+        let signature = hex(&crypto::hmac(&key, string_to_sign.as_bytes()).await?);
+        Ok(vec![
+            ("x-amz-content-sha256", payload_hash),
+            ("x-amz-date", time),
+            (
+                "Authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                    self.access_key
+                ),
+            ),
+        ])
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Adapter for S3 {
+    fn load(&self, name: &str, ctx: &egui::Context) -> Pending<Option<String>> {
+        let pending = Pending::new();
+        let done = pending.clone();
+        self.send("GET", name, None, ctx, move |response| {
+            done.finish(response.and_then(loaded))
+        });
+        pending
+    }
+    fn store(&self, name: &str, contents: String, ctx: &egui::Context) -> Pending<()> {
+        let pending = Pending::new();
+        let done = pending.clone();
+        self.send("PUT", name, Some(contents), ctx, move |response| {
+            done.finish(response.and_then(stored))
+        });
+        pending
+    }
+}
+
+/// The project in a response to a `GET`, or `None` if the server has none by that name.
+#[cfg(target_arch = "wasm32")]
+fn loaded((status, body): (u16, String)) -> Result<Option<String>, String> {
+    match status {
+        200..=299 => Ok(Some(body)),
+        404 => Ok(None),
+        status => Err(format!("Server responded with {status}")),
+    }
+}
+
+/// Whether a `PUT` was accepted.
+#[cfg(target_arch = "wasm32")]
+fn stored((status, _): (u16, String)) -> Result<(), String> {
+    match status {
+        200..=299 => Ok(()),
+        status => Err(format!("Server responded with {status}")),
+    }
+}
+
+/// Lowercase hexadecimal, as used in request signatures.
+#[cfg(target_arch = "wasm32")]
+fn hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+/// Hashing with the browser's Web Crypto API.
+#[cfg(target_arch = "wasm32")]
+mod crypto {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    fn error(e: JsValue) -> String {
+        format!("{e:?}")
+    }
+    fn subtle() -> Result<web_sys::SubtleCrypto, String> {
+        Ok(web_sys::window()
+            .ok_or("No window")?
+            .crypto()
+            .map_err(error)?
+            .subtle())
+    }
+    /// The SHA-256 digest of `data`.
+    pub async fn sha256(data: &[u8]) -> Result<Vec<u8>, String> {
+        let promise = subtle()?
+            .digest_with_str_and_u8_array("SHA-256", data)
+            .map_err(error)?;
+        let digest = JsFuture::from(promise).await.map_err(error)?;
+        Ok(js_sys::Uint8Array::new(&digest).to_vec())
+    }
+    /// The HMAC-SHA256 of `data` with `key`.
+    pub async fn hmac(key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+        let subtle = subtle()?;
+        let algorithm = js_sys::Object::new();
+        for (field, value) in [("name", "HMAC"), ("hash", "SHA-256")] {
+            js_sys::Reflect::set(&algorithm, &field.into(), &value.into()).map_err(error)?;
+        }
+        let usages = js_sys::Array::of1(&"sign".into());
+        let promise = subtle
+            .import_key_with_object(
+                "raw",
+                &js_sys::Uint8Array::from(key),
+                &algorithm,
+                false,
+                &usages,
+            )
+            .map_err(error)?;
+        let key: web_sys::CryptoKey = JsFuture::from(promise)
+            .await
+            .map_err(error)?
+            .unchecked_into();
+        let promise = subtle
+            .sign_with_str_and_u8_array("HMAC", &key, data)
+            .map_err(error)?;
+        let signature = JsFuture::from(promise).await.map_err(error)?;
+        Ok(js_sys::Uint8Array::new(&signature).to_vec())
+    }
+}
+
+/// Sends an HTTP request in the background, calling `done` with the status and body.
+#[cfg(target_arch = "wasm32")]
+fn request(
+    method: &str,
+    url: String,
+    headers: Vec<(&'static str, String)>,
+    body: Option<String>,
+    ctx: &egui::Context,
+    done: impl FnOnce(Result<(u16, String), String>) + 'static,
+) {
+    use std::{cell::RefCell, rc::Rc};
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    let error = |e: wasm_bindgen::JsValue| format!("{e:?}");
+    let xhr = match web_sys::XmlHttpRequest::new() {
+        Ok(xhr) => xhr,
+        Err(e) => return done(Err(error(e))),
+    };
+    let sent = xhr
+        .open_with_async(method, &url, true)
+        .and_then(|_| {
+            headers
+                .iter()
+                .try_for_each(|(name, value)| xhr.set_request_header(name, value))
+        })
+        .map_err(error);
+    if let Err(e) = sent {
+        return done(Err(e));
+    }
+    xhr.set_timeout(TIMEOUT * 1000);
+    // Shared with the send below, since the request never ends if it can't be sent
+    let done = Rc::new(RefCell::new(Some(done)));
+    let ended = done.clone();
+    let target = xhr.clone();
+    let repaint = ctx.clone();
+    // Fires once however the request ends, whether loaded, failed or timed out
+    let onloadend = Closure::once_into_js(move || {
+        let result = match target.status() {
+            // Browsers hide why a request failed
+            Ok(0) => Err(
+                "The server couldn't be reached, took too long, or doesn't allow Pathy (CORS)"
+                    .into(),
+            ),
+            Ok(status) => Ok((
+                status,
+                target.response_text().ok().flatten().unwrap_or_default(),
+            )),
+            Err(e) => Err(error(e)),
+        };
+        let done = ended.borrow_mut().take();
+        if let Some(done) = done {
+            done(result);
+        }
+        repaint.request_repaint();
+    });
+    xhr.set_onloadend(Some(onloadend.unchecked_ref()));
+    if let Err(e) = xhr.send_with_opt_str(body.as_deref()) {
+        log::warn!("Could not send the sync request: {e:?}");
+        let done = done.borrow_mut().take();
+        if let Some(done) = done {
+            done(Err(error(e)));
+        }
+        ctx.request_repaint();
+    }
+}
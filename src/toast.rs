@@ -0,0 +1,47 @@
+use egui::{Align2, Context, Frame, Id};
+
+/// How long a toast stays on screen, in seconds.
+const DURATION: f64 = 4.0;
+
+/// A short-lived notification.
+struct Toast {
+    text: String,
+    // Set the first time the toast is shown
+    expires: Option<f64>,
+}
+
+/// Notifications shown in the bottom corner of the screen.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    /// Queues a notification.
+    pub fn add(&mut self, text: impl Into<String>) {
+        self.toasts.push(Toast {
+            text: text.into(),
+            expires: None,
+        });
+    }
+    /// Draws active notifications and drops expired ones.
+    pub fn show(&mut self, ctx: &Context) {
+        let now = ctx.input(|i| i.time);
+        self.toasts
+            .retain(|toast| toast.expires.map_or(true, |expires| expires > now));
+        if self.toasts.is_empty() {
+            return;
+        }
+        egui::Area::new(Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .show(ctx, |ui| {
+                for toast in &mut self.toasts {
+                    toast.expires.get_or_insert(now + DURATION);
+                    Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.label(&toast.text);
+                    });
+                }
+            });
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(0.25));
+    }
+}
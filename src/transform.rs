@@ -0,0 +1,150 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::bezier::{BezPoint, Pose};
+use crate::constraint::Constraint;
+use egui::{pos2, vec2, Pos2, Vec2};
+
+/// A field center line to reflect across.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// The vertical center line, swapping left and right
+    Vertical,
+    /// The horizontal center line, swapping top and bottom
+    Horizontal,
+}
+
+/// Reflects every point across a center line of a field with the given size.
+pub fn mirror(
+    points: &[Rc<RefCell<BezPoint>>],
+    constraints: &mut [Constraint],
+    axis: Axis,
+    size: f32,
+) {
+    for point in points {
+        let mut point = point.borrow_mut();
+        point.transform(|p| reflect(p, axis, size));
+        point.heading = point.heading.map(|h| reflect_heading(h, axis));
+    }
+    for constraint in constraints {
+        constraint.transform_offset(|v| match axis {
+            Axis::Vertical => vec2(-v.x, v.y),
+            Axis::Horizontal => vec2(v.x, -v.y),
+        });
+    }
+}
+
+/// Reflects a pose across a center line, the way [`mirror`] reflects points.
+pub fn mirror_pose(pose: &mut Pose, axis: Axis, size: f32) {
+    pose.pos = reflect(pose.pos, axis, size);
+    pose.heading = reflect_heading(pose.heading, axis);
+}
+
+/// A position reflected across a center line of a field with the given size.
+fn reflect(p: Pos2, axis: Axis, size: f32) -> Pos2 {
+    match axis {
+        Axis::Vertical => pos2(size - p.x, p.y),
+        Axis::Horizontal => pos2(p.x, size - p.y),
+    }
+}
+
+/// A heading in degrees reflected across a center line.
+pub fn reflect_heading(heading: f32, axis: Axis) -> f32 {
+    match axis {
+        Axis::Vertical => normalize(180.0 - heading),
+        Axis::Horizontal => normalize(-heading),
+    }
+}
+
+/// Shifts every point by `dx`, `dy`.
+pub fn translate(points: &[Rc<RefCell<BezPoint>>], dx: f32, dy: f32) {
+    for point in points {
+        point.borrow_mut().translate(dx, dy);
+    }
+}
+
+/// Rotates every point clockwise by `degrees` about `pivot`.
+pub fn rotate<'a>(
+    points: &[Rc<RefCell<BezPoint>>],
+    constraints: impl IntoIterator<Item = &'a mut Constraint>,
+    degrees: f32,
+    pivot: Pos2,
+) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    // y points down, so this turns clockwise on screen
+    let turn = |v: Vec2| vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos);
+    for point in points {
+        let mut point = point.borrow_mut();
+        point.transform(|p| pivot + turn(p - pivot));
+        point.heading = point.heading.map(|h| normalize(h + degrees));
+    }
+    for constraint in constraints {
+        constraint.transform_offset(turn);
+    }
+}
+
+/// Rotates a pose clockwise by `degrees` about `pivot`, the way [`rotate`] turns points.
+pub fn rotate_pose(pose: &mut Pose, degrees: f32, pivot: Pos2) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let v = pose.pos - pivot;
+    pose.pos = pivot + vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos);
+    pose.heading = normalize(pose.heading + degrees);
+}
+
+/// Wraps an angle in degrees into (-180, 180].
+pub fn normalize(degrees: f32) -> f32 {
+    let wrapped = degrees.rem_euclid(360.0);
+    if wrapped > 180.0 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+/// Moves a point near a field wall to exactly `offset` from it, with its handles parallel to the wall,
+/// like a robot squared up against it.
+///
+/// # Arguments
+/// * `size` - Field size.
+/// * `reach` - How close to the nearest wall the point must be to snap.
+/// * `offset` - Distance from the wall to the point, usually half the robot's width.
+///
+/// # Returns
+/// Whether the point snapped.
+pub fn snap_to_wall(point: &mut BezPoint, size: f32, reach: f32, offset: f32) -> bool {
+    let pos = Pos2::from(point.pos.borrow().clone());
+    let walls = [
+        (pos.x, vec2(offset - pos.x, 0.0), vec2(0.0, 1.0)),
+        (
+            size - pos.x,
+            vec2(size - offset - pos.x, 0.0),
+            vec2(0.0, 1.0),
+        ),
+        (pos.y, vec2(0.0, offset - pos.y), vec2(1.0, 0.0)),
+        (
+            size - pos.y,
+            vec2(0.0, size - offset - pos.y),
+            vec2(1.0, 0.0),
+        ),
+    ];
+    let Some((_, shift, along)) = walls
+        .into_iter()
+        .filter(|(distance, ..)| *distance <= reach)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+    else {
+        return false;
+    };
+    point.translate(shift.x, shift.y);
+    let pos = pos + shift;
+    // Keep the handles' lengths and which way along the wall the path heads
+    let exit = Pos2::from(point.cp2.borrow().clone()) - pos;
+    let heading = if exit.dot(along) < 0.0 { -along } else { along };
+    for (handle, sign) in [(&point.cp1, -1.0), (&point.cp2, 1.0)] {
+        let mut handle = handle.borrow_mut();
+        let len = (Pos2::from(handle.clone()) - pos).length();
+        Pos2 {
+            x: handle.x,
+            y: handle.y,
+        } = pos + heading * sign * len;
+    }
+    true
+}
@@ -0,0 +1,111 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::app::CODE_SAMPLES_RANGE;
+use crate::bezier::{BezPoint, HandleMode};
+use egui::{Pos2, Vec2};
+use uuid::Uuid;
+
+/// Handles shorter than this count as collapsed onto their anchor.
+const MIN_HANDLE: f32 = 1e-3;
+
+/// Checks a path for states that break drawing or code generation, repairing what can be
+/// repaired without guessing at the user's intent.
+///
+/// * Points with non-finite anchors are removed.
+/// * Non-finite or collapsed handles are pointed along the path, matching the other handle if it is usable.
+///   Sharp points are meant to have collapsed handles, so they are left alone.
+/// * Repeated ids are replaced.
+/// * Segment sample counts are brought into range, since a count of zero divides by zero in generated code.
+///
+/// Anchors outside the field and repeated anchors are only reported.
+///
+/// # Returns
+/// A message for every problem found.
+pub fn check(points: &mut Vec<Rc<RefCell<BezPoint>>>, size: f32) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let before = points.len();
+    points.retain(|p| {
+        let p = p.borrow();
+        let pos = p.pos.borrow();
+        pos.x.is_finite() && pos.y.is_finite()
+    });
+    if points.len() < before {
+        problems.push(format!(
+            "Removed {} points with invalid positions",
+            before - points.len()
+        ));
+    }
+
+    let anchors: Vec<Pos2> = points
+        .iter()
+        .map(|p| Pos2::from(p.borrow().pos.borrow().clone()))
+        .collect();
+    let mut ids: Vec<Uuid> = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        let mut point = point.borrow_mut();
+        let anchor = anchors[i];
+        if !(0.0..=size).contains(&anchor.x) || !(0.0..=size).contains(&anchor.y) {
+            problems.push(format!("Point {} is outside the field", i + 1));
+        }
+        if i > 0 && anchors[i - 1].distance(anchor) < MIN_HANDLE {
+            problems.push(format!("Points {i} and {} are on top of each other", i + 1));
+        }
+        if ids.contains(&point.id) {
+            point.id = Uuid::new_v4();
+            problems.push(format!("Point {} had a repeated id", i + 1));
+        }
+        ids.push(point.id);
+        if let Some(steps) = point.steps.filter(|n| !CODE_SAMPLES_RANGE.contains(n)) {
+            point.steps = Some(steps.clamp(*CODE_SAMPLES_RANGE.start(), *CODE_SAMPLES_RANGE.end()));
+            problems.push(format!("Point {} had an invalid sample count", i + 1));
+        }
+
+        if point.mode != HandleMode::Sharp && repair(&point, &anchors, i) {
+            problems.push(format!("Repaired collapsed handles on point {}", i + 1));
+        }
+    }
+    problems
+}
+
+/// Points the handles of point `i` along the path if they are non-finite or collapsed,
+/// such as after it stops being a sharp corner. Returns whether they were changed.
+pub fn open_handles(points: &[Rc<RefCell<BezPoint>>], i: usize) -> bool {
+    let anchors: Vec<Pos2> = points
+        .iter()
+        .map(|p| Pos2::from(p.borrow().pos.borrow().clone()))
+        .collect();
+    repair(&points[i].borrow(), &anchors, i)
+}
+
+/// Rebuilds unusable handles on the point at `anchors[i]`, returning whether any were.
+fn repair(point: &BezPoint, anchors: &[Pos2], i: usize) -> bool {
+    let anchor = anchors[i];
+    // Direction of travel through the anchor, for rebuilding handles
+    let chord = match (i.checked_sub(1).map(|j| anchors[j]), anchors.get(i + 1)) {
+        (Some(prev), Some(&next)) => (next - prev) / 2.0,
+        (Some(prev), None) => anchor - prev,
+        (None, Some(&next)) => next - anchor,
+        (None, None) => Vec2::new(20.0, 0.0),
+    } / 3.0;
+    let chord = if chord.length() < MIN_HANDLE {
+        Vec2::new(10.0, 0.0)
+    } else {
+        chord
+    };
+    let usable = |v: Vec2| v.is_finite() && v.length() >= MIN_HANDLE;
+    let entry = Pos2::from(point.cp1.borrow().clone()) - anchor;
+    let exit = Pos2::from(point.cp2.borrow().clone()) - anchor;
+    let (entry, exit) = match (usable(entry), usable(exit)) {
+        (true, true) => return false,
+        (true, false) => (entry, -entry),
+        (false, true) => (-exit, exit),
+        (false, false) => (-chord, chord),
+    };
+    for (handle, offset) in [(&point.cp1, entry), (&point.cp2, exit)] {
+        let mut handle = handle.borrow_mut();
+        handle.x = anchor.x + offset.x;
+        handle.y = anchor.y + offset.y;
+    }
+    true
+}